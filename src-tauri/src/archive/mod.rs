@@ -0,0 +1,285 @@
+pub mod store;
+pub mod writer;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::audio::AudioFrame;
+use crate::history::{self, RetentionPolicy};
+use crate::settings::SettingsState;
+use crate::stt::SessionSummary;
+pub use store::{ArchiveDir, ArchivedSession};
+pub use writer::{ArchiveWriter, Sidecar};
+
+pub const RECORDING_ARCHIVED_EVENT: &str = "recording-archived";
+pub const RECORDING_DELETED_EVENT: &str = "recording-deleted";
+/// Payload: the number of recordings removed by retention or a wipe.
+pub const RECORDINGS_PURGED_EVENT: &str = "recordings-purged";
+
+/// Sub-directory of the app data dir holding the recordings.
+const RECORDINGS_DIR: &str = "recordings";
+
+/**
+ * Struct: ArchiveState
+ * Responsibility: The recordings directory, set once at startup. Unset if it could not
+ * be created; sessions are then not archived.
+ */
+#[derive(Default)]
+pub struct ArchiveState {
+    dir: OnceLock<ArchiveDir>,
+}
+
+impl ArchiveState {
+    fn dir(&self) -> Result<&ArchiveDir, String> {
+        self.dir
+            .get()
+            .ok_or_else(|| "Recordings are not available".into())
+    }
+}
+
+/**
+ * Struct: ArchiveRecording
+ * Responsibility: The task writing one session's audio, started alongside the
+ * transcription session and finished with its summary.
+ */
+pub struct ArchiveRecording {
+    app: AppHandle,
+    stop_tx: oneshot::Sender<()>,
+    task: JoinHandle<ArchiveWriter>,
+}
+
+/**
+ * Function: begin
+ * Starts archiving `frames` if recording audio is enabled in the settings and
+ * nothing prevents persisting this session (storing off, private session).
+ */
+pub fn begin(
+    app: &AppHandle,
+    mut frames: broadcast::Receiver<AudioFrame>,
+    started_at: i64,
+) -> Option<ArchiveRecording> {
+    let settings = app.state::<SettingsState>().get();
+    if !settings.archive_audio || !history::persistence_allowed(app) {
+        return None;
+    }
+    let policy = RetentionPolicy::from(&settings);
+    let mut writer = app
+        .state::<ArchiveState>()
+        .dir()
+        .ok()?
+        .writer(started_at, policy.archive_max_bytes);
+
+    let (stop_tx, mut stop_rx) = oneshot::channel();
+    let task = tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                frame = frames.recv() => match frame {
+                    Ok(frame) => {
+                        if let Err(e) = writer.push(&frame) {
+                            eprintln!("{e}");
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = &mut stop_rx => break,
+            }
+        }
+        writer
+    });
+    Some(ArchiveRecording {
+        app: app.clone(),
+        stop_tx,
+        task,
+    })
+}
+
+impl ArchiveRecording {
+    /**
+     * Function: finish
+     * Stops writing and stores the audio with its sidecar, unless persisting was
+     * ruled out in the meantime (e.g. a private session was started), in which case
+     * the audio is destroyed.
+     */
+    pub async fn finish(self, summary: &SessionSummary) {
+        let app = &self.app;
+        let _ = self.stop_tx.send(());
+        let Ok(writer) = self.task.await else {
+            return;
+        };
+        if !history::persistence_allowed(app) {
+            writer.discard();
+            return;
+        }
+
+        let state = app.state::<ArchiveState>();
+        match writer.finish(summary) {
+            Ok(Some(sidecar)) => {
+                if let Ok(Some(session)) = state.dir().and_then(|dir| dir.get(&sidecar.id)) {
+                    let _ = app.emit(RECORDING_ARCHIVED_EVENT, session);
+                }
+                history::enforce_retention(app);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
+    /// Stops writing and destroys the audio, for a session that ended without a summary.
+    pub async fn discard(self) {
+        let _ = self.stop_tx.send(());
+        if let Ok(writer) = self.task.await {
+            writer.discard();
+        }
+    }
+}
+
+/**
+ * Function: enforce_retention
+ * Applies the retention policy and size cap to the recordings. Called by
+ * `history::enforce_retention`, which owns the schedule.
+ */
+pub fn enforce_retention(app: &AppHandle, policy: &RetentionPolicy, now_ms: i64) {
+    let state = app.state::<ArchiveState>();
+    let Ok(dir) = state.dir() else {
+        return;
+    };
+    match dir.enforce(policy, now_ms) {
+        Ok(0) => {}
+        Ok(removed) => {
+            let _ = app.emit(RECORDINGS_PURGED_EVENT, removed);
+        }
+        Err(e) => eprintln!("Cannot apply recording retention: {e}"),
+    }
+}
+
+/// Deletes every recording; part of `wipe_history`.
+pub fn wipe(app: &AppHandle) -> Result<u64, String> {
+    let state = app.state::<ArchiveState>();
+    let Ok(dir) = state.dir() else {
+        return Ok(0);
+    };
+    let removed = dir.wipe()?;
+    let _ = app.emit(RECORDINGS_PURGED_EVENT, removed);
+    Ok(removed)
+}
+
+/**
+ * Function: setup
+ * Responsibility: Creates the recordings directory and destroys audio left by sessions
+ * that never finished. Called once from the Tauri `setup` hook, before the history.
+ */
+pub fn setup(app: &AppHandle) {
+    let created = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| {
+            let dir = dir.join(RECORDINGS_DIR);
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            Ok(dir)
+        });
+    match created {
+        Ok(dir) => {
+            let dir = ArchiveDir::new(dir);
+            dir.remove_partial();
+            let _ = app.state::<ArchiveState>().dir.set(dir);
+        }
+        Err(e) => eprintln!("Recordings disabled: {e}"),
+    }
+}
+
+/**
+ * Command: list_recordings
+ * Responsibility: Archived sessions, newest first.
+ */
+#[tauri::command]
+pub fn list_recordings(state: State<'_, ArchiveState>) -> Result<Vec<ArchivedSession>, String> {
+    state.dir()?.list()
+}
+
+/**
+ * Command: get_recording
+ * Responsibility: One archived session by id, or `null` if it no longer exists.
+ */
+#[tauri::command]
+pub fn get_recording(
+    state: State<'_, ArchiveState>,
+    id: String,
+) -> Result<Option<ArchivedSession>, String> {
+    state.dir()?.get(&id)
+}
+
+/**
+ * Command: read_recording_audio
+ * Responsibility: The WAV bytes of a recording, for playback in the webview
+ * (`new Blob([bytes], { type: "audio/wav" })`).
+ */
+#[tauri::command]
+pub async fn read_recording_audio(
+    app: AppHandle,
+    id: String,
+) -> Result<tauri::ipc::Response, String> {
+    let path = app.state::<ArchiveState>().dir()?.audio_path(&id)?;
+    let bytes = fs::read(path).map_err(|e| format!("Cannot read recording: {e}"))?;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
+/**
+ * Command: export_recording
+ * Responsibility: Copies a recording to `destination` (a `.wav` path) and its sidecar
+ * next to it, as `.json` with the same name.
+ */
+#[tauri::command]
+pub async fn export_recording(
+    app: AppHandle,
+    id: String,
+    destination: String,
+) -> Result<(), String> {
+    let state = app.state::<ArchiveState>();
+    let dir = state.dir()?;
+    let session = dir.get(&id)?.ok_or("Recording not found")?;
+    let audio = PathBuf::from(destination);
+    if !audio
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+    {
+        return Err("Export to a file name ending in .wav".into());
+    }
+    let sidecar_path = audio.with_extension("json");
+
+    fs::copy(&session.audio_path, &audio).map_err(|e| format!("Cannot export recording: {e}"))?;
+    let sidecar = Sidecar {
+        audio_file: audio
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        ..session.sidecar
+    };
+    let json = serde_json::to_string_pretty(&sidecar).map_err(|e| e.to_string())?;
+    fs::write(sidecar_path, json).map_err(|e| format!("Cannot export recording details: {e}"))
+}
+
+/**
+ * Command: delete_recording
+ * Responsibility: Destroys one recording and its sidecar. Resolves with whether it
+ * existed.
+ */
+#[tauri::command]
+pub fn delete_recording(
+    app: AppHandle,
+    state: State<'_, ArchiveState>,
+    id: String,
+) -> Result<bool, String> {
+    let deleted = state.dir()?.delete(&id)?;
+    if deleted {
+        let _ = app.emit(RECORDING_DELETED_EVENT, &id);
+    }
+    Ok(deleted)
+}
@@ -0,0 +1,350 @@
+use std::cmp::Reverse;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::writer::{ArchiveWriter, Sidecar, PARTIAL_SUFFIX};
+use crate::history::RetentionPolicy;
+
+/// Zeros written at a time when shredding a file.
+const SHRED_CHUNK: usize = 64 * 1024;
+
+/**
+ * Struct: ArchivedSession
+ * A stored recording as listed to the webview: its sidecar, where the audio is, and
+ * how much space it takes.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedSession {
+    #[serde(flatten)]
+    pub sidecar: Sidecar,
+    pub audio_path: String,
+    pub size_bytes: u64,
+}
+
+/**
+ * Function: shred
+ * Overwrites a file with zeros before removing it, so the audio does not linger in
+ * freed disk blocks (on file systems that write in place).
+ */
+pub fn shred(path: &Path) -> io::Result<()> {
+    let len = fs::metadata(path)?.len();
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; SHRED_CHUNK];
+    let mut left = len;
+    while left > 0 {
+        let n = left.min(SHRED_CHUNK as u64) as usize;
+        file.write_all(&zeros[..n])?;
+        left -= n as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path)
+}
+
+/// Ids are the session's start time in Unix ms; anything else could name a file
+/// outside the archive.
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())
+}
+
+/**
+ * Struct: ArchiveDir
+ * Responsibility: The directory of recordings, one `<id>.wav` and `<id>.json` per
+ * session. Works on any directory, so it can be exercised without the app.
+ */
+pub struct ArchiveDir {
+    dir: PathBuf,
+}
+
+impl ArchiveDir {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn writer(&self, started_at: i64, max_bytes: u64) -> ArchiveWriter {
+        ArchiveWriter::new(&self.dir, started_at.to_string(), max_bytes)
+    }
+
+    fn paths(&self, id: &str) -> Result<(PathBuf, PathBuf), String> {
+        if !valid_id(id) {
+            return Err(format!("Invalid recording id: {id}"));
+        }
+        Ok((
+            self.dir.join(format!("{id}.wav")),
+            self.dir.join(format!("{id}.json")),
+        ))
+    }
+
+    fn read(&self, sidecar_path: &Path) -> Option<ArchivedSession> {
+        let sidecar: Sidecar =
+            serde_json::from_str(&fs::read_to_string(sidecar_path).ok()?).ok()?;
+        let (audio, _) = self.paths(&sidecar.id).ok()?;
+        let size_bytes = fs::metadata(&audio).ok()?.len();
+        Some(ArchivedSession {
+            audio_path: audio.to_string_lossy().into_owned(),
+            size_bytes,
+            sidecar,
+        })
+    }
+
+    /// Every complete recording, newest first.
+    pub fn list(&self) -> Result<Vec<ArchivedSession>, String> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Cannot read recordings: {e}")),
+        };
+        let mut sessions: Vec<ArchivedSession> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| self.read(&path))
+            .collect();
+        sessions.sort_by_key(|session| Reverse(session.sidecar.started_at));
+        Ok(sessions)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<ArchivedSession>, String> {
+        let (_, sidecar) = self.paths(id)?;
+        Ok(self.read(&sidecar))
+    }
+
+    /// Path of a recording's audio, if it exists.
+    pub fn audio_path(&self, id: &str) -> Result<PathBuf, String> {
+        let (audio, _) = self.paths(id)?;
+        if audio.is_file() {
+            Ok(audio)
+        } else {
+            Err("Recording not found".into())
+        }
+    }
+
+    /// Returns whether a recording was deleted.
+    pub fn delete(&self, id: &str) -> Result<bool, String> {
+        let (audio, sidecar) = self.paths(id)?;
+        let mut deleted = false;
+        for path in [audio, sidecar] {
+            match shred(&path) {
+                Ok(()) => deleted = true,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Cannot delete recording: {e}")),
+            }
+        }
+        Ok(deleted)
+    }
+
+    /**
+     * Function: enforce
+     * Deletes the recordings the policy no longer allows: all of them when storing is
+     * off, otherwise those past the age or count limit, then the oldest ones until the
+     * rest fit in `archive_max_bytes`. Returns how many were removed.
+     */
+    pub fn enforce(&self, policy: &RetentionPolicy, now_ms: i64) -> Result<u64, String> {
+        let cutoff = policy.cutoff(now_ms);
+        let mut used = 0;
+        let mut removed = 0;
+        for (index, session) in self.list()?.into_iter().enumerate() {
+            let mut expired = !policy.store
+                || cutoff.is_some_and(|cutoff| session.sidecar.started_at < cutoff)
+                || (policy.max_entries > 0 && index >= policy.max_entries as usize);
+            if !expired {
+                used += session.size_bytes;
+                expired = used > policy.archive_max_bytes;
+            }
+            if expired && self.delete(&session.sidecar.id)? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Deletes every finished recording. Returns how many there were.
+    pub fn wipe(&self) -> Result<u64, String> {
+        let sessions = self.list()?;
+        for session in &sessions {
+            self.delete(&session.sidecar.id)?;
+        }
+        Ok(sessions.len() as u64)
+    }
+
+    /// Removes audio left behind by sessions that never finished (e.g. a crash).
+    pub fn remove_partial(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+            if path.to_string_lossy().ends_with(PARTIAL_SUFFIX) {
+                let _ = shred(&path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioFrame;
+    use crate::stt::{FinalizeReason, SessionInfo, SessionSummary};
+
+    /// Stores a session of `frames` × 100 ms at 16 kHz (3.2 kB per frame).
+    fn record(archive: &ArchiveDir, started_at: i64, frames: u64) {
+        let mut writer = archive.writer(started_at, u64::MAX);
+        for index in 0..frames {
+            writer
+                .push(&AudioFrame {
+                    samples: vec![1; 1_600],
+                    sample_rate: 16_000,
+                    timestamp_ms: index * 100,
+                    is_speech: true,
+                })
+                .unwrap();
+        }
+        let summary = SessionSummary {
+            transcript: format!("Session {started_at}"),
+            segments: Vec::new(),
+            reason: FinalizeReason::FinalReceived,
+            session: SessionInfo {
+                started_at,
+                ..SessionInfo::default()
+            },
+        };
+        writer.finish(&summary).unwrap();
+    }
+
+    fn ids(archive: &ArchiveDir) -> Vec<String> {
+        archive
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|session| session.sidecar.id)
+            .collect()
+    }
+
+    #[test]
+    fn recordings_are_listed_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveDir::new(dir.path().to_path_buf());
+        record(&archive, 1_000, 1);
+        record(&archive, 3_000, 1);
+        record(&archive, 2_000, 1);
+
+        assert_eq!(ids(&archive), ["3000", "2000", "1000"]);
+        let session = archive.get("2000").unwrap().unwrap();
+        assert_eq!(session.sidecar.transcript, "Session 2000");
+        assert_eq!(
+            session.size_bytes,
+            fs::metadata(dir.path().join("2000.wav")).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn missing_directory_lists_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveDir::new(dir.path().join("recordings"));
+        assert!(archive.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn ids_cannot_leave_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveDir::new(dir.path().to_path_buf());
+        assert!(archive.get("../settings").is_err());
+        assert!(archive.delete("").is_err());
+        assert!(archive.audio_path("1.wav").is_err());
+    }
+
+    #[test]
+    fn delete_removes_audio_and_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveDir::new(dir.path().to_path_buf());
+        record(&archive, 1_000, 1);
+
+        assert_eq!(archive.delete("1000"), Ok(true));
+        assert_eq!(archive.delete("1000"), Ok(false));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn size_cap_removes_the_oldest_recordings() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveDir::new(dir.path().to_path_buf());
+        for started_at in [1_000, 2_000, 3_000] {
+            record(&archive, started_at, 10);
+        }
+        let size = archive.get("1000").unwrap().unwrap().size_bytes;
+        let policy = RetentionPolicy {
+            archive_max_bytes: 2 * size + size / 2,
+            ..RetentionPolicy::default()
+        };
+
+        assert_eq!(archive.enforce(&policy, 10_000), Ok(1));
+        assert_eq!(ids(&archive), ["3000", "2000"]);
+    }
+
+    #[test]
+    fn count_limit_and_storing_off_apply_to_recordings() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveDir::new(dir.path().to_path_buf());
+        for started_at in [1_000, 2_000, 3_000] {
+            record(&archive, started_at, 1);
+        }
+
+        let newest = RetentionPolicy {
+            max_entries: 1,
+            ..RetentionPolicy::default()
+        };
+        assert_eq!(archive.enforce(&newest, 10_000), Ok(2));
+        assert_eq!(ids(&archive), ["3000"]);
+
+        let off = RetentionPolicy {
+            store: false,
+            ..RetentionPolicy::default()
+        };
+        assert_eq!(archive.enforce(&off, 10_000), Ok(1));
+        assert!(ids(&archive).is_empty());
+    }
+
+    #[test]
+    fn remove_partial_only_removes_unfinished_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveDir::new(dir.path().to_path_buf());
+        record(&archive, 1_000, 1);
+        // A session that was still recording when the app went away.
+        let mut unfinished = archive.writer(2_000, u64::MAX);
+        unfinished
+            .push(&AudioFrame {
+                samples: vec![1; 1_600],
+                sample_rate: 16_000,
+                timestamp_ms: 0,
+                is_speech: true,
+            })
+            .unwrap();
+        std::mem::forget(unfinished);
+        assert!(dir
+            .path()
+            .join(format!("2000.wav{PARTIAL_SUFFIX}"))
+            .exists());
+
+        archive.remove_partial();
+        let mut left: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["1000.json", "1000.wav"]);
+    }
+
+    #[test]
+    fn wipe_removes_every_recording() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = ArchiveDir::new(dir.path().to_path_buf());
+        record(&archive, 1_000, 1);
+        record(&archive, 2_000, 1);
+
+        assert_eq!(archive.wipe(), Ok(2));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,271 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use hound::{SampleFormat, WavSpec, WavWriter};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioFrame;
+use crate::stt::{SessionSummary, TranscriptSegment};
+
+/// Size of the header `hound` writes before the samples.
+const WAV_HEADER_BYTES: u64 = 44;
+
+/// Suffix of audio still being recorded; renamed away once the session finishes.
+pub const PARTIAL_SUFFIX: &str = ".part";
+
+/**
+ * Struct: Sidecar
+ * The JSON file stored next to each recording: what was said according to the
+ * provider, and when. Times are Unix milliseconds; segment times are seconds into
+ * the audio.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sidecar {
+    pub id: String,
+    /// File name of the audio, in the same directory.
+    pub audio_file: String,
+    pub started_at: i64,
+    pub ended_at: i64,
+    /// Length of the stored audio.
+    pub audio_ms: u64,
+    pub sample_rate: u32,
+    pub provider: String,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub finalize_reason: String,
+    pub transcript: String,
+    pub segments: Vec<TranscriptSegment>,
+    /// The size cap was reached and the rest of the session was not stored.
+    pub truncated: bool,
+}
+
+/**
+ * Struct: ArchiveWriter
+ * Responsibility: Writes one session's frames to a 16-bit mono WAV file, stopping at
+ * `max_bytes`. The file is created with the first frame, so a session that captured
+ * nothing leaves nothing behind.
+ */
+pub struct ArchiveWriter {
+    id: String,
+    dir: PathBuf,
+    max_bytes: u64,
+    wav: Option<WavWriter<BufWriter<File>>>,
+    sample_rate: u32,
+    samples: u64,
+    truncated: bool,
+}
+
+impl ArchiveWriter {
+    pub fn new(dir: &Path, id: String, max_bytes: u64) -> Self {
+        Self {
+            id,
+            dir: dir.to_path_buf(),
+            max_bytes,
+            wav: None,
+            sample_rate: 0,
+            samples: 0,
+            truncated: false,
+        }
+    }
+
+    fn audio_file(&self) -> String {
+        format!("{}.wav", self.id)
+    }
+
+    fn partial_path(&self) -> PathBuf {
+        self.dir
+            .join(format!("{}{PARTIAL_SUFFIX}", self.audio_file()))
+    }
+
+    pub fn push(&mut self, frame: &AudioFrame) -> Result<(), String> {
+        if self.truncated {
+            return Ok(());
+        }
+        let room = self
+            .max_bytes
+            .saturating_sub(WAV_HEADER_BYTES + self.samples * 2)
+            / 2;
+        if room < frame.samples.len() as u64 {
+            self.truncated = true;
+            return Ok(());
+        }
+
+        if self.wav.is_none() {
+            let spec = WavSpec {
+                channels: 1,
+                sample_rate: frame.sample_rate,
+                bits_per_sample: 16,
+                sample_format: SampleFormat::Int,
+            };
+            let wav = WavWriter::create(self.partial_path(), spec)
+                .map_err(|e| format!("Cannot create recording: {e}"))?;
+            self.wav = Some(wav);
+            self.sample_rate = frame.sample_rate;
+        }
+        let Some(wav) = self.wav.as_mut() else {
+            return Ok(());
+        };
+        for &sample in &frame.samples {
+            wav.write_sample(sample)
+                .map_err(|e| format!("Cannot write recording: {e}"))?;
+        }
+        self.samples += frame.samples.len() as u64;
+        Ok(())
+    }
+
+    /**
+     * Function: finish
+     * Completes the WAV file and writes the sidecar for `summary`. Returns `None` when
+     * no audio was captured.
+     */
+    pub fn finish(mut self, summary: &SessionSummary) -> Result<Option<Sidecar>, String> {
+        let Some(wav) = self.wav.take() else {
+            return Ok(None);
+        };
+        wav.finalize()
+            .map_err(|e| format!("Cannot finish recording: {e}"))?;
+
+        let session = &summary.session;
+        let sidecar = Sidecar {
+            id: self.id.clone(),
+            audio_file: self.audio_file(),
+            started_at: session.started_at,
+            ended_at: session.started_at + session.duration_ms,
+            audio_ms: self.samples * 1000 / self.sample_rate.max(1) as u64,
+            sample_rate: self.sample_rate,
+            provider: session.provider.clone(),
+            model: session.model.clone(),
+            language: session.language.clone(),
+            finalize_reason: summary.reason.as_str().to_string(),
+            transcript: summary.transcript.clone(),
+            segments: summary.segments.clone(),
+            truncated: self.truncated,
+        };
+        fs::rename(self.partial_path(), self.dir.join(&sidecar.audio_file))
+            .map_err(|e| format!("Cannot store recording: {e}"))?;
+        let json = serde_json::to_string_pretty(&sidecar).map_err(|e| e.to_string())?;
+        fs::write(self.dir.join(format!("{}.json", self.id)), json)
+            .map_err(|e| format!("Cannot write recording details: {e}"))?;
+        Ok(Some(sidecar))
+    }
+
+    /// Drops the recording without keeping anything.
+    pub fn discard(mut self) {
+        if let Some(wav) = self.wav.take() {
+            drop(wav);
+            let _ = super::store::shred(&self.partial_path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stt::{FinalizeReason, SessionInfo};
+
+    const RATE: u32 = 16_000;
+
+    /// 100 ms of audio.
+    fn frame(index: u64) -> AudioFrame {
+        AudioFrame {
+            samples: vec![index as i16; RATE as usize / 10],
+            sample_rate: RATE,
+            timestamp_ms: index * 100,
+            is_speech: true,
+        }
+    }
+
+    fn summary() -> SessionSummary {
+        SessionSummary {
+            transcript: "Hello world.".into(),
+            segments: vec![TranscriptSegment {
+                text: "Hello world.".into(),
+                start: 0.0,
+                end: 0.5,
+            }],
+            reason: FinalizeReason::FinalReceived,
+            session: SessionInfo {
+                provider: "deepgram".into(),
+                model: None,
+                language: Some("en-US".into()),
+                started_at: 1_700_000_000_000,
+                duration_ms: 1_000,
+            },
+        }
+    }
+
+    fn files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn finished_session_keeps_audio_and_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = ArchiveWriter::new(dir.path(), "42".into(), u64::MAX);
+        for index in 0..5 {
+            writer.push(&frame(index)).unwrap();
+        }
+        assert_eq!(files(dir.path()), [format!("42.wav{PARTIAL_SUFFIX}")]);
+
+        let sidecar = writer.finish(&summary()).unwrap().unwrap();
+        assert_eq!(files(dir.path()), ["42.json", "42.wav"]);
+        assert_eq!(sidecar.audio_ms, 500);
+        assert_eq!(sidecar.ended_at, 1_700_000_001_000);
+        assert_eq!(sidecar.transcript, "Hello world.");
+        assert!(!sidecar.truncated);
+
+        let stored: Sidecar =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("42.json")).unwrap()).unwrap();
+        assert_eq!(stored, sidecar);
+        let wav = hound::WavReader::open(dir.path().join("42.wav")).unwrap();
+        assert_eq!(wav.spec().sample_rate, RATE);
+        assert_eq!(wav.len(), 5 * RATE / 10);
+    }
+
+    #[test]
+    fn size_cap_truncates_at_a_whole_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let frame_bytes = 2 * RATE as u64 / 10;
+        // Room for two and a half frames after the header.
+        let cap = WAV_HEADER_BYTES + 2 * frame_bytes + frame_bytes / 2;
+        let mut writer = ArchiveWriter::new(dir.path(), "7".into(), cap);
+        for index in 0..5 {
+            writer.push(&frame(index)).unwrap();
+        }
+
+        let sidecar = writer.finish(&summary()).unwrap().unwrap();
+        assert!(sidecar.truncated);
+        assert_eq!(sidecar.audio_ms, 200);
+        let size = fs::metadata(dir.path().join("7.wav")).unwrap().len();
+        assert!(size <= cap, "{size} bytes stored, cap is {cap}");
+    }
+
+    #[test]
+    fn session_without_audio_leaves_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = ArchiveWriter::new(dir.path(), "1".into(), u64::MAX);
+        assert_eq!(writer.finish(&summary()), Ok(None));
+
+        // A cap below one frame means nothing is ever written either.
+        let mut writer = ArchiveWriter::new(dir.path(), "2".into(), WAV_HEADER_BYTES);
+        writer.push(&frame(0)).unwrap();
+        assert_eq!(writer.finish(&summary()), Ok(None));
+        assert!(files(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn discarded_session_removes_its_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = ArchiveWriter::new(dir.path(), "3".into(), u64::MAX);
+        writer.push(&frame(0)).unwrap();
+        writer.discard();
+        assert!(files(dir.path()).is_empty());
+    }
+}
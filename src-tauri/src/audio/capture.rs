@@ -0,0 +1,451 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{
+    Device, FromSample, Sample, SampleFormat, SampleRate, SizedSample, Stream, StreamConfig,
+    StreamError, SupportedStreamConfig,
+};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast;
+
+use super::devices::find_input_device;
+use super::meter::{LevelMeter, AUDIO_LEVEL_EVENT};
+use super::vad::{self, Vad};
+use super::{
+    AudioFrame, CaptureConfig, CaptureInfo, CAPTURE_DEVICE_CHANGED_EVENT, CAPTURE_ERROR_EVENT,
+};
+
+const MIN_SAMPLE_RATE: u32 = 8_000;
+const MAX_SAMPLE_RATE: u32 = 48_000;
+
+/// Frames the callback may queue ahead of the capture thread (~5 s at 20 ms). When the
+/// queue is full the callback drops frames rather than wait.
+const CALLBACK_QUEUE_FRAMES: usize = 256;
+
+/**
+ * Enum: CaptureMessage
+ * What the cpal callbacks and the handle send to the capture thread.
+ */
+enum CaptureMessage {
+    Frame(AudioFrame),
+    StreamError(String),
+    /// The named device went away; reopen on the fallback if it is the one in use.
+    DeviceLost(String),
+    /// Wakes the thread so it notices `CaptureHandle::stopping`.
+    Stop,
+}
+
+/**
+ * Struct: CaptureHandle
+ * Responsibility: Owns the dedicated capture thread.
+ * cpal streams are not `Send`, so the stream is created, played and dropped on that thread.
+ * The real-time callback only builds frames and queues them without blocking; the thread
+ * runs the analysis, emits events and broadcasts the frames. If the device is lost, the
+ * thread reopens the stream on the fallback device and timestamps carry on.
+ */
+pub struct CaptureHandle {
+    info: Arc<Mutex<CaptureInfo>>,
+    messages: mpsc::SyncSender<CaptureMessage>,
+    /// Checked before every message, so a stop is seen even when the queue is too full
+    /// to take the `Stop` message.
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CaptureHandle {
+    pub fn spawn(
+        app: AppHandle,
+        device_name: Option<String>,
+        config: CaptureConfig,
+        frames: broadcast::Sender<AudioFrame>,
+    ) -> Result<Self, String> {
+        if !(MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE).contains(&config.sample_rate) {
+            return Err(format!(
+                "Sample rate must be between {MIN_SAMPLE_RATE} and {MAX_SAMPLE_RATE} Hz"
+            ));
+        }
+        if !(10..=1000).contains(&config.frame_ms) {
+            return Err("Frame length must be between 10 and 1000 ms".into());
+        }
+
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (messages, inbox) = mpsc::sync_channel(CALLBACK_QUEUE_FRAMES);
+        let callback_tx = messages.clone();
+        let stopping = Arc::new(AtomicBool::new(false));
+        let thread_stopping = stopping.clone();
+
+        let thread = thread::Builder::new()
+            .name("audio-capture".into())
+            .spawn(move || {
+                let opened = open_stream(device_name.as_deref(), &config, callback_tx.clone(), 0);
+                let (stream, info) = match opened {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let mut stream = Some(stream);
+                let mut analysis = FrameAnalysis::new(&app, &config, &info, frames);
+                let shared = Arc::new(Mutex::new(info));
+                let _ = ready_tx.send(Ok(shared.clone()));
+
+                let mut next_frame = 0;
+                // Runs until `stop()` is called or the handle is dropped.
+                for message in inbox {
+                    if thread_stopping.load(Ordering::Acquire) {
+                        break;
+                    }
+                    match message {
+                        CaptureMessage::Frame(frame) => {
+                            next_frame = frame.timestamp_ms / config.frame_ms as u64 + 1;
+                            analysis.handle(frame);
+                        }
+                        CaptureMessage::StreamError(e) => {
+                            let _ = app.emit(CAPTURE_ERROR_EVENT, e);
+                        }
+                        CaptureMessage::DeviceLost(name) => {
+                            if stream.is_none() || shared.lock().unwrap().device_name != name {
+                                continue;
+                            }
+                            drop(stream.take());
+                            let reopened = open_stream(
+                                device_name.as_deref(),
+                                &config,
+                                callback_tx.clone(),
+                                next_frame,
+                            );
+                            match reopened {
+                                Ok((reopened, info)) => {
+                                    stream = Some(reopened);
+                                    *shared.lock().unwrap() = info.clone();
+                                    let _ = app.emit(CAPTURE_DEVICE_CHANGED_EVENT, info);
+                                }
+                                Err(e) => {
+                                    let _ = app.emit(
+                                        CAPTURE_ERROR_EVENT,
+                                        format!("Input device {name} was lost: {e}"),
+                                    );
+                                }
+                            }
+                        }
+                        CaptureMessage::Stop => break,
+                    }
+                }
+                drop(stream);
+            })
+            .map_err(|e| e.to_string())?;
+
+        let info = ready_rx
+            .recv()
+            .map_err(|_| "Capture thread exited unexpectedly".to_string())??;
+
+        Ok(Self {
+            info,
+            messages,
+            stopping,
+            thread: Some(thread),
+        })
+    }
+
+    /// The stream in use, which changes if capture moved to the fallback device.
+    pub fn info(&self) -> CaptureInfo {
+        self.info.lock().unwrap().clone()
+    }
+
+    /// Tells the capture thread that `name` was unplugged. Ignored unless it is in use.
+    pub fn device_lost(&self, name: &str) {
+        let _ = self
+            .messages
+            .try_send(CaptureMessage::DeviceLost(name.to_string()));
+    }
+
+    pub fn stop(mut self) {
+        self.signal_stop();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// The callbacks hold senders too, so the thread would not notice on its own. If the
+    /// queue is full the wake-up is dropped, but the thread is busy draining it and sees
+    /// the flag at the next frame.
+    fn signal_stop(&self) {
+        self.stopping.store(true, Ordering::Release);
+        let _ = self.messages.try_send(CaptureMessage::Stop);
+    }
+}
+
+impl Drop for CaptureHandle {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            self.signal_stop();
+        }
+    }
+}
+
+/**
+ * Helper: open_stream
+ * Opens the named input device (or the default) and starts a stream that queues its
+ * frames and errors on `messages`. Frame numbering starts at `first_frame`.
+ */
+fn open_stream(
+    device_name: Option<&str>,
+    config: &CaptureConfig,
+    messages: mpsc::SyncSender<CaptureMessage>,
+    first_frame: u64,
+) -> Result<(Stream, CaptureInfo), String> {
+    let (device, is_fallback) = find_input_device(device_name)?;
+    let device_name = device.name().unwrap_or_else(|_| "Unknown device".into());
+
+    let supported = pick_config(&device, config.sample_rate)?;
+    let sample_format = supported.sample_format();
+    let stream_config: StreamConfig = supported.into();
+
+    let info = CaptureInfo {
+        device_name,
+        is_fallback,
+        device_sample_rate: stream_config.sample_rate.0,
+        device_channels: stream_config.channels,
+        sample_rate: config.sample_rate,
+        frame_ms: config.frame_ms,
+    };
+
+    let builder = FrameBuilder::new(&info, first_frame);
+    let stream = match sample_format {
+        SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, builder, messages),
+        SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, builder, messages),
+        SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, builder, messages),
+        SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, builder, messages),
+        other => Err(format!("Unsupported sample format: {other}")),
+    }?;
+
+    stream.play().map_err(|e| e.to_string())?;
+    Ok((stream, info))
+}
+
+/**
+ * Helper: pick_config
+ * Prefers a device configuration that natively supports the target rate (no resampling),
+ * with as few channels as possible. Falls back to the device default.
+ */
+fn pick_config(device: &Device, target_rate: u32) -> Result<SupportedStreamConfig, String> {
+    let target = SampleRate(target_rate);
+
+    if let Ok(ranges) = device.supported_input_configs() {
+        let native = ranges
+            .filter(|range| {
+                matches!(
+                    range.sample_format(),
+                    SampleFormat::I16 | SampleFormat::U16 | SampleFormat::I32 | SampleFormat::F32
+                )
+            })
+            .filter(|range| range.min_sample_rate() <= target && target <= range.max_sample_rate())
+            .min_by_key(|range| range.channels());
+
+        if let Some(range) = native {
+            return Ok(range.with_sample_rate(target));
+        }
+    }
+
+    device.default_input_config().map_err(|e| e.to_string())
+}
+
+/// The callbacks run on the audio thread, so they only ever `try_send`: no locks, no
+/// IPC, no waiting on the capture thread.
+fn build_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    mut builder: FrameBuilder,
+    messages: mpsc::SyncSender<CaptureMessage>,
+) -> Result<Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let errors = messages.clone();
+    let name = device.name().unwrap_or_default();
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                builder.push(data, |frame| {
+                    let _ = messages.try_send(CaptureMessage::Frame(frame));
+                });
+            },
+            move |err| {
+                let message = match err {
+                    StreamError::DeviceNotAvailable => CaptureMessage::DeviceLost(name.clone()),
+                    other => CaptureMessage::StreamError(other.to_string()),
+                };
+                let _ = errors.try_send(message);
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/**
+ * Struct: FrameAnalysis
+ * Responsibility: Per-frame work on the capture thread, off the real-time callback,
+ * before frames are broadcast: voice activity detection and level metering, emitting
+ * their events.
+ */
+struct FrameAnalysis {
+    app: AppHandle,
+    vad: Vad,
+    meter: Option<LevelMeter>,
+    frames: broadcast::Sender<AudioFrame>,
+}
+
+impl FrameAnalysis {
+    fn new(
+        app: &AppHandle,
+        config: &CaptureConfig,
+        info: &CaptureInfo,
+        frames: broadcast::Sender<AudioFrame>,
+    ) -> Self {
+        Self {
+            app: app.clone(),
+            vad: Vad::new(&config.vad, info.sample_rate, info.frame_ms),
+            meter: config
+                .meter
+                .enabled
+                .then(|| LevelMeter::new(&config.meter, info.sample_rate, info.frame_ms)),
+            frames,
+        }
+    }
+
+    fn handle(&mut self, mut frame: AudioFrame) {
+        if let Some(event) = self.vad.process(&frame) {
+            vad::emit_event(&self.app, event);
+        }
+        frame.is_speech = self.vad.is_speaking();
+
+        if let Some(level) = self.meter.as_mut().and_then(|m| m.process(&frame)) {
+            let _ = self.app.emit(AUDIO_LEVEL_EVENT, level);
+        }
+
+        // An error only means nobody is subscribed right now.
+        let _ = self.frames.send(frame);
+    }
+}
+
+/**
+ * Struct: FrameBuilder
+ * Responsibility: Turns interleaved device samples into fixed-size mono i16 frames
+ * at the requested sample rate (downmix → resample → chunk).
+ */
+struct FrameBuilder {
+    channels: usize,
+    resampler: Option<LinearResampler>,
+    sample_rate: u32,
+    frame_ms: u32,
+    frame_len: usize,
+    pending: Vec<i16>,
+    mono: Vec<f32>,
+    resampled: Vec<f32>,
+    frames_emitted: u64,
+}
+
+impl FrameBuilder {
+    fn new(info: &CaptureInfo, first_frame: u64) -> Self {
+        let resampler = (info.device_sample_rate != info.sample_rate)
+            .then(|| LinearResampler::new(info.device_sample_rate, info.sample_rate));
+        let frame_len = (info.sample_rate as usize * info.frame_ms as usize) / 1000;
+
+        Self {
+            channels: info.device_channels.max(1) as usize,
+            resampler,
+            sample_rate: info.sample_rate,
+            frame_ms: info.frame_ms,
+            frame_len,
+            pending: Vec::with_capacity(frame_len * 2),
+            mono: Vec::new(),
+            resampled: Vec::new(),
+            frames_emitted: first_frame,
+        }
+    }
+
+    fn push<T>(&mut self, data: &[T], mut emit: impl FnMut(AudioFrame))
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        self.mono.clear();
+        for frame in data.chunks(self.channels) {
+            let sum: f32 = frame.iter().map(|&s| f32::from_sample(s)).sum();
+            self.mono.push(sum / frame.len() as f32);
+        }
+
+        let samples = match self.resampler.as_mut() {
+            Some(resampler) => {
+                self.resampled.clear();
+                resampler.process(&self.mono, &mut self.resampled);
+                &self.resampled
+            }
+            None => &self.mono,
+        };
+
+        self.pending.extend(
+            samples
+                .iter()
+                .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16),
+        );
+
+        while self.pending.len() >= self.frame_len {
+            let samples: Vec<i16> = self.pending.drain(..self.frame_len).collect();
+            emit(AudioFrame {
+                samples,
+                sample_rate: self.sample_rate,
+                timestamp_ms: self.frames_emitted * self.frame_ms as u64,
+                is_speech: false,
+            });
+            self.frames_emitted += 1;
+        }
+    }
+}
+
+/**
+ * Struct: LinearResampler
+ * Streaming linear-interpolation resampler. Good enough for speech going to an STT engine
+ * and keeps us free of a DSP dependency.
+ */
+pub(crate) struct LinearResampler {
+    step: f64,
+    /// Position in a virtual buffer where index 0 is the last sample of the previous block.
+    pos: f64,
+    prev: f32,
+}
+
+impl LinearResampler {
+    pub(crate) fn new(from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            step: from_rate as f64 / to_rate as f64,
+            pos: 1.0,
+            prev: 0.0,
+        }
+    }
+
+    pub(crate) fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        let Some(&last) = input.last() else {
+            return;
+        };
+
+        let len = input.len() as f64;
+        while self.pos + 1.0 <= len {
+            let idx = self.pos as usize;
+            let frac = (self.pos - idx as f64) as f32;
+            let a = if idx == 0 { self.prev } else { input[idx - 1] };
+            let b = input[idx];
+            out.push(a + (b - a) * frac);
+            self.pos += self.step;
+        }
+
+        self.pos -= len;
+        self.prev = last;
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::BTreeSet;
+use std::thread;
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::Device;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::AudioState;
+
+/// Rates reported to the UI. cpal only exposes min/max ranges, so we probe these.
+const COMMON_SAMPLE_RATES: [u32; 9] = [
+    8_000, 11_025, 16_000, 22_050, 32_000, 44_100, 48_000, 88_200, 96_000,
+];
+
+pub const DEVICES_CHANGED_EVENT: &str = "audio-devices-changed";
+
+/**
+ * Struct: InputDevice
+ * A capture device as shown in the Settings panel.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputDevice {
+    pub name: String,
+    pub channels: u16,
+    pub sample_rates: Vec<u32>,
+    pub is_default: bool,
+}
+
+/**
+ * Trait: DeviceBackend
+ * Responsibility: Source of the current input device list.
+ * The watcher only depends on this trait, so a fake backend can drive hot-plug logic
+ * without real hardware.
+ */
+pub trait DeviceBackend: Send + 'static {
+    fn input_devices(&self) -> Result<Vec<InputDevice>, String>;
+}
+
+/**
+ * Struct: CpalBackend
+ * Enumerates devices through the default cpal host (ALSA/PulseAudio on Linux).
+ */
+pub struct CpalBackend;
+
+impl DeviceBackend for CpalBackend {
+    fn input_devices(&self) -> Result<Vec<InputDevice>, String> {
+        let host = cpal::default_host();
+        let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+        let devices = host.input_devices().map_err(|e| e.to_string())?;
+        Ok(devices
+            .filter_map(|device| {
+                let name = device.name().ok()?;
+                let is_default = default_name.as_deref() == Some(name.as_str());
+                Some(describe(&device, name, is_default))
+            })
+            .collect())
+    }
+}
+
+fn describe(device: &Device, name: String, is_default: bool) -> InputDevice {
+    let mut channels = 0;
+    let mut rates = BTreeSet::new();
+
+    if let Ok(ranges) = device.supported_input_configs() {
+        for range in ranges {
+            channels = channels.max(range.channels());
+            let (min, max) = (range.min_sample_rate().0, range.max_sample_rate().0);
+            rates.extend(
+                COMMON_SAMPLE_RATES
+                    .iter()
+                    .filter(|&&rate| min <= rate && rate <= max),
+            );
+        }
+    }
+
+    InputDevice {
+        name,
+        channels,
+        sample_rates: rates.into_iter().collect(),
+        is_default,
+    }
+}
+
+/**
+ * Function: choose_input_device
+ * Picks the preferred device if it is present, otherwise the system default, otherwise
+ * the first one listed. Returns its name and whether the preference could not be met.
+ */
+pub fn choose_input_device(
+    devices: &[InputDevice],
+    preferred: Option<&str>,
+) -> Option<(String, bool)> {
+    if let Some(device) = preferred.and_then(|name| devices.iter().find(|d| d.name == name)) {
+        return Some((device.name.clone(), false));
+    }
+
+    devices
+        .iter()
+        .find(|d| d.is_default)
+        .or_else(|| devices.first())
+        .map(|device| (device.name.clone(), preferred.is_some()))
+}
+
+/**
+ * Function: find_input_device
+ * Looks up an input device by name, falling back as in `choose_input_device`.
+ * Returns the device and whether the fallback was used.
+ */
+pub fn find_input_device(name: Option<&str>) -> Result<(Device, bool), String> {
+    let devices = CpalBackend.input_devices()?;
+    let (chosen, is_fallback) =
+        choose_input_device(&devices, name).ok_or("No input device available")?;
+
+    cpal::default_host()
+        .input_devices()
+        .map_err(|e| e.to_string())?
+        .find(|device| device.name().is_ok_and(|n| n == chosen))
+        .map(|device| (device, is_fallback))
+        .ok_or_else(|| format!("Input device disappeared: {chosen}"))
+}
+
+/**
+ * Struct: DevicesChanged
+ * Payload of the `audio-devices-changed` event.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevicesChanged {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub devices: Vec<InputDevice>,
+    /// False when the preferred device is gone and capture will use the default instead.
+    pub preferred_available: bool,
+}
+
+/**
+ * Function: diff_devices
+ * Compares two snapshots by name. Returns `None` when nothing was plugged or unplugged.
+ */
+pub fn diff_devices(
+    previous: &[InputDevice],
+    current: &[InputDevice],
+    preferred: Option<&str>,
+) -> Option<DevicesChanged> {
+    let before: BTreeSet<&str> = previous.iter().map(|d| d.name.as_str()).collect();
+    let after: BTreeSet<&str> = current.iter().map(|d| d.name.as_str()).collect();
+
+    if before == after {
+        return None;
+    }
+
+    Some(DevicesChanged {
+        added: after.difference(&before).map(|s| s.to_string()).collect(),
+        removed: before.difference(&after).map(|s| s.to_string()).collect(),
+        devices: current.to_vec(),
+        preferred_available: preferred.is_none_or(|name| after.contains(name)),
+    })
+}
+
+/**
+ * Struct: DeviceWatch
+ * Responsibility: Remembers the last device list and reports what changed since.
+ * A failed enumeration is skipped rather than treated as every device being unplugged.
+ */
+pub struct DeviceWatch<B> {
+    backend: B,
+    known: Vec<InputDevice>,
+}
+
+impl<B: DeviceBackend> DeviceWatch<B> {
+    pub fn new(backend: B) -> Self {
+        let known = backend.input_devices().unwrap_or_default();
+        Self { backend, known }
+    }
+
+    pub fn poll(&mut self, preferred: Option<&str>) -> Option<DevicesChanged> {
+        let current = self.backend.input_devices().ok()?;
+        let change = diff_devices(&self.known, &current, preferred);
+        self.known = current;
+        change
+    }
+}
+
+/**
+ * Function: spawn_watcher
+ * Responsibility: Polls the backend and emits `audio-devices-changed` on hot-plug.
+ * If the device being captured from is unplugged, capture moves to the fallback device.
+ * cpal has no device notifications, so polling is the portable option.
+ */
+pub fn spawn_watcher<B: DeviceBackend>(app: AppHandle, backend: B, interval: Duration) {
+    thread::Builder::new()
+        .name("audio-device-watcher".into())
+        .spawn(move || {
+            let mut watch = DeviceWatch::new(backend);
+            loop {
+                thread::sleep(interval);
+
+                let audio = app.state::<AudioState>();
+                if let Some(change) = watch.poll(audio.preferred_device().as_deref()) {
+                    audio.devices_removed(&change.removed);
+                    let _ = app.emit(DEVICES_CHANGED_EVENT, change);
+                }
+            }
+        })
+        .expect("failed to spawn audio device watcher");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Device list that the test edits between polls; `None` makes enumeration fail.
+    #[derive(Clone, Default)]
+    struct FakeBackend(Arc<Mutex<Option<Vec<InputDevice>>>>);
+
+    impl FakeBackend {
+        fn set(&self, devices: Option<Vec<InputDevice>>) {
+            *self.0.lock().unwrap() = devices;
+        }
+    }
+
+    impl DeviceBackend for FakeBackend {
+        fn input_devices(&self) -> Result<Vec<InputDevice>, String> {
+            self.0
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or_else(|| "host busy".into())
+        }
+    }
+
+    fn device(name: &str, is_default: bool) -> InputDevice {
+        InputDevice {
+            name: name.into(),
+            channels: 1,
+            sample_rates: vec![16_000],
+            is_default,
+        }
+    }
+
+    #[test]
+    fn diff_reports_added_and_removed_devices() {
+        let before = [device("Built-in", true), device("USB Mic", false)];
+        let after = [device("Built-in", true), device("Headset", false)];
+
+        let change = diff_devices(&before, &after, Some("USB Mic")).unwrap();
+        assert_eq!(change.added, ["Headset"]);
+        assert_eq!(change.removed, ["USB Mic"]);
+        assert_eq!(change.devices, after);
+        assert!(!change.preferred_available);
+
+        let change = diff_devices(&before, &after, Some("Headset")).unwrap();
+        assert!(change.preferred_available);
+        let change = diff_devices(&before, &after, None).unwrap();
+        assert!(change.preferred_available);
+    }
+
+    #[test]
+    fn diff_ignores_unchanged_names() {
+        let before = [device("Built-in", true), device("USB Mic", false)];
+        // Only the default moved; nothing was plugged or unplugged.
+        let after = [device("USB Mic", true), device("Built-in", false)];
+        assert!(diff_devices(&before, &after, None).is_none());
+    }
+
+    #[test]
+    fn choose_prefers_named_device() {
+        let devices = [device("Built-in", true), device("USB Mic", false)];
+        assert_eq!(
+            choose_input_device(&devices, Some("USB Mic")),
+            Some(("USB Mic".into(), false))
+        );
+        assert_eq!(
+            choose_input_device(&devices, None),
+            Some(("Built-in".into(), false))
+        );
+    }
+
+    #[test]
+    fn choose_falls_back_when_preferred_is_missing() {
+        let devices = [device("USB Mic", false), device("Built-in", true)];
+        assert_eq!(
+            choose_input_device(&devices, Some("Headset")),
+            Some(("Built-in".into(), true))
+        );
+
+        // No default reported: take whatever is there.
+        let devices = [device("USB Mic", false)];
+        assert_eq!(
+            choose_input_device(&devices, Some("Headset")),
+            Some(("USB Mic".into(), true))
+        );
+        assert_eq!(choose_input_device(&[], Some("Headset")), None);
+    }
+
+    #[test]
+    fn watch_reports_each_change_once() {
+        let backend = FakeBackend::default();
+        backend.set(Some(vec![device("Built-in", true)]));
+        let mut watch = DeviceWatch::new(backend.clone());
+        assert!(watch.poll(None).is_none());
+
+        backend.set(Some(vec![
+            device("Built-in", true),
+            device("USB Mic", false),
+        ]));
+        let change = watch.poll(Some("USB Mic")).unwrap();
+        assert_eq!(change.added, ["USB Mic"]);
+        assert!(change.preferred_available);
+        assert!(watch.poll(Some("USB Mic")).is_none());
+
+        backend.set(Some(vec![device("Built-in", true)]));
+        let change = watch.poll(Some("USB Mic")).unwrap();
+        assert_eq!(change.removed, ["USB Mic"]);
+        assert!(!change.preferred_available);
+    }
+
+    #[test]
+    fn watch_skips_failed_enumeration() {
+        let backend = FakeBackend::default();
+        backend.set(Some(vec![device("Built-in", true)]));
+        let mut watch = DeviceWatch::new(backend.clone());
+
+        backend.set(None);
+        assert!(watch.poll(None).is_none());
+
+        // Compared against the last good list, not an empty one.
+        backend.set(Some(vec![device("Built-in", true)]));
+        assert!(watch.poll(None).is_none());
+    }
+}
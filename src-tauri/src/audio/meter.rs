@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+
+use super::spectrum::{self, BandAnalyzer};
+use super::AudioFrame;
+
+pub const AUDIO_LEVEL_EVENT: &str = "audio-level";
+
+/// Lowest and highest frequency covered by the band summary.
+const BANDS_MIN_HZ: f32 = 60.0;
+const BANDS_MAX_HZ: f32 = 8_000.0;
+
+const MAX_BANDS: u8 = 32;
+
+/// Peaks at or above this level (dBFS) are reported as clipping.
+const CLIPPING_DB: f32 = -0.1;
+
+/**
+ * Struct: MeterConfig
+ * Level metering settings, part of `CaptureConfig`.
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MeterConfig {
+    pub enabled: bool,
+    /// Minimum time between `audio-level` events. Rounded up to whole frames.
+    pub interval_ms: u32,
+    /// Number of log-spaced frequency bands to include (0 = none, at most 32).
+    pub bands: u8,
+}
+
+impl Default for MeterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_ms: 50,
+            bands: 0,
+        }
+    }
+}
+
+/**
+ * Struct: AudioLevel
+ * Payload of the `audio-level` event, summarizing all frames since the previous one.
+ * Levels are dBFS, floored at -100.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioLevel {
+    /// Capture time of the first frame in the interval.
+    pub timestamp_ms: u64,
+    pub rms_db: f32,
+    pub peak_db: f32,
+    pub clipping: bool,
+    /// True if any frame in the interval was part of a speech segment.
+    pub is_speech: bool,
+    /// Loudest level per band, low to high. Empty unless `MeterConfig::bands` is set.
+    pub bands: Vec<f32>,
+}
+
+/**
+ * Struct: LevelMeter
+ * Responsibility: Accumulates RMS, peak and band levels over frames and hands out
+ * one `AudioLevel` per interval, so the UI gets a steady, throttled stream.
+ */
+pub struct LevelMeter {
+    interval_frames: u32,
+    analyzer: Option<BandAnalyzer>,
+    frames: u32,
+    energy: f64,
+    samples: usize,
+    peak: u16,
+    bands: Vec<f32>,
+    is_speech: bool,
+    started_ms: u64,
+}
+
+impl LevelMeter {
+    pub fn new(config: &MeterConfig, sample_rate: u32, frame_ms: u32) -> Self {
+        let band_count = config.bands.min(MAX_BANDS) as usize;
+        let analyzer = (band_count > 0).then(|| {
+            let edges = log_bands(band_count, sample_rate);
+            let frame_len = (sample_rate as usize * frame_ms as usize) / 1000;
+            BandAnalyzer::new(&edges, sample_rate, frame_len)
+        });
+
+        Self {
+            interval_frames: config.interval_ms.div_ceil(frame_ms.max(1)).max(1),
+            analyzer,
+            frames: 0,
+            energy: 0.0,
+            samples: 0,
+            peak: 0,
+            bands: vec![spectrum::MIN_LEVEL_DB; band_count],
+            is_speech: false,
+            started_ms: 0,
+        }
+    }
+
+    /**
+     * Function: process
+     * Adds one frame. Returns the summary once the interval is complete.
+     */
+    pub fn process(&mut self, frame: &AudioFrame) -> Option<AudioLevel> {
+        if self.frames == 0 {
+            self.started_ms = frame.timestamp_ms;
+        }
+        self.frames += 1;
+        self.energy += frame
+            .samples
+            .iter()
+            .map(|&s| (s as f64).powi(2))
+            .sum::<f64>();
+        self.samples += frame.samples.len();
+        self.peak = frame
+            .samples
+            .iter()
+            .map(|&s| s.unsigned_abs())
+            .fold(self.peak, u16::max);
+        self.is_speech |= frame.is_speech;
+        if let Some(analyzer) = self.analyzer.as_mut() {
+            for (band, &level) in self
+                .bands
+                .iter_mut()
+                .zip(analyzer.levels_db(&frame.samples))
+            {
+                *band = band.max(level);
+            }
+        }
+
+        if self.frames < self.interval_frames {
+            return None;
+        }
+
+        let full_scale = (i16::MAX as f64).powi(2);
+        let rms_db = spectrum::power_db(self.energy / self.samples.max(1) as f64 / full_scale);
+        let peak_db = spectrum::power_db((self.peak as f64).powi(2) / full_scale);
+        let level = AudioLevel {
+            timestamp_ms: self.started_ms,
+            rms_db,
+            peak_db,
+            clipping: peak_db >= CLIPPING_DB,
+            is_speech: self.is_speech,
+            bands: self.bands.clone(),
+        };
+
+        self.frames = 0;
+        self.energy = 0.0;
+        self.samples = 0;
+        self.peak = 0;
+        self.is_speech = false;
+        self.bands.fill(spectrum::MIN_LEVEL_DB);
+        Some(level)
+    }
+}
+
+/// `count` log-spaced bands between `BANDS_MIN_HZ` and `BANDS_MAX_HZ` (or Nyquist).
+fn log_bands(count: usize, sample_rate: u32) -> Vec<(f32, f32)> {
+    let high = BANDS_MAX_HZ.min(sample_rate as f32 / 2.0);
+    let ratio = (high / BANDS_MIN_HZ).powf(1.0 / count as f32);
+    (0..count)
+        .map(|i| {
+            let low = BANDS_MIN_HZ * ratio.powi(i as i32);
+            (low, low * ratio)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::spectrum::{tone, MIN_LEVEL_DB};
+
+    const RATE: u32 = 16_000;
+    const FRAME_MS: u32 = 20;
+    const FRAME_LEN: usize = (RATE * FRAME_MS / 1000) as usize;
+
+    fn frame(index: u64, samples: Vec<i16>, is_speech: bool) -> AudioFrame {
+        AudioFrame {
+            samples,
+            sample_rate: RATE,
+            timestamp_ms: index * FRAME_MS as u64,
+            is_speech,
+        }
+    }
+
+    /// Feeds the same samples until the meter reports.
+    fn measure(meter: &mut LevelMeter, samples: &[i16]) -> AudioLevel {
+        (0..)
+            .find_map(|i| meter.process(&frame(i, samples.to_vec(), false)))
+            .unwrap()
+    }
+
+    fn meter(bands: u8) -> LevelMeter {
+        let config = MeterConfig {
+            bands,
+            ..MeterConfig::default()
+        };
+        LevelMeter::new(&config, RATE, FRAME_MS)
+    }
+
+    #[test]
+    fn silence_reports_the_floor() {
+        let level = measure(&mut meter(4), &[0; FRAME_LEN]);
+        assert_eq!(level.rms_db, MIN_LEVEL_DB);
+        assert_eq!(level.peak_db, MIN_LEVEL_DB);
+        assert!(!level.clipping);
+        assert_eq!(level.bands, [MIN_LEVEL_DB; 4]);
+    }
+
+    #[test]
+    fn full_scale_sine_peaks_at_zero_dbfs_and_clips() {
+        let level = measure(&mut meter(0), &tone(1_000.0, 1.0, RATE, FRAME_LEN));
+        assert!(level.peak_db.abs() < 0.01, "peak at {}", level.peak_db);
+        assert!(
+            (level.rms_db + 3.01).abs() < 0.05,
+            "rms at {}",
+            level.rms_db
+        );
+        assert!(level.clipping);
+        assert!(level.bands.is_empty());
+
+        let level = measure(&mut meter(0), &tone(1_000.0, 0.5, RATE, FRAME_LEN));
+        assert!((level.peak_db + 6.02).abs() < 0.05);
+        assert!(!level.clipping);
+    }
+
+    #[test]
+    fn tone_is_loudest_in_its_band() {
+        let edges = log_bands(8, RATE);
+        let level = measure(&mut meter(8), &tone(1_000.0, 0.5, RATE, FRAME_LEN));
+
+        let expected = edges
+            .iter()
+            .position(|&(low, high)| (low..high).contains(&1_000.0))
+            .unwrap();
+        let loudest = (0..level.bands.len())
+            .max_by(|&a, &b| level.bands[a].total_cmp(&level.bands[b]))
+            .unwrap();
+        assert_eq!(loudest, expected, "{:?}", level.bands);
+    }
+
+    #[test]
+    fn one_report_per_interval() {
+        // 50 ms rounds up to three 20 ms frames.
+        let mut meter = meter(0);
+        let quiet = vec![0; FRAME_LEN];
+        assert!(meter.process(&frame(0, quiet.clone(), false)).is_none());
+        assert!(meter.process(&frame(1, quiet.clone(), true)).is_none());
+        let level = meter.process(&frame(2, quiet.clone(), false)).unwrap();
+        assert_eq!(level.timestamp_ms, 0);
+        assert!(level.is_speech);
+
+        assert!(meter.process(&frame(3, quiet.clone(), false)).is_none());
+        assert!(meter.process(&frame(4, quiet.clone(), false)).is_none());
+        let level = meter.process(&frame(5, quiet, false)).unwrap();
+        assert_eq!(level.timestamp_ms, 60);
+        assert!(!level.is_speech);
+    }
+}
@@ -0,0 +1,269 @@
+mod capture;
+pub mod devices;
+pub mod meter;
+mod spectrum;
+pub mod vad;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use tokio::sync::broadcast;
+
+use capture::CaptureHandle;
+pub(crate) use capture::LinearResampler;
+use devices::{CpalBackend, DeviceBackend, InputDevice};
+use meter::MeterConfig;
+use vad::VadConfig;
+
+use crate::settings::{self, Settings};
+
+/// Sample rate Deepgram (and most STT engines) expect for linear16 audio.
+pub const DEFAULT_SAMPLE_RATE: u32 = 16_000;
+
+/// Frame length in milliseconds. 20 ms keeps latency low and matches VAD frame sizes.
+pub const DEFAULT_FRAME_MS: u32 = 20;
+
+/// How many frames subscribers may lag behind before they start dropping audio.
+const FRAME_CHANNEL_CAPACITY: usize = 512;
+
+pub const CAPTURE_ERROR_EVENT: &str = "capture-error";
+
+/// Emitted with the new `CaptureInfo` when capture moves to the fallback device.
+pub const CAPTURE_DEVICE_CHANGED_EVENT: &str = "capture-device-changed";
+
+/// How often the device list is polled for hot-plug changes.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/**
+ * Struct: AudioFrame
+ * A fixed-length block of mono 16-bit PCM produced by the capture thread.
+ */
+#[derive(Debug, Clone)]
+pub struct AudioFrame {
+    pub samples: Vec<i16>,
+    pub sample_rate: u32,
+    /// Milliseconds since the capture session started.
+    pub timestamp_ms: u64,
+    /// Whether the VAD considers this frame part of a speech segment (including hangover).
+    pub is_speech: bool,
+}
+
+/**
+ * Struct: CaptureConfig
+ * Options accepted by `start_capture`. Missing fields fall back to the defaults above.
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureConfig {
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u32,
+    #[serde(default = "default_frame_ms")]
+    pub frame_ms: u32,
+    #[serde(default)]
+    pub vad: VadConfig,
+    #[serde(default)]
+    pub meter: MeterConfig,
+}
+
+fn default_sample_rate() -> u32 {
+    DEFAULT_SAMPLE_RATE
+}
+
+fn default_frame_ms() -> u32 {
+    DEFAULT_FRAME_MS
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            frame_ms: DEFAULT_FRAME_MS,
+            vad: VadConfig::default(),
+            meter: MeterConfig::default(),
+        }
+    }
+}
+
+/// Capture for a recording: defaults, with auto-stop after the configured silence.
+impl From<&Settings> for CaptureConfig {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            vad: VadConfig {
+                silence_timeout_ms: settings.silence_timeout as u64 * 1000,
+                ..VadConfig::default()
+            },
+            ..CaptureConfig::default()
+        }
+    }
+}
+
+/**
+ * Struct: CaptureInfo
+ * Describes the stream that was actually opened, returned to the frontend.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureInfo {
+    pub device_name: String,
+    /// True when the preferred device was missing and the system default was opened.
+    pub is_fallback: bool,
+    pub device_sample_rate: u32,
+    pub device_channels: u16,
+    pub sample_rate: u32,
+    pub frame_ms: u32,
+}
+
+/**
+ * Struct: AudioState
+ * Responsibility: Owns the active capture session and the frame broadcast channel.
+ * Other backend modules call `subscribe()` to receive PCM frames.
+ */
+pub struct AudioState {
+    frames: broadcast::Sender<AudioFrame>,
+    capture: Mutex<Option<CaptureHandle>>,
+    preferred_device: Mutex<Option<String>>,
+}
+
+impl Default for AudioState {
+    fn default() -> Self {
+        let (frames, _) = broadcast::channel(FRAME_CHANNEL_CAPACITY);
+        Self {
+            frames,
+            capture: Mutex::new(None),
+            preferred_device: Mutex::new(None),
+        }
+    }
+}
+
+impl AudioState {
+    pub fn subscribe(&self) -> broadcast::Receiver<AudioFrame> {
+        self.frames.subscribe()
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capture.lock().unwrap().is_some()
+    }
+
+    pub fn preferred_device(&self) -> Option<String> {
+        self.preferred_device.lock().unwrap().clone()
+    }
+
+    pub fn set_preferred_device(&self, name: Option<String>) {
+        *self.preferred_device.lock().unwrap() = name;
+    }
+
+    /**
+     * Function: start
+     * Opens the preferred input device (or the default) and starts pushing frames.
+     * Fails if a capture session is already running.
+     */
+    pub fn start(&self, app: &AppHandle, config: CaptureConfig) -> Result<CaptureInfo, String> {
+        let mut capture = self.capture.lock().unwrap();
+        if capture.is_some() {
+            return Err("Capture already running".into());
+        }
+
+        let handle = CaptureHandle::spawn(
+            app.clone(),
+            self.preferred_device(),
+            config,
+            self.frames.clone(),
+        )?;
+        let info = handle.info();
+        *capture = Some(handle);
+        Ok(info)
+    }
+
+    /**
+     * Function: devices_removed
+     * Called by the hot-plug watcher. If the device in use is among `names`, the running
+     * session reopens on the fallback device.
+     */
+    pub fn devices_removed(&self, names: &[String]) {
+        if let Some(handle) = self.capture.lock().unwrap().as_ref() {
+            let active = handle.info().device_name;
+            if names.contains(&active) {
+                handle.device_lost(&active);
+            }
+        }
+    }
+
+    /**
+     * Function: stop
+     * Stops the running session, releasing the device. No-op when idle.
+     */
+    pub fn stop(&self) {
+        let handle = self.capture.lock().unwrap().take();
+        if let Some(handle) = handle {
+            handle.stop();
+        }
+    }
+}
+
+/**
+ * Function: setup
+ * Responsibility: Starts the hot-plug watcher. The device choice is restored by
+ * `settings::setup`. Called once from the Tauri `setup` hook.
+ */
+pub fn setup(app: &AppHandle) {
+    devices::spawn_watcher(app.clone(), CpalBackend, DEVICE_POLL_INTERVAL);
+}
+
+/**
+ * Command: start_capture
+ * Responsibility: Starts native microphone capture and returns the opened stream format.
+ */
+#[tauri::command]
+pub fn start_capture(
+    app: AppHandle,
+    state: State<'_, AudioState>,
+    config: Option<CaptureConfig>,
+) -> Result<CaptureInfo, String> {
+    state.start(&app, config.unwrap_or_default())
+}
+
+/**
+ * Command: stop_capture
+ * Responsibility: Stops native microphone capture.
+ */
+#[tauri::command]
+pub fn stop_capture(state: State<'_, AudioState>) -> Result<(), String> {
+    state.stop();
+    Ok(())
+}
+
+/**
+ * Command: list_input_devices
+ * Responsibility: Lists capture devices with their channels and supported rates.
+ */
+#[tauri::command]
+pub fn list_input_devices() -> Result<Vec<InputDevice>, String> {
+    CpalBackend.input_devices()
+}
+
+/**
+ * Command: get_preferred_device
+ * Responsibility: Returns the persisted device name, or `null` for the system default.
+ */
+#[tauri::command]
+pub fn get_preferred_device(state: State<'_, AudioState>) -> Option<String> {
+    state.preferred_device()
+}
+
+/**
+ * Command: set_preferred_device
+ * Responsibility: Persists the device used by the next `start_capture` in the settings.
+ * Pass `null` to follow the system default.
+ */
+#[tauri::command]
+pub fn set_preferred_device(app: AppHandle, name: Option<String>) -> Result<(), String> {
+    settings::update(&app, |current| {
+        Ok(Settings {
+            preferred_device: name,
+            ..current.clone()
+        })
+    })?;
+    Ok(())
+}
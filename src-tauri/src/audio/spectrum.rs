@@ -0,0 +1,175 @@
+use std::f32::consts::PI;
+
+/// Levels are clamped here so digital silence does not produce `-inf`.
+pub const MIN_LEVEL_DB: f32 = -100.0;
+
+/// Upper bound on DFT bins evaluated per band, so long frames stay cheap.
+const MAX_BINS_PER_BAND: usize = 8;
+
+/// Converts a power ratio (relative to full scale) to dB.
+pub fn power_db(power: f64) -> f32 {
+    (10.0 * power.log10() as f32).max(MIN_LEVEL_DB)
+}
+
+/// RMS level of `samples` in dBFS.
+pub fn rms_db(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return MIN_LEVEL_DB;
+    }
+    let energy: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
+    power_db(energy / samples.len() as f64 / (i16::MAX as f64).powi(2))
+}
+
+/// `len` samples of a sine at `hz`, peaking at `amplitude` (1.0 = full scale).
+#[cfg(test)]
+pub fn tone(hz: f32, amplitude: f32, sample_rate: u32, len: usize) -> Vec<i16> {
+    (0..len)
+        .map(|i| {
+            let phase = 2.0 * PI * hz * i as f32 / sample_rate as f32;
+            (amplitude * phase.sin() * i16::MAX as f32).round() as i16
+        })
+        .collect()
+}
+
+/**
+ * Struct: BandAnalyzer
+ * Responsibility: Mean power per frequency band of fixed-length frames.
+ * Evaluates a handful of Hann-windowed Goertzel bins per band instead of a full FFT,
+ * which is all a VAD or a level meter needs.
+ */
+pub struct BandAnalyzer {
+    window: Vec<f32>,
+    /// Goertzel coefficient of every evaluated bin, grouped by band.
+    bands: Vec<Vec<f32>>,
+    scratch: Vec<f32>,
+    levels: Vec<f32>,
+}
+
+impl BandAnalyzer {
+    /// `edges` are `(low, high)` in Hz; parts above Nyquist are ignored.
+    pub fn new(edges: &[(f32, f32)], sample_rate: u32, frame_len: usize) -> Self {
+        let len = frame_len.max(2);
+        let window = (0..len)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / len as f32).cos())
+            .collect();
+
+        let bin_hz = sample_rate as f32 / len as f32;
+        let nyquist = sample_rate as f32 / 2.0;
+        let bands = edges
+            .iter()
+            .map(|&(low, high)| {
+                let first = (low / bin_hz).ceil() as usize;
+                let last = (high.min(nyquist) / bin_hz).floor() as usize;
+                let count = (last + 1).saturating_sub(first);
+                let step = count.div_ceil(MAX_BINS_PER_BAND).max(1);
+                let mut bins: Vec<usize> = (first..=last).step_by(step).collect();
+                // Bands narrower than the bin spacing use the bin closest to their centre.
+                if bins.is_empty() && low < nyquist {
+                    bins.push(((low + high.min(nyquist)) / 2.0 / bin_hz).round() as usize);
+                }
+                bins.into_iter()
+                    .filter(|&k| k > 0 && k < len / 2)
+                    .map(|k| 2.0 * (2.0 * PI * k as f32 / len as f32).cos())
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            window,
+            bands,
+            scratch: Vec::with_capacity(len),
+            levels: Vec::with_capacity(edges.len()),
+        }
+    }
+
+    /**
+     * Function: levels_db
+     * Band levels of one frame in dB, in the order of `edges`.
+     * Bands above Nyquist report `MIN_LEVEL_DB`.
+     */
+    pub fn levels_db(&mut self, samples: &[i16]) -> &[f32] {
+        self.scratch.clear();
+        self.scratch.extend(
+            samples
+                .iter()
+                .zip(&self.window)
+                .map(|(&s, &w)| s as f32 / i16::MAX as f32 * w),
+        );
+
+        self.levels.clear();
+        for coefficients in &self.bands {
+            self.levels.push(band_db(&self.scratch, coefficients));
+        }
+        &self.levels
+    }
+}
+
+/// Mean power of the band's bins, in dB.
+fn band_db(samples: &[f32], coefficients: &[f32]) -> f32 {
+    if coefficients.is_empty() || samples.is_empty() {
+        return MIN_LEVEL_DB;
+    }
+    let n = samples.len() as f32;
+    let power: f32 = coefficients
+        .iter()
+        .map(|&coeff| {
+            let (mut s1, mut s2) = (0.0f32, 0.0f32);
+            for &x in samples {
+                let s0 = x + coeff * s1 - s2;
+                s2 = s1;
+                s1 = s0;
+            }
+            (s1 * s1 + s2 * s2 - coeff * s1 * s2) / (n * n)
+        })
+        .sum();
+    power_db((power / coefficients.len() as f32) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+    const FRAME_LEN: usize = 320;
+
+    #[test]
+    fn silence_sits_at_the_floor() {
+        assert_eq!(rms_db(&[]), MIN_LEVEL_DB);
+        assert_eq!(rms_db(&[0; FRAME_LEN]), MIN_LEVEL_DB);
+        assert_eq!(power_db(0.0), MIN_LEVEL_DB);
+
+        let mut analyzer = BandAnalyzer::new(&[(100.0, 500.0), (500.0, 2000.0)], RATE, FRAME_LEN);
+        assert_eq!(analyzer.levels_db(&[0; FRAME_LEN]), [MIN_LEVEL_DB; 2]);
+    }
+
+    #[test]
+    fn full_scale_levels() {
+        // A full-scale square wave is 0 dBFS; a full-scale sine is 3 dB below it.
+        let square: Vec<i16> = (0..FRAME_LEN)
+            .map(|i| if i % 2 == 0 { i16::MAX } else { -i16::MAX })
+            .collect();
+        assert!(rms_db(&square).abs() < 0.01);
+        let sine = rms_db(&tone(1_000.0, 1.0, RATE, FRAME_LEN));
+        assert!((sine + 3.01).abs() < 0.05, "sine at {sine} dBFS");
+    }
+
+    #[test]
+    fn tone_lands_in_its_band() {
+        let bands = [
+            (100.0, 500.0),
+            (800.0, 1_200.0),
+            (2_000.0, 4_000.0),
+            (9_000.0, 12_000.0),
+        ];
+        let mut analyzer = BandAnalyzer::new(&bands, RATE, FRAME_LEN);
+        let levels = analyzer
+            .levels_db(&tone(1_000.0, 0.5, RATE, FRAME_LEN))
+            .to_vec();
+
+        assert!(levels[1] > -30.0, "tone band at {} dB", levels[1]);
+        assert!(levels[1] - levels[0] > 30.0, "{levels:?}");
+        assert!(levels[1] - levels[2] > 30.0, "{levels:?}");
+        // Entirely above Nyquist.
+        assert_eq!(levels[3], MIN_LEVEL_DB);
+    }
+}
@@ -0,0 +1,608 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use super::spectrum::{self, BandAnalyzer, MIN_LEVEL_DB};
+use super::AudioFrame;
+
+pub const SPEECH_START_EVENT: &str = "speech-start";
+pub const SPEECH_END_EVENT: &str = "speech-end";
+pub const SILENCE_TIMEOUT_EVENT: &str = "silence-timeout";
+
+/// Speech must stand this far above the tracked noise floor.
+const NOISE_MARGIN_DB: f32 = 10.0;
+
+/// How fast the energy detector's noise floor may rise. Slow enough that a long
+/// utterance does not raise it, fast enough that a fan switching on is absorbed.
+const ENERGY_NOISE_RISE_DB_PER_SEC: f32 = 1.0;
+
+/// Per-band floors drop back to the noise between syllables, so they can rise faster.
+const SPECTRAL_NOISE_RISE_DB_PER_SEC: f32 = 3.0;
+
+/// Sub-bands (Hz) of the spectral detector, roughly the bands WebRTC's VAD uses.
+const SPECTRAL_BANDS: [(f32, f32); 6] = [
+    (80.0, 250.0),
+    (250.0, 500.0),
+    (500.0, 1000.0),
+    (1000.0, 2000.0),
+    (2000.0, 3000.0),
+    (3000.0, 4000.0),
+];
+
+/// Weight of each band's SNR; formant bands carry most of the evidence.
+const SPECTRAL_WEIGHTS: [f32; 6] = [0.10, 0.20, 0.25, 0.20, 0.15, 0.10];
+
+/// Weighted SNR (dB) needed per aggressiveness level 0..=3.
+const SPECTRAL_THRESHOLDS_DB: [f32; 4] = [3.0, 4.5, 6.0, 8.0];
+
+/**
+ * Enum: VadMode
+ * Which detector classifies individual frames.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VadMode {
+    /// Frame level against a fixed threshold and an adaptive noise floor.
+    #[default]
+    Energy,
+    /// WebRTC-style sub-band SNR model. More robust against steady background noise.
+    Spectral,
+}
+
+/**
+ * Struct: VadConfig
+ * Voice activity detection settings, part of `CaptureConfig`.
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VadConfig {
+    pub mode: VadMode,
+    /// Frames quieter than this (dBFS) are never speech.
+    pub threshold_db: f32,
+    /// Spectral mode only: 0 (lenient) to 3 (strict).
+    pub aggressiveness: u8,
+    /// Speech must last this long before `speech-start` fires.
+    pub start_ms: u32,
+    /// Silence must last this long before `speech-end` fires.
+    pub hangover_ms: u32,
+    /// Emit `silence-timeout` after this much silence. 0 disables it.
+    pub silence_timeout_ms: u64,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            mode: VadMode::Energy,
+            threshold_db: -45.0,
+            aggressiveness: 2,
+            start_ms: 60,
+            hangover_ms: 300,
+            silence_timeout_ms: 0,
+        }
+    }
+}
+
+/**
+ * Enum: VadEvent
+ * Transitions reported by `Vad::process`. Timestamps use the capture clock
+ * (`AudioFrame::timestamp_ms`).
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum VadEvent {
+    SpeechStart { timestamp_ms: u64 },
+    SpeechEnd { timestamp_ms: u64, duration_ms: u64 },
+    SilenceTimeout { timestamp_ms: u64, silence_ms: u64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeechStartPayload {
+    timestamp_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeechEndPayload {
+    timestamp_ms: u64,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SilenceTimeoutPayload {
+    timestamp_ms: u64,
+    silence_ms: u64,
+}
+
+pub fn emit_event(app: &AppHandle, event: VadEvent) {
+    let _ = match event {
+        VadEvent::SpeechStart { timestamp_ms } => {
+            app.emit(SPEECH_START_EVENT, SpeechStartPayload { timestamp_ms })
+        }
+        VadEvent::SpeechEnd {
+            timestamp_ms,
+            duration_ms,
+        } => app.emit(
+            SPEECH_END_EVENT,
+            SpeechEndPayload {
+                timestamp_ms,
+                duration_ms,
+            },
+        ),
+        VadEvent::SilenceTimeout {
+            timestamp_ms,
+            silence_ms,
+        } => app.emit(
+            SILENCE_TIMEOUT_EVENT,
+            SilenceTimeoutPayload {
+                timestamp_ms,
+                silence_ms,
+            },
+        ),
+    };
+}
+
+/**
+ * Trait: VoiceDetector
+ * Responsibility: Classifies a single frame as speech or not, without smoothing.
+ * `Vad` adds the start/hangover logic on top.
+ */
+pub trait VoiceDetector: Send {
+    fn is_speech(&mut self, samples: &[i16]) -> bool;
+}
+
+/**
+ * Struct: NoiseFloor
+ * Minimum tracker: follows the level down immediately and creeps up slowly.
+ */
+#[derive(Debug, Clone, Copy)]
+struct NoiseFloor {
+    db: f32,
+    rise_per_frame: f32,
+}
+
+impl NoiseFloor {
+    fn new(rise_db_per_sec: f32, frame_ms: u32) -> Self {
+        Self {
+            db: MIN_LEVEL_DB,
+            rise_per_frame: rise_db_per_sec * frame_ms as f32 / 1000.0,
+        }
+    }
+
+    fn update(&mut self, level_db: f32) {
+        self.db = if level_db < self.db {
+            level_db
+        } else {
+            (self.db + self.rise_per_frame).min(level_db)
+        };
+    }
+}
+
+/**
+ * Struct: EnergyDetector
+ * Speech when the frame is above both `threshold_db` and the noise floor plus a margin.
+ */
+pub struct EnergyDetector {
+    threshold_db: f32,
+    noise: NoiseFloor,
+}
+
+impl EnergyDetector {
+    pub fn new(threshold_db: f32, frame_ms: u32) -> Self {
+        Self {
+            threshold_db,
+            noise: NoiseFloor::new(ENERGY_NOISE_RISE_DB_PER_SEC, frame_ms),
+        }
+    }
+}
+
+impl VoiceDetector for EnergyDetector {
+    fn is_speech(&mut self, samples: &[i16]) -> bool {
+        let level = spectrum::rms_db(samples);
+        let speech = level > self.threshold_db && level > self.noise.db + NOISE_MARGIN_DB;
+        self.noise.update(level);
+        speech
+    }
+}
+
+/**
+ * Struct: SpectralDetector
+ * Responsibility: WebRTC-style detector. Splits each frame into speech sub-bands,
+ * tracks a noise floor per band and compares the weighted band SNR against a
+ * threshold picked by `aggressiveness`.
+ */
+pub struct SpectralDetector {
+    threshold_db: f32,
+    snr_threshold_db: f32,
+    analyzer: BandAnalyzer,
+    noise: Vec<NoiseFloor>,
+}
+
+impl SpectralDetector {
+    pub fn new(threshold_db: f32, aggressiveness: u8, sample_rate: u32, frame_ms: u32) -> Self {
+        let frame_len = (sample_rate as usize * frame_ms as usize) / 1000;
+        Self {
+            threshold_db,
+            snr_threshold_db: SPECTRAL_THRESHOLDS_DB[aggressiveness.min(3) as usize],
+            analyzer: BandAnalyzer::new(&SPECTRAL_BANDS, sample_rate, frame_len),
+            noise: vec![
+                NoiseFloor::new(SPECTRAL_NOISE_RISE_DB_PER_SEC, frame_ms);
+                SPECTRAL_BANDS.len()
+            ],
+        }
+    }
+}
+
+impl VoiceDetector for SpectralDetector {
+    fn is_speech(&mut self, samples: &[i16]) -> bool {
+        let loud_enough = spectrum::rms_db(samples) > self.threshold_db;
+
+        let mut weighted_snr = 0.0;
+        let levels = self.analyzer.levels_db(samples);
+        for ((&level, noise), weight) in levels
+            .iter()
+            .zip(self.noise.iter_mut())
+            .zip(SPECTRAL_WEIGHTS)
+        {
+            weighted_snr += weight * (level - noise.db).max(0.0);
+            noise.update(level);
+        }
+
+        loud_enough && weighted_snr > self.snr_threshold_db
+    }
+}
+
+/**
+ * Struct: Vad
+ * Responsibility: Smooths per-frame decisions into speech segments.
+ * Speech starts after `start_ms` of consecutive voiced frames and ends after
+ * `hangover_ms` of unvoiced ones; a silence timeout fires once per pause.
+ */
+pub struct Vad {
+    detector: Box<dyn VoiceDetector>,
+    frame_ms: u64,
+    start_frames: u32,
+    hangover_frames: u32,
+    silence_timeout_ms: u64,
+    speaking: bool,
+    /// Consecutive frames disagreeing with `speaking`.
+    run: u32,
+    speech_started_ms: u64,
+    silence_started_ms: u64,
+    timeout_fired: bool,
+}
+
+impl Vad {
+    pub fn new(config: &VadConfig, sample_rate: u32, frame_ms: u32) -> Self {
+        let detector: Box<dyn VoiceDetector> = match config.mode {
+            VadMode::Energy => Box::new(EnergyDetector::new(config.threshold_db, frame_ms)),
+            VadMode::Spectral => Box::new(SpectralDetector::new(
+                config.threshold_db,
+                config.aggressiveness,
+                sample_rate,
+                frame_ms,
+            )),
+        };
+        Self::with_detector(detector, config, frame_ms)
+    }
+
+    pub fn with_detector(
+        detector: Box<dyn VoiceDetector>,
+        config: &VadConfig,
+        frame_ms: u32,
+    ) -> Self {
+        let frames = |ms: u32| ms.div_ceil(frame_ms.max(1)).max(1);
+        Self {
+            detector,
+            frame_ms: frame_ms as u64,
+            start_frames: frames(config.start_ms),
+            hangover_frames: frames(config.hangover_ms),
+            silence_timeout_ms: config.silence_timeout_ms,
+            speaking: false,
+            run: 0,
+            speech_started_ms: 0,
+            silence_started_ms: 0,
+            timeout_fired: false,
+        }
+    }
+
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    /**
+     * Function: process
+     * Classifies one frame and returns the transition it caused, if any.
+     */
+    pub fn process(&mut self, frame: &AudioFrame) -> Option<VadEvent> {
+        let voiced = self.detector.is_speech(&frame.samples);
+        let frame_end_ms = frame.timestamp_ms + self.frame_ms;
+
+        if voiced == self.speaking {
+            self.run = 0;
+        } else {
+            self.run += 1;
+        }
+
+        if !self.speaking && self.run >= self.start_frames {
+            // Backdate to the first voiced frame of the run.
+            let start = frame_end_ms.saturating_sub(self.run as u64 * self.frame_ms);
+            self.speaking = true;
+            self.run = 0;
+            self.speech_started_ms = start;
+            return Some(VadEvent::SpeechStart {
+                timestamp_ms: start,
+            });
+        }
+
+        if self.speaking && self.run >= self.hangover_frames {
+            let end = frame_end_ms.saturating_sub(self.run as u64 * self.frame_ms);
+            self.speaking = false;
+            self.run = 0;
+            self.silence_started_ms = end;
+            self.timeout_fired = false;
+            return Some(VadEvent::SpeechEnd {
+                timestamp_ms: end,
+                duration_ms: end.saturating_sub(self.speech_started_ms),
+            });
+        }
+
+        if !self.speaking && self.silence_timeout_ms > 0 && !self.timeout_fired {
+            let silence_ms = frame_end_ms.saturating_sub(self.silence_started_ms);
+            if silence_ms >= self.silence_timeout_ms {
+                self.timeout_fired = true;
+                return Some(VadEvent::SilenceTimeout {
+                    timestamp_ms: frame_end_ms,
+                    silence_ms,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+/**
+ * Struct: SilenceGate
+ * Responsibility: Lets only speech frames through to a provider.
+ * Unvoiced frames are held in a short pre-roll so the onset that `Vad` needed
+ * to confirm speech (and a little before it) is not clipped.
+ */
+pub struct SilenceGate {
+    preroll: VecDeque<AudioFrame>,
+    preroll_ms: u64,
+}
+
+impl SilenceGate {
+    pub fn new(preroll_ms: u64) -> Self {
+        Self {
+            preroll: VecDeque::new(),
+            preroll_ms,
+        }
+    }
+
+    /// Feeds one frame and calls `send` for every frame that should reach the provider.
+    pub fn admit(&mut self, frame: AudioFrame, mut send: impl FnMut(AudioFrame)) {
+        if frame.is_speech {
+            for held in self.preroll.drain(..) {
+                send(held);
+            }
+            send(frame);
+            return;
+        }
+
+        self.preroll.push_back(frame);
+        while let (Some(first), Some(last)) = (self.preroll.front(), self.preroll.back()) {
+            if last.timestamp_ms - first.timestamp_ms < self.preroll_ms {
+                break;
+            }
+            self.preroll.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::spectrum::tone;
+
+    const RATE: u32 = 16_000;
+    const FRAME_MS: u32 = 20;
+    const FRAME_LEN: usize = (RATE * FRAME_MS / 1000) as usize;
+
+    /// Speech exactly when the frame is not digital silence, so `Vad` is tested alone.
+    struct Scripted;
+
+    impl VoiceDetector for Scripted {
+        fn is_speech(&mut self, samples: &[i16]) -> bool {
+            samples.iter().any(|&s| s != 0)
+        }
+    }
+
+    fn frame(index: u64, samples: Vec<i16>) -> AudioFrame {
+        AudioFrame {
+            samples,
+            sample_rate: RATE,
+            timestamp_ms: index * FRAME_MS as u64,
+            is_speech: false,
+        }
+    }
+
+    fn silence() -> Vec<i16> {
+        vec![0; FRAME_LEN]
+    }
+
+    fn voice() -> Vec<i16> {
+        tone(300.0, 0.3, RATE, FRAME_LEN)
+    }
+
+    /// Runs `voiced` (one entry per frame) through a scripted `Vad`, returning each
+    /// event with the index of the frame that produced it.
+    fn run(config: &VadConfig, voiced: &[bool]) -> Vec<(usize, VadEvent)> {
+        let mut vad = Vad::with_detector(Box::new(Scripted), config, FRAME_MS);
+        voiced
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &v)| {
+                let samples = if v { voice() } else { silence() };
+                vad.process(&frame(i as u64, samples)).map(|e| (i, e))
+            })
+            .collect()
+    }
+
+    fn config(start_ms: u32, hangover_ms: u32, silence_timeout_ms: u64) -> VadConfig {
+        VadConfig {
+            start_ms,
+            hangover_ms,
+            silence_timeout_ms,
+            ..VadConfig::default()
+        }
+    }
+
+    #[test]
+    fn speech_starts_after_start_ms_and_is_backdated_to_its_onset() {
+        let mut voiced = vec![false; 5];
+        voiced.extend([true; 3]);
+        let events = run(&config(60, 300, 0), &voiced);
+        assert_eq!(events, [(7, VadEvent::SpeechStart { timestamp_ms: 100 })]);
+    }
+
+    #[test]
+    fn blips_shorter_than_start_ms_are_ignored() {
+        let voiced = [true, true, false, true, true, false, false];
+        assert!(run(&config(60, 300, 0), &voiced).is_empty());
+    }
+
+    #[test]
+    fn speech_ends_after_the_hangover_at_the_first_silent_frame() {
+        let mut voiced = vec![true; 10];
+        // A pause shorter than the hangover does not split the segment.
+        voiced.extend([false; 5]);
+        voiced.extend([true; 5]);
+        voiced.extend([false; 15]);
+        let events = run(&config(60, 300, 0), &voiced);
+        assert_eq!(
+            events,
+            [
+                (2, VadEvent::SpeechStart { timestamp_ms: 0 }),
+                (
+                    34,
+                    VadEvent::SpeechEnd {
+                        timestamp_ms: 400,
+                        duration_ms: 400,
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn silence_timeout_counts_from_the_end_of_speech_and_fires_once() {
+        let mut voiced = vec![true; 10];
+        voiced.extend([false; 100]);
+        let events = run(&config(60, 300, 1000), &voiced);
+        // Speech ends at 200 ms, so one second of silence is complete at 1200 ms: the
+        // end of frame 59, although the hangover only confirmed the pause at frame 24.
+        assert_eq!(
+            events[1..],
+            [
+                (
+                    24,
+                    VadEvent::SpeechEnd {
+                        timestamp_ms: 200,
+                        duration_ms: 200,
+                    }
+                ),
+                (
+                    59,
+                    VadEvent::SilenceTimeout {
+                        timestamp_ms: 1200,
+                        silence_ms: 1000,
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn silence_timeout_also_fires_before_anyone_spoke() {
+        let events = run(&config(60, 300, 500), &[false; 40]);
+        assert_eq!(
+            events,
+            [(
+                24,
+                VadEvent::SilenceTimeout {
+                    timestamp_ms: 500,
+                    silence_ms: 500,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn energy_detector_needs_a_level_above_threshold_and_noise_floor() {
+        let mut detector = EnergyDetector::new(-45.0, FRAME_MS);
+        assert!(!detector.is_speech(&silence()));
+        // About -63 dBFS: above the floor, below the threshold.
+        assert!(!detector.is_speech(&tone(300.0, 0.001, RATE, FRAME_LEN)));
+        assert!(detector.is_speech(&voice()));
+    }
+
+    #[test]
+    fn energy_detector_absorbs_steady_noise_into_its_floor() {
+        let mut detector = EnergyDetector::new(-60.0, FRAME_MS);
+        // A fan at about -43 dBFS counts as speech until the floor has crept up to it.
+        let fan = tone(100.0, 0.01, RATE, FRAME_LEN);
+        assert!(detector.is_speech(&fan));
+        for _ in 0..60_000 / FRAME_MS {
+            detector.is_speech(&fan);
+        }
+        assert!(!detector.is_speech(&fan));
+        // Within the margin above the fan is still noise; well above it is speech.
+        assert!(!detector.is_speech(&tone(100.0, 0.02, RATE, FRAME_LEN)));
+        assert!(detector.is_speech(&voice()));
+    }
+
+    #[test]
+    fn spectral_detector_finds_tones_in_the_speech_bands_only() {
+        let mut detector = SpectralDetector::new(-45.0, 2, RATE, FRAME_MS);
+        assert!(!detector.is_speech(&silence()));
+        assert!(detector.is_speech(&tone(700.0, 0.3, RATE, FRAME_LEN)));
+
+        let mut detector = SpectralDetector::new(-45.0, 2, RATE, FRAME_MS);
+        assert!(!detector.is_speech(&silence()));
+        assert!(!detector.is_speech(&tone(6_000.0, 0.3, RATE, FRAME_LEN)));
+        // Quieter than `threshold_db` counts as silence whatever the spectrum.
+        assert!(!detector.is_speech(&tone(700.0, 0.001, RATE, FRAME_LEN)));
+    }
+
+    #[test]
+    fn silence_gate_sends_speech_with_its_preroll() {
+        let mut gate = SilenceGate::new(100);
+        let mut sent = Vec::new();
+        for i in 0..10 {
+            gate.admit(frame(i, silence()), |f| sent.push(f.timestamp_ms));
+        }
+        assert!(sent.is_empty());
+
+        let speech = AudioFrame {
+            is_speech: true,
+            ..frame(10, voice())
+        };
+        gate.admit(speech.clone(), |f| sent.push(f.timestamp_ms));
+        assert_eq!(sent, [100, 120, 140, 160, 180, 200]);
+
+        sent.clear();
+        gate.admit(frame(11, silence()), |f| sent.push(f.timestamp_ms));
+        assert!(sent.is_empty());
+        gate.admit(
+            AudioFrame {
+                timestamp_ms: 240,
+                ..speech
+            },
+            |f| sent.push(f.timestamp_ms),
+        );
+        assert_eq!(sent, [220, 240]);
+    }
+}
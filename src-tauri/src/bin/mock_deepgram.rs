@@ -0,0 +1,341 @@
+//! Local stand-in for Deepgram's streaming `listen` endpoint.
+//!
+//! Replies to incoming audio with scripted `Results` messages read from a JSON fixture,
+//! so transcription sessions can run without network access or an API key.
+//!
+//! Usage:
+//!   cargo run --bin mock_deepgram -- fixtures/mock-deepgram/basic.json [--port 8765] [--api-key KEY]
+//!
+//! Then point the app at it:
+//!   DEEPGRAM_BASE_URL=ws://127.0.0.1:8765/v1/listen DEEPGRAM_API_KEY=dev npm run tauri dev
+
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+const DEFAULT_PORT: u16 = 8765;
+
+/**
+ * Struct: Fixture
+ * One script per connection. Connection N uses `connections[N]`; once the list is
+ * exhausted the last script is reused, which makes reconnection scenarios easy to write.
+ */
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    connections: Vec<Script>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Script {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Step {
+    /// Fire once this much audio (in ms at the negotiated sample rate) has been received.
+    #[serde(default)]
+    after_audio_ms: u64,
+    #[serde(flatten)]
+    action: Action,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+enum Action {
+    /// A `Results` message built from plain text.
+    #[serde(rename_all = "camelCase")]
+    Result {
+        transcript: String,
+        #[serde(default)]
+        is_final: bool,
+        #[serde(default)]
+        speech_final: bool,
+    },
+    /// Any JSON message sent verbatim (errors, Metadata, UtteranceEnd, ...).
+    Raw { message: Value },
+    /// Close the socket with the given code, like Deepgram's idle timeout (1011).
+    Close {
+        code: u16,
+        #[serde(default)]
+        reason: String,
+    },
+    /// Drop the TCP connection without a close frame.
+    Disconnect,
+}
+
+struct Config {
+    fixture: Fixture,
+    port: u16,
+    api_key: Option<String>,
+}
+
+fn parse_args() -> Result<Config, String> {
+    let mut args = std::env::args().skip(1);
+    let mut fixture_path = None;
+    let mut port = DEFAULT_PORT;
+    let mut api_key = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                port = args
+                    .next()
+                    .and_then(|p| p.parse().ok())
+                    .ok_or("--port needs a number")?;
+            }
+            "--api-key" => api_key = Some(args.next().ok_or("--api-key needs a value")?),
+            _ => fixture_path = Some(arg),
+        }
+    }
+
+    let path =
+        fixture_path.ok_or("usage: mock_deepgram <fixture.json> [--port N] [--api-key KEY]")?;
+    let contents = fs::read_to_string(&path).map_err(|e| format!("{path}: {e}"))?;
+    let fixture: Fixture = serde_json::from_str(&contents).map_err(|e| format!("{path}: {e}"))?;
+    if fixture.connections.is_empty() {
+        return Err(format!("{path}: fixture has no connections"));
+    }
+
+    Ok(Config {
+        fixture,
+        port,
+        api_key,
+    })
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let config = match parse_args() {
+        Ok(config) => Arc::new(config),
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+
+    let listener = TcpListener::bind(("127.0.0.1", config.port))
+        .await
+        .expect("failed to bind mock server port");
+    println!(
+        "mock deepgram listening on ws://127.0.0.1:{}/v1/listen",
+        config.port
+    );
+
+    let connections = Arc::new(AtomicUsize::new(0));
+    while let Ok((stream, _)) = listener.accept().await {
+        let index = connections.fetch_add(1, Ordering::SeqCst);
+        let config = config.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, index, &config).await {
+                eprintln!("[conn {index}] {e}");
+            }
+        });
+    }
+}
+
+/**
+ * Function: handle_connection
+ * Responsibility: Runs one scripted session: checks auth, counts audio bytes, fires
+ * steps as their audio threshold is reached and answers control messages.
+ */
+async fn handle_connection(stream: TcpStream, index: usize, config: &Config) -> Result<(), String> {
+    let scripts = &config.fixture.connections;
+    let script = scripts[index.min(scripts.len() - 1)].clone();
+    let mut sample_rate = 16_000u64;
+
+    let expected_key = config.api_key.clone();
+    // The signature is fixed by tungstenite's handshake `Callback`.
+    #[allow(clippy::result_large_err)]
+    let callback = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+        if let Some(rate) = request
+            .uri()
+            .query()
+            .and_then(|q| query_param(q, "sample_rate"))
+        {
+            sample_rate = rate.parse().unwrap_or(sample_rate);
+        }
+        let token = request
+            .headers()
+            .get("Authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Token "));
+        let authorized = match (&expected_key, token) {
+            (Some(expected), Some(token)) => expected == token,
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+        if authorized {
+            Ok(response)
+        } else {
+            let mut rejection = ErrorResponse::new(Some("Invalid credentials".into()));
+            *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(rejection)
+        }
+    };
+
+    let socket = tokio_tungstenite::accept_hdr_async(stream, callback)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (mut sink, mut source) = socket.split();
+    println!("[conn {index}] opened ({sample_rate} Hz)");
+
+    let bytes_per_ms = sample_rate * 2 / 1000;
+    let mut audio_bytes = 0u64;
+    let mut keepalives = 0;
+    let mut steps = script.steps.into_iter().peekable();
+    let mut last_final_end = 0.0;
+
+    while let Some(message) = source.next().await {
+        let message = message.map_err(|e| e.to_string())?;
+        let mut finalize = false;
+
+        match message {
+            Message::Binary(bytes) => audio_bytes += bytes.len() as u64,
+            Message::Text(text) => {
+                let kind = serde_json::from_str::<Value>(&text)
+                    .ok()
+                    .and_then(|v| v.get("type").and_then(Value::as_str).map(str::to_string));
+                match kind.as_deref() {
+                    Some("KeepAlive") => keepalives += 1,
+                    Some("Finalize") => finalize = true,
+                    Some("CloseStream") => {
+                        let metadata = json!({ "type": "Metadata", "keepalives": keepalives });
+                        let _ = sink.send(Message::text(metadata.to_string())).await;
+                        let _ = sink.send(Message::Close(None)).await;
+                        println!("[conn {index}] closed by client");
+                        return Ok(());
+                    }
+                    other => println!("[conn {index}] ignoring control message {other:?}"),
+                }
+            }
+            Message::Close(_) => return Ok(()),
+            _ => {}
+        }
+
+        let received_ms = audio_bytes / bytes_per_ms.max(1);
+        while let Some(step) = steps.next_if(|s| s.after_audio_ms <= received_ms) {
+            match step.action {
+                Action::Result {
+                    transcript,
+                    is_final,
+                    speech_final,
+                } => {
+                    let end = step.after_audio_ms as f64 / 1000.0;
+                    let message = results_message(
+                        &transcript,
+                        last_final_end,
+                        end,
+                        is_final,
+                        speech_final,
+                        false,
+                    );
+                    if is_final {
+                        last_final_end = end;
+                    }
+                    sink.send(Message::text(message.to_string()))
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+                Action::Raw { message } => {
+                    sink.send(Message::text(message.to_string()))
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+                Action::Close { code, reason } => {
+                    let frame = CloseFrame {
+                        code: CloseCode::from(code),
+                        reason: reason.into(),
+                    };
+                    let _ = sink.send(Message::Close(Some(frame))).await;
+                    println!("[conn {index}] closed by script ({code})");
+                    return Ok(());
+                }
+                Action::Disconnect => {
+                    println!("[conn {index}] dropped by script");
+                    return Ok(());
+                }
+            }
+        }
+
+        if finalize {
+            let end = received_ms as f64 / 1000.0;
+            let message = results_message("", last_final_end, end, true, true, true);
+            last_final_end = end;
+            sink.send(Message::text(message.to_string()))
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/**
+ * Helper: results_message
+ * Builds a Deepgram-shaped `Results` message, spreading words evenly over [start, end].
+ */
+fn results_message(
+    transcript: &str,
+    start: f64,
+    end: f64,
+    is_final: bool,
+    speech_final: bool,
+    from_finalize: bool,
+) -> Value {
+    let duration = (end - start).max(0.0);
+    let tokens: Vec<&str> = transcript.split_whitespace().collect();
+    let step = if tokens.is_empty() {
+        0.0
+    } else {
+        duration / tokens.len() as f64
+    };
+    let words: Vec<Value> = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            json!({
+                "word": token.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase(),
+                "start": start + step * i as f64,
+                "end": start + step * (i + 1) as f64,
+                "confidence": 0.99,
+                "punctuated_word": token,
+            })
+        })
+        .collect();
+
+    json!({
+        "type": "Results",
+        "channel_index": [0, 1],
+        "start": start,
+        "duration": duration,
+        "is_final": is_final,
+        "speech_final": speech_final,
+        "from_finalize": from_finalize,
+        "channel": {
+            "alternatives": [{
+                "transcript": transcript,
+                "confidence": 0.99,
+                "words": words,
+            }]
+        }
+    })
+}
@@ -0,0 +1,174 @@
+use tauri::image::Image;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/**
+ * Enum: ClipboardContents
+ * What the clipboard held, in the formats we can read back and write again.
+ * Formats the clipboard plugin cannot read (HTML, files, ...) are not preserved.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardContents {
+    Empty,
+    Text(String),
+    Image {
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    },
+}
+
+/**
+ * Trait: ClipboardBackend
+ * Responsibility: Reads and writes the system clipboard. Delivery only depends on this
+ * trait, so a fake clipboard can drive the preserve/restore logic.
+ * Reads may block on the clipboard owner; never call them on the main thread.
+ */
+pub trait ClipboardBackend: Send + Sync {
+    fn read(&self) -> Result<ClipboardContents, String>;
+
+    fn write(&self, contents: &ClipboardContents) -> Result<(), String>;
+}
+
+/**
+ * Struct: SystemClipboard
+ * The real clipboard, through `tauri_plugin_clipboard_manager`.
+ */
+pub struct SystemClipboard(pub AppHandle);
+
+impl ClipboardBackend for SystemClipboard {
+    fn read(&self) -> Result<ClipboardContents, String> {
+        let clipboard = self.0.clipboard();
+        // Reading text fails when the clipboard holds something else, so try each format.
+        if let Ok(text) = clipboard.read_text() {
+            return Ok(ClipboardContents::Text(text));
+        }
+        if let Ok(image) = clipboard.read_image() {
+            return Ok(ClipboardContents::Image {
+                width: image.width(),
+                height: image.height(),
+                rgba: image.rgba().to_vec(),
+            });
+        }
+        Ok(ClipboardContents::Empty)
+    }
+
+    fn write(&self, contents: &ClipboardContents) -> Result<(), String> {
+        let clipboard = self.0.clipboard();
+        let result = match contents {
+            ClipboardContents::Empty => clipboard.clear(),
+            ClipboardContents::Text(text) => clipboard.write_text(text.as_str()),
+            ClipboardContents::Image {
+                width,
+                height,
+                rgba,
+            } => clipboard.write_image(&Image::new(rgba, *width, *height)),
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
+
+/**
+ * Function: restore_if_unchanged
+ * Puts `snapshot` back, unless the clipboard no longer holds `written` (the user or
+ * another app copied something since). Returns whether it restored.
+ * Only text and images survive the round trip; anything else the clipboard held
+ * before was read as `Empty` and is lost.
+ */
+pub fn restore_if_unchanged(
+    clipboard: &dyn ClipboardBackend,
+    snapshot: &ClipboardContents,
+    written: &str,
+) -> Result<bool, String> {
+    match clipboard.read()? {
+        ClipboardContents::Text(current) if current == written => {
+            clipboard.write(snapshot)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/**
+ * Struct: FakeClipboard
+ * A clipboard held in memory, for tests.
+ */
+#[cfg(test)]
+pub struct FakeClipboard(std::sync::Mutex<ClipboardContents>);
+
+#[cfg(test)]
+impl FakeClipboard {
+    pub fn holding(contents: ClipboardContents) -> Self {
+        Self(std::sync::Mutex::new(contents))
+    }
+
+    pub fn contents(&self) -> ClipboardContents {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl ClipboardBackend for FakeClipboard {
+    fn read(&self) -> Result<ClipboardContents, String> {
+        Ok(self.contents())
+    }
+
+    fn write(&self, contents: &ClipboardContents) -> Result<(), String> {
+        *self.0.lock().unwrap() = contents.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> ClipboardContents {
+        ClipboardContents::Image {
+            width: 1,
+            height: 2,
+            rgba: vec![1, 2, 3, 4, 5, 6, 7, 8],
+        }
+    }
+
+    fn pasted() -> ClipboardContents {
+        ClipboardContents::Text("hello world".into())
+    }
+
+    #[test]
+    fn unchanged_clipboard_is_restored() {
+        for snapshot in [
+            ClipboardContents::Text("earlier copy".into()),
+            image(),
+            ClipboardContents::Empty,
+        ] {
+            let clipboard = FakeClipboard::holding(pasted());
+            assert_eq!(
+                restore_if_unchanged(&clipboard, &snapshot, "hello world"),
+                Ok(true)
+            );
+            assert_eq!(clipboard.contents(), snapshot);
+        }
+    }
+
+    #[test]
+    fn clipboard_changed_by_the_user_is_left_alone() {
+        let clipboard = FakeClipboard::holding(pasted());
+        let snapshot = ClipboardContents::Text("earlier copy".into());
+
+        let copied_since = ClipboardContents::Text("copied since".into());
+        clipboard.write(&copied_since).unwrap();
+        assert_eq!(
+            restore_if_unchanged(&clipboard, &snapshot, "hello world"),
+            Ok(false)
+        );
+        assert_eq!(clipboard.contents(), copied_since);
+
+        clipboard.write(&image()).unwrap();
+        assert_eq!(
+            restore_if_unchanged(&clipboard, &snapshot, "hello world"),
+            Ok(false)
+        );
+        assert_eq!(clipboard.contents(), image());
+    }
+}
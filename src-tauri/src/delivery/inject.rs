@@ -0,0 +1,114 @@
+use serde::Deserialize;
+
+#[cfg(target_os = "linux")]
+use super::{uinput::UinputInjector, xtest::XTestInjector};
+
+/**
+ * Enum: Key
+ * Keys the injectors can press as chords. Kept to what the paste chords need;
+ * text goes through `KeyInjector::type_char`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Control,
+    Shift,
+    Insert,
+    V,
+}
+
+/**
+ * Enum: PasteKeys
+ * The chord that pastes in the target application.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteKeys {
+    #[default]
+    CtrlV,
+    /// Most terminal emulators.
+    CtrlShiftV,
+    ShiftInsert,
+}
+
+impl PasteKeys {
+    pub fn chord(self) -> &'static [Key] {
+        match self {
+            PasteKeys::CtrlV => &[Key::Control, Key::V],
+            PasteKeys::CtrlShiftV => &[Key::Control, Key::Shift, Key::V],
+            PasteKeys::ShiftInsert => &[Key::Shift, Key::Insert],
+        }
+    }
+}
+
+/**
+ * Enum: InjectorBackend
+ * How keystrokes reach other applications. `Auto` uses XTest on X11 sessions and
+ * falls back to a uinput virtual keyboard (Wayland, headless, or no X server).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InjectorBackend {
+    #[default]
+    Auto,
+    X11,
+    Uinput,
+}
+
+/**
+ * Trait: KeyInjector
+ * Responsibility: Presses keys and types text in whichever window has input focus.
+ * Window targeting is optional; backends that cannot see windows (uinput) keep the
+ * defaults and always act on the focused one.
+ */
+pub trait KeyInjector: Send {
+    fn backend(&self) -> InjectorBackend;
+
+    /// Presses `keys` in order, then releases them in reverse.
+    fn chord(&mut self, keys: &[Key]) -> Result<(), String>;
+
+    /// Types one character, whatever keys the backend needs for it.
+    fn type_char(&mut self, c: char) -> Result<(), String>;
+
+    /// Opaque id of the window that currently has input focus.
+    fn focused_window(&mut self) -> Option<u32> {
+        None
+    }
+
+    fn focus_window(&mut self, _window: u32) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Application class of a window (e.g. `"firefox"`), for per-application settings.
+    fn window_class(&mut self, _window: u32) -> Option<String> {
+        None
+    }
+}
+
+/**
+ * Function: open_injector
+ * Connects the requested backend. With `Auto`, XTest is tried first unless the session
+ * is Wayland, where XTest only reaches XWayland clients.
+ */
+#[cfg(target_os = "linux")]
+pub fn open_injector(backend: InjectorBackend) -> Result<Box<dyn KeyInjector>, String> {
+    match backend {
+        InjectorBackend::X11 => Ok(Box::new(XTestInjector::connect()?)),
+        InjectorBackend::Uinput => Ok(Box::new(UinputInjector::create()?)),
+        InjectorBackend::Auto => {
+            let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+            if !wayland && std::env::var_os("DISPLAY").is_some() {
+                if let Ok(injector) = XTestInjector::connect() {
+                    return Ok(Box::new(injector));
+                }
+            }
+            UinputInjector::create()
+                .map(|injector| Box::new(injector) as Box<dyn KeyInjector>)
+                .map_err(|e| format!("No keystroke backend available: {e}"))
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn open_injector(_backend: InjectorBackend) -> Result<Box<dyn KeyInjector>, String> {
+    Err("Auto-paste is only supported on Linux".into())
+}
@@ -0,0 +1,526 @@
+mod clipboard;
+mod inject;
+mod typing;
+#[cfg(target_os = "linux")]
+mod uinput;
+#[cfg(target_os = "linux")]
+mod xtest;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::Settings;
+use crate::stt::{FinalizeReason, SessionSummary};
+pub use clipboard::{restore_if_unchanged, ClipboardBackend, ClipboardContents, SystemClipboard};
+use inject::{open_injector, KeyInjector};
+pub use inject::{InjectorBackend, PasteKeys};
+use typing::EscapeWatch;
+pub use typing::TypingOptions;
+
+pub const TRANSCRIPT_DELIVERED_EVENT: &str = "transcript-delivered";
+
+/// How long a pasted transcript stays on the clipboard before it is restored.
+pub const DEFAULT_RESTORE_DELAY_MS: u32 = 750;
+
+/// Gives the clipboard time to change hands before the target application asks for it.
+const PASTE_DELAY: Duration = Duration::from_millis(60);
+
+/// Gives the window manager time to hand focus back to the target window.
+const FOCUS_DELAY: Duration = Duration::from_millis(80);
+
+/**
+ * Enum: InsertMode
+ * How the transcript is put into the target application.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InsertMode {
+    /// Copy, then press the paste chord.
+    #[default]
+    Paste,
+    /// Type it character by character, for applications that block paste.
+    Type,
+}
+
+/**
+ * Struct: DeliveryOptions
+ * What to do with a finished transcript. Part of `RecordingOptions`.
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeliveryOptions {
+    /// Write the transcript to the system clipboard (the "Auto Copy" setting).
+    pub copy_to_clipboard: bool,
+    /// Put the transcript into the window that was focused when the recording
+    /// started. Linux only.
+    #[serde(alias = "paste")]
+    pub insert: bool,
+    pub insert_mode: InsertMode,
+    /// Per-application `insert_mode`, keyed by window class (X11 `WM_CLASS`,
+    /// case-insensitive), e.g. `{ "xterm": "type" }`.
+    pub app_insert_modes: HashMap<String, InsertMode>,
+    pub paste_keys: PasteKeys,
+    pub injector: InjectorBackend,
+    /// After a successful paste, put back what the clipboard held before (text and
+    /// images only).
+    pub restore_clipboard: bool,
+    /// How long the transcript stays on the clipboard after pasting, so slow
+    /// applications can still read it.
+    pub restore_delay_ms: u32,
+    pub typing: TypingOptions,
+}
+
+impl Default for DeliveryOptions {
+    fn default() -> Self {
+        Self {
+            copy_to_clipboard: false,
+            insert: false,
+            insert_mode: InsertMode::default(),
+            app_insert_modes: HashMap::new(),
+            paste_keys: PasteKeys::default(),
+            injector: InjectorBackend::default(),
+            restore_clipboard: true,
+            restore_delay_ms: DEFAULT_RESTORE_DELAY_MS,
+            typing: TypingOptions::default(),
+        }
+    }
+}
+
+/// Delivery for a recording, as configured in Settings.
+impl From<&Settings> for DeliveryOptions {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            copy_to_clipboard: settings.auto_copy_paste,
+            insert: settings.auto_paste,
+            insert_mode: settings.insert_mode,
+            app_insert_modes: settings.app_insert_modes.clone(),
+            restore_clipboard: settings.restore_clipboard,
+            restore_delay_ms: settings.restore_delay_ms,
+            ..DeliveryOptions::default()
+        }
+    }
+}
+
+impl DeliveryOptions {
+    fn insert_mode_for(&self, class: Option<&str>) -> InsertMode {
+        class
+            .and_then(|class| {
+                self.app_insert_modes
+                    .iter()
+                    .find(|(app, _)| app.eq_ignore_ascii_case(class))
+            })
+            .map_or(self.insert_mode, |(_, mode)| *mode)
+    }
+}
+
+/**
+ * Struct: Delivery
+ * Report of one delivery: exactly the text that was handed over, why finalization
+ * completed, and which outputs received it. Payload of `transcript-delivered`.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub text: String,
+    pub reason: FinalizeReason,
+    pub copied: bool,
+    pub pasted: bool,
+    pub typed: bool,
+    /// Why inserting failed (or that Escape cancelled typing). After a failed paste
+    /// the transcript is still on the clipboard.
+    pub insert_error: Option<String>,
+    /// Application class of the window it was inserted into, when known.
+    pub target_app: Option<String>,
+}
+
+/// Window focused when a recording started, and its application class.
+struct Target {
+    window: u32,
+    class: Option<String>,
+}
+
+/**
+ * Struct: DeliveryState
+ * Responsibility: Keeps the keystroke backend open between deliveries (a new uinput
+ * device needs time to settle) and remembers which window to insert into.
+ */
+#[derive(Default)]
+pub struct DeliveryState {
+    injector: Mutex<Option<Box<dyn KeyInjector>>>,
+    target: Mutex<Option<Target>>,
+}
+
+impl DeliveryState {
+    /// Runs `f` with an open injector for `backend`. A failing injector is dropped, so
+    /// the next delivery reconnects.
+    fn with_injector<T>(
+        &self,
+        backend: InjectorBackend,
+        f: impl FnOnce(&mut dyn KeyInjector) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut slot = self.injector.lock().unwrap();
+        if slot.as_ref().is_some_and(|injector| {
+            backend != InjectorBackend::Auto && injector.backend() != backend
+        }) {
+            *slot = None;
+        }
+        let injector = match slot.as_mut() {
+            Some(injector) => injector,
+            None => slot.insert(open_injector(backend)?),
+        };
+        let result = f(injector.as_mut());
+        if result.is_err() {
+            *slot = None;
+        }
+        result
+    }
+
+    /**
+     * Function: remember_target
+     * Notes the focused window at recording start so the insert can go back to it.
+     * When our own window has focus (recording started from the widget) there is no
+     * target, and inserting is skipped if it still has focus at delivery.
+     */
+    pub fn remember_target(&self, app: &AppHandle, options: &DeliveryOptions) {
+        let target = if options.insert && !own_window_focused(app) {
+            self.with_injector(options.injector, |injector| {
+                Ok(injector.focused_window().map(|window| Target {
+                    window,
+                    class: injector.window_class(window),
+                }))
+            })
+            .ok()
+            .flatten()
+        } else {
+            None
+        };
+        *self.target.lock().unwrap() = target;
+    }
+}
+
+fn own_window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false)
+}
+
+/**
+ * Function: insert
+ * Pastes or types the transcript into the remembered target window (or whatever has
+ * focus, for backends that cannot target windows). Key events are paced with short
+ * sleeps, so this runs on a blocking thread.
+ */
+async fn insert(
+    app: &AppHandle,
+    options: &DeliveryOptions,
+    mode: InsertMode,
+    target: Option<Target>,
+    text: &str,
+) -> Result<(), String> {
+    if mode == InsertMode::Paste {
+        tokio::time::sleep(PASTE_DELAY).await;
+    }
+
+    let app = app.clone();
+    let options = options.clone();
+    let text = text.to_string();
+    let completed = tauri::async_runtime::spawn_blocking(move || {
+        let own_focused = own_window_focused(&app);
+        let escape = (mode == InsertMode::Type).then(|| EscapeWatch::start(&app));
+        app.state::<DeliveryState>()
+            .with_injector(options.injector, |injector| {
+                match target {
+                    Some(Target { window, .. }) if injector.focused_window() != Some(window) => {
+                        injector.focus_window(window)?;
+                        thread::sleep(FOCUS_DELAY);
+                    }
+                    None if own_focused => {
+                        return Err(
+                            "No window to insert into; start recording with the shortcut".into(),
+                        );
+                    }
+                    _ => {}
+                }
+                match &escape {
+                    Some(escape) => typing::type_text(injector, &text, &options.typing, escape),
+                    None => injector.chord(options.paste_keys.chord()).map(|()| true),
+                }
+            })
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    if completed {
+        Ok(())
+    } else {
+        Err("Typing cancelled with Escape".into())
+    }
+}
+
+/**
+ * Struct: Handover
+ * What `hand_over` did with a transcript, and what to put back on the clipboard later.
+ */
+#[derive(Debug, PartialEq)]
+struct Handover {
+    copied: bool,
+    pasted: bool,
+    typed: bool,
+    insert_error: Option<String>,
+    /// Clipboard contents to restore once the paste has gone through.
+    restore: Option<ClipboardContents>,
+}
+
+/**
+ * Function: hand_over
+ * Copies `text` and inserts it with `insert` as `options` and `mode` ask. Empty text
+ * never overwrites the clipboard. A failed copy fails; a failed insert is only reported.
+ * When the clipboard is written only to paste, what it held is snapshotted first and
+ * returned for restoring after a successful paste. With Auto Copy on, the transcript
+ * is meant to stay there, so nothing is restored.
+ */
+async fn hand_over<F, Fut>(
+    clipboard: &dyn ClipboardBackend,
+    text: &str,
+    options: &DeliveryOptions,
+    mode: Option<InsertMode>,
+    insert: F,
+) -> Result<Handover, String>
+where
+    F: FnOnce(InsertMode) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let pasting = mode == Some(InsertMode::Paste);
+    let copied = (options.copy_to_clipboard || pasting) && !text.is_empty();
+    let snapshot = if copied && pasting && !options.copy_to_clipboard && options.restore_clipboard {
+        clipboard.read().ok()
+    } else {
+        None
+    };
+    if copied {
+        clipboard
+            .write(&ClipboardContents::Text(text.to_string()))
+            .map_err(|e| format!("Failed to copy transcript: {e}"))?;
+    }
+
+    let inserted = match mode {
+        Some(mode) if !text.is_empty() => Some(insert(mode).await),
+        _ => None,
+    };
+    let succeeded = matches!(inserted, Some(Ok(())));
+    let pasted = pasting && succeeded;
+    Ok(Handover {
+        copied,
+        pasted,
+        typed: mode == Some(InsertMode::Type) && succeeded,
+        insert_error: inserted.and_then(Result::err),
+        restore: snapshot.filter(|_| pasted),
+    })
+}
+
+/**
+ * Function: deliver
+ * Responsibility: Hands the assembled transcript to its outputs (see `hand_over`).
+ * Runs only after finalization, so the text can no longer change underneath it.
+ * A clipboard snapshot taken for a paste is restored in the background after
+ * `restore_delay_ms`. Typing leaves the clipboard alone.
+ */
+pub async fn deliver(
+    app: &AppHandle,
+    summary: SessionSummary,
+    options: &DeliveryOptions,
+) -> Result<Delivery, String> {
+    let text = summary.transcript;
+
+    let clipboard = SystemClipboard(app.clone());
+    let target = app.state::<DeliveryState>().target.lock().unwrap().take();
+    let mode = options
+        .insert
+        .then(|| options.insert_mode_for(target.as_ref().and_then(|t| t.class.as_deref())));
+    let target_app = target.as_ref().and_then(|t| t.class.clone());
+
+    let handover = hand_over(&clipboard, &text, options, mode, |mode| {
+        insert(app, options, mode, target, &text)
+    })
+    .await?;
+
+    if let Some(snapshot) = handover.restore {
+        let delay = Duration::from_millis(options.restore_delay_ms as u64);
+        let written = text.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = restore_if_unchanged(&clipboard, &snapshot, &written);
+        });
+    }
+
+    let delivery = Delivery {
+        text,
+        reason: summary.reason,
+        copied: handover.copied,
+        pasted: handover.pasted,
+        typed: handover.typed,
+        insert_error: handover.insert_error,
+        target_app,
+    };
+    let _ = app.emit(TRANSCRIPT_DELIVERED_EVENT, &delivery);
+    Ok(delivery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clipboard::FakeClipboard;
+    use super::*;
+
+    #[test]
+    fn insert_mode_follows_settings_and_app_overrides() {
+        let settings = Settings {
+            auto_paste: true,
+            insert_mode: InsertMode::Paste,
+            app_insert_modes: HashMap::from([("XTerm".into(), InsertMode::Type)]),
+            ..Settings::default()
+        };
+        let options = DeliveryOptions::from(&settings);
+
+        assert!(options.insert);
+        assert_eq!(options.insert_mode_for(Some("xterm")), InsertMode::Type);
+        assert_eq!(options.insert_mode_for(Some("firefox")), InsertMode::Paste);
+        assert_eq!(options.insert_mode_for(None), InsertMode::Paste);
+    }
+
+    fn earlier() -> ClipboardContents {
+        ClipboardContents::Text("earlier copy".into())
+    }
+
+    fn transcript() -> ClipboardContents {
+        ClipboardContents::Text("hello world".into())
+    }
+
+    fn options(copy_to_clipboard: bool) -> DeliveryOptions {
+        DeliveryOptions {
+            copy_to_clipboard,
+            insert: true,
+            ..DeliveryOptions::default()
+        }
+    }
+
+    async fn hand_over_with(
+        clipboard: &FakeClipboard,
+        text: &str,
+        options: &DeliveryOptions,
+        mode: Option<InsertMode>,
+        result: Result<(), String>,
+    ) -> Handover {
+        hand_over(clipboard, text, options, mode, |_| async { result })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn paste_restores_the_clipboard_it_replaced() {
+        let clipboard = FakeClipboard::holding(earlier());
+        let handover = hand_over_with(
+            &clipboard,
+            "hello world",
+            &options(false),
+            Some(InsertMode::Paste),
+            Ok(()),
+        )
+        .await;
+
+        assert!(handover.copied && handover.pasted);
+        assert_eq!(clipboard.contents(), transcript());
+        let snapshot = handover.restore.unwrap();
+        assert_eq!(
+            restore_if_unchanged(&clipboard, &snapshot, "hello world"),
+            Ok(true)
+        );
+        assert_eq!(clipboard.contents(), earlier());
+    }
+
+    #[tokio::test]
+    async fn auto_copy_keeps_the_pasted_transcript_on_the_clipboard() {
+        let clipboard = FakeClipboard::holding(earlier());
+        let handover = hand_over_with(
+            &clipboard,
+            "hello world",
+            &options(true),
+            Some(InsertMode::Paste),
+            Ok(()),
+        )
+        .await;
+
+        assert!(handover.copied && handover.pasted);
+        assert_eq!(handover.restore, None);
+        assert_eq!(clipboard.contents(), transcript());
+    }
+
+    #[tokio::test]
+    async fn failed_paste_leaves_the_transcript_to_paste_by_hand() {
+        let clipboard = FakeClipboard::holding(earlier());
+        let handover = hand_over_with(
+            &clipboard,
+            "hello world",
+            &options(false),
+            Some(InsertMode::Paste),
+            Err("No window to paste into".into()),
+        )
+        .await;
+
+        assert!(handover.copied && !handover.pasted);
+        assert_eq!(
+            handover.insert_error.as_deref(),
+            Some("No window to paste into")
+        );
+        assert_eq!(handover.restore, None);
+        assert_eq!(clipboard.contents(), transcript());
+    }
+
+    #[tokio::test]
+    async fn empty_transcript_touches_nothing() {
+        let clipboard = FakeClipboard::holding(earlier());
+        let handover = hand_over(
+            &clipboard,
+            "",
+            &options(true),
+            Some(InsertMode::Paste),
+            |_| async { panic!("nothing to insert") },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            handover,
+            Handover {
+                copied: false,
+                pasted: false,
+                typed: false,
+                insert_error: None,
+                restore: None,
+            }
+        );
+        assert_eq!(clipboard.contents(), earlier());
+    }
+
+    #[tokio::test]
+    async fn typing_leaves_the_clipboard_alone() {
+        let clipboard = FakeClipboard::holding(earlier());
+        let handover = hand_over_with(
+            &clipboard,
+            "hello world",
+            &options(false),
+            Some(InsertMode::Type),
+            Ok(()),
+        )
+        .await;
+
+        assert!(handover.typed && !handover.copied);
+        assert_eq!(handover.restore, None);
+        assert_eq!(clipboard.contents(), earlier());
+    }
+}
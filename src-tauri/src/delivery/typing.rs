@@ -0,0 +1,100 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Shortcut, ShortcutState};
+
+use super::inject::KeyInjector;
+
+/**
+ * Struct: TypingOptions
+ * Settings of the `type` insert mode, part of `DeliveryOptions`.
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TypingOptions {
+    /// Upper bound on the typing rate. Slow remote desktops may need less.
+    pub chars_per_second: u32,
+}
+
+impl Default for TypingOptions {
+    fn default() -> Self {
+        Self {
+            chars_per_second: 60,
+        }
+    }
+}
+
+/**
+ * Struct: EscapeWatch
+ * Responsibility: Grabs Escape system-wide while typing, so the user can stop it from
+ * any application. Released on drop. If Escape cannot be grabbed, typing simply cannot
+ * be cancelled.
+ */
+pub struct EscapeWatch {
+    app: AppHandle,
+    shortcut: Option<Shortcut>,
+    pressed: Arc<AtomicBool>,
+}
+
+impl EscapeWatch {
+    pub fn start(app: &AppHandle) -> Self {
+        let escape = Shortcut::new(None, Code::Escape);
+        let pressed = Arc::new(AtomicBool::new(false));
+        let flag = pressed.clone();
+        let registered = app
+            .global_shortcut()
+            .on_shortcut(escape, move |_, _, event| {
+                if event.state() == ShortcutState::Pressed {
+                    flag.store(true, Ordering::SeqCst);
+                }
+            })
+            .is_ok();
+
+        Self {
+            app: app.clone(),
+            shortcut: registered.then_some(escape),
+            pressed,
+        }
+    }
+
+    pub fn pressed(&self) -> bool {
+        self.pressed.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for EscapeWatch {
+    fn drop(&mut self) {
+        if let Some(shortcut) = self.shortcut {
+            let _ = self.app.global_shortcut().unregister(shortcut);
+        }
+    }
+}
+
+/**
+ * Function: type_text
+ * Types `text` one character at a time, no faster than the configured rate.
+ * Returns `false` if Escape stopped it part-way.
+ */
+pub fn type_text(
+    injector: &mut dyn KeyInjector,
+    text: &str,
+    options: &TypingOptions,
+    escape: &EscapeWatch,
+) -> Result<bool, String> {
+    let interval = Duration::from_secs(1) / options.chars_per_second.max(1);
+    for c in text.chars() {
+        if escape.pressed() {
+            return Ok(false);
+        }
+        let started = Instant::now();
+        injector.type_char(c)?;
+        if let Some(rest) = interval.checked_sub(started.elapsed()) {
+            thread::sleep(rest);
+        }
+    }
+    Ok(true)
+}
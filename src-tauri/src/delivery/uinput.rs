@@ -0,0 +1,220 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use evdev::uinput::VirtualDevice;
+use evdev::{AttributeSet, KeyCode, KeyEvent};
+
+use super::inject::{InjectorBackend, Key, KeyInjector};
+
+const DEVICE_NAME: &str = "Wispr Flow virtual keyboard";
+
+/// A new uinput device is ignored until udev and the compositor have picked it up.
+const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+const KEY_DELAY: Duration = Duration::from_millis(8);
+
+/// Letters in alphabetical order, digits from 0; evdev numbers them by keyboard row.
+const LETTERS: [KeyCode; 26] = [
+    KeyCode::KEY_A,
+    KeyCode::KEY_B,
+    KeyCode::KEY_C,
+    KeyCode::KEY_D,
+    KeyCode::KEY_E,
+    KeyCode::KEY_F,
+    KeyCode::KEY_G,
+    KeyCode::KEY_H,
+    KeyCode::KEY_I,
+    KeyCode::KEY_J,
+    KeyCode::KEY_K,
+    KeyCode::KEY_L,
+    KeyCode::KEY_M,
+    KeyCode::KEY_N,
+    KeyCode::KEY_O,
+    KeyCode::KEY_P,
+    KeyCode::KEY_Q,
+    KeyCode::KEY_R,
+    KeyCode::KEY_S,
+    KeyCode::KEY_T,
+    KeyCode::KEY_U,
+    KeyCode::KEY_V,
+    KeyCode::KEY_W,
+    KeyCode::KEY_X,
+    KeyCode::KEY_Y,
+    KeyCode::KEY_Z,
+];
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::KEY_0,
+    KeyCode::KEY_1,
+    KeyCode::KEY_2,
+    KeyCode::KEY_3,
+    KeyCode::KEY_4,
+    KeyCode::KEY_5,
+    KeyCode::KEY_6,
+    KeyCode::KEY_7,
+    KeyCode::KEY_8,
+    KeyCode::KEY_9,
+];
+const PUNCTUATION: [KeyCode; 14] = [
+    KeyCode::KEY_SPACE,
+    KeyCode::KEY_ENTER,
+    KeyCode::KEY_TAB,
+    KeyCode::KEY_MINUS,
+    KeyCode::KEY_EQUAL,
+    KeyCode::KEY_LEFTBRACE,
+    KeyCode::KEY_RIGHTBRACE,
+    KeyCode::KEY_BACKSLASH,
+    KeyCode::KEY_SEMICOLON,
+    KeyCode::KEY_APOSTROPHE,
+    KeyCode::KEY_GRAVE,
+    KeyCode::KEY_COMMA,
+    KeyCode::KEY_DOT,
+    KeyCode::KEY_SLASH,
+];
+
+fn key_code(key: Key) -> KeyCode {
+    match key {
+        Key::Control => KeyCode::KEY_LEFTCTRL,
+        Key::Shift => KeyCode::KEY_LEFTSHIFT,
+        Key::Insert => KeyCode::KEY_INSERT,
+        Key::V => KeyCode::KEY_V,
+    }
+}
+
+/// Key and shift state producing `c` on a US layout, if any.
+fn us_key(c: char) -> Option<(KeyCode, bool)> {
+    let key = match c {
+        'a'..='z' => return Some((LETTERS[c as usize - 'a' as usize], false)),
+        'A'..='Z' => return Some((LETTERS[c as usize - 'A' as usize], true)),
+        '0'..='9' => return Some((DIGITS[c as usize - '0' as usize], false)),
+        ' ' => (KeyCode::KEY_SPACE, false),
+        '\n' => (KeyCode::KEY_ENTER, false),
+        '\t' => (KeyCode::KEY_TAB, false),
+        ')' => (KeyCode::KEY_0, true),
+        '!' => (KeyCode::KEY_1, true),
+        '@' => (KeyCode::KEY_2, true),
+        '#' => (KeyCode::KEY_3, true),
+        '$' => (KeyCode::KEY_4, true),
+        '%' => (KeyCode::KEY_5, true),
+        '^' => (KeyCode::KEY_6, true),
+        '&' => (KeyCode::KEY_7, true),
+        '*' => (KeyCode::KEY_8, true),
+        '(' => (KeyCode::KEY_9, true),
+        '-' => (KeyCode::KEY_MINUS, false),
+        '_' => (KeyCode::KEY_MINUS, true),
+        '=' => (KeyCode::KEY_EQUAL, false),
+        '+' => (KeyCode::KEY_EQUAL, true),
+        '[' => (KeyCode::KEY_LEFTBRACE, false),
+        '{' => (KeyCode::KEY_LEFTBRACE, true),
+        ']' => (KeyCode::KEY_RIGHTBRACE, false),
+        '}' => (KeyCode::KEY_RIGHTBRACE, true),
+        '\\' => (KeyCode::KEY_BACKSLASH, false),
+        '|' => (KeyCode::KEY_BACKSLASH, true),
+        ';' => (KeyCode::KEY_SEMICOLON, false),
+        ':' => (KeyCode::KEY_SEMICOLON, true),
+        '\'' => (KeyCode::KEY_APOSTROPHE, false),
+        '"' => (KeyCode::KEY_APOSTROPHE, true),
+        '`' => (KeyCode::KEY_GRAVE, false),
+        '~' => (KeyCode::KEY_GRAVE, true),
+        ',' => (KeyCode::KEY_COMMA, false),
+        '<' => (KeyCode::KEY_COMMA, true),
+        '.' => (KeyCode::KEY_DOT, false),
+        '>' => (KeyCode::KEY_DOT, true),
+        '/' => (KeyCode::KEY_SLASH, false),
+        '?' => (KeyCode::KEY_SLASH, true),
+        _ => return None,
+    };
+    Some(key)
+}
+
+/**
+ * Struct: UinputInjector
+ * Responsibility: Virtual keyboard created through /dev/uinput. Works below the display
+ * server (Wayland, X11 or a bare console), but sends physical key codes, so chords
+ * follow the active layout's mapping of those keys and typed text assumes a US layout.
+ * Characters a US keyboard cannot produce are entered as Ctrl+Shift+U and their hex
+ * code point, which GTK and IBus input methods understand.
+ */
+pub struct UinputInjector {
+    device: VirtualDevice,
+    created: Instant,
+}
+
+impl UinputInjector {
+    pub fn create() -> Result<Self, String> {
+        let mut keys = AttributeSet::<KeyCode>::new();
+        for key in [Key::Control, Key::Shift, Key::Insert, Key::V] {
+            keys.insert(key_code(key));
+        }
+        for key in LETTERS.iter().chain(&DIGITS).chain(&PUNCTUATION) {
+            keys.insert(*key);
+        }
+
+        let device = VirtualDevice::builder()
+            .and_then(|builder| builder.name(DEVICE_NAME).with_keys(&keys))
+            .and_then(|builder| builder.build())
+            .map_err(|e| {
+                format!("Cannot create uinput keyboard (is /dev/uinput writable?): {e}")
+            })?;
+        Ok(Self {
+            device,
+            created: Instant::now(),
+        })
+    }
+
+    fn settle(&self) {
+        if let Some(wait) = SETTLE_TIME.checked_sub(self.created.elapsed()) {
+            thread::sleep(wait);
+        }
+    }
+
+    fn send(&mut self, code: KeyCode, value: i32) -> Result<(), String> {
+        self.device
+            .emit(&[*KeyEvent::new(code, value)])
+            .map_err(|e| format!("uinput write failed: {e}"))?;
+        thread::sleep(KEY_DELAY);
+        Ok(())
+    }
+
+    fn press(&mut self, codes: &[KeyCode]) -> Result<(), String> {
+        for &code in codes {
+            self.send(code, 1)?;
+        }
+        for &code in codes.iter().rev() {
+            self.send(code, 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl KeyInjector for UinputInjector {
+    fn backend(&self) -> InjectorBackend {
+        InjectorBackend::Uinput
+    }
+
+    fn chord(&mut self, keys: &[Key]) -> Result<(), String> {
+        self.settle();
+        let codes: Vec<KeyCode> = keys.iter().map(|&key| key_code(key)).collect();
+        self.press(&codes)
+    }
+
+    fn type_char(&mut self, c: char) -> Result<(), String> {
+        self.settle();
+        match us_key(c) {
+            Some((code, false)) => self.press(&[code]),
+            Some((code, true)) => self.press(&[KeyCode::KEY_LEFTSHIFT, code]),
+            None => {
+                self.press(&[
+                    KeyCode::KEY_LEFTCTRL,
+                    KeyCode::KEY_LEFTSHIFT,
+                    KeyCode::KEY_U,
+                ])?;
+                for digit in format!("{:x}", c as u32).chars() {
+                    if let Some((code, _)) = us_key(digit) {
+                        self.press(&[code])?;
+                    }
+                }
+                self.press(&[KeyCode::KEY_SPACE])
+            }
+        }
+    }
+}
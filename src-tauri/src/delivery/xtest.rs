@@ -0,0 +1,232 @@
+use std::thread;
+use std::time::Duration;
+
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    self, AtomEnum, ConnectionExt as _, InputFocus, Keycode, Keysym, Window,
+};
+use x11rb::protocol::xtest::ConnectionExt as _;
+use x11rb::rust_connection::RustConnection;
+
+use super::inject::{InjectorBackend, Key, KeyInjector};
+
+/// Pause between fake key events; some toolkits drop events that arrive in one burst.
+const KEY_DELAY: Duration = Duration::from_millis(8);
+
+/// Clients re-read the keyboard mapping asynchronously after it changes.
+const REMAP_DELAY: Duration = Duration::from_millis(20);
+
+const SHIFT_L: Keysym = 0xffe1;
+
+/// How far up the window tree to look for `WM_CLASS` from the focused window.
+const MAX_TREE_DEPTH: usize = 8;
+
+fn keysym(key: Key) -> Keysym {
+    match key {
+        Key::Control => 0xffe3, // Control_L
+        Key::Shift => SHIFT_L,
+        Key::Insert => 0xff63,
+        Key::V => 0x0076,
+    }
+}
+
+/// Keysym typing `c`: Latin-1 maps directly, everything else uses the Unicode range.
+fn char_keysym(c: char) -> Keysym {
+    match c {
+        '\n' => 0xff0d, // Return
+        '\t' => 0xff09, // Tab
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as Keysym,
+        _ => 0x0100_0000 + c as Keysym,
+    }
+}
+
+/**
+ * Struct: KeyboardMapping
+ * Snapshot of the server's keycode → keysyms table.
+ */
+struct KeyboardMapping {
+    min_keycode: Keycode,
+    per_keycode: usize,
+    keysyms: Vec<Keysym>,
+}
+
+impl KeyboardMapping {
+    fn rows(&self) -> impl Iterator<Item = (Keycode, &[Keysym])> {
+        self.keysyms
+            .chunks(self.per_keycode)
+            .enumerate()
+            .map(|(index, syms)| (self.min_keycode + index as u8, syms))
+    }
+
+    /// Keycode producing `keysym`, and whether Shift is needed for it.
+    fn find(&self, keysym: Keysym) -> Option<(Keycode, bool)> {
+        self.rows().find_map(|(keycode, syms)| match syms {
+            [plain, ..] if *plain == keysym => Some((keycode, false)),
+            [_, shifted, ..] if *shifted == keysym => Some((keycode, true)),
+            _ => None,
+        })
+    }
+
+    /// A keycode with no symbols bound, free to borrow for characters the layout lacks.
+    fn spare(&self) -> Option<Keycode> {
+        self.rows()
+            .filter(|(_, syms)| syms.iter().all(|&sym| sym == 0))
+            .map(|(keycode, _)| keycode)
+            .last()
+    }
+}
+
+/**
+ * Struct: XTestInjector
+ * Responsibility: Fakes key events on the X server through the XTEST extension.
+ * Keys are looked up by keysym in the current keyboard mapping, so chords and typing
+ * work with any layout. Characters the layout cannot produce are typed by briefly
+ * binding their keysym to an unused keycode.
+ */
+pub struct XTestInjector {
+    conn: RustConnection,
+    root: Window,
+}
+
+impl XTestInjector {
+    pub fn connect() -> Result<Self, String> {
+        let (conn, screen) =
+            x11rb::connect(None).map_err(|e| format!("Cannot connect to X server: {e}"))?;
+        conn.xtest_get_version(2, 2)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| format!("X server has no XTEST extension: {e}"))?;
+        let root = conn.setup().roots[screen].root;
+        Ok(Self { conn, root })
+    }
+
+    fn mapping(&self) -> Result<KeyboardMapping, String> {
+        let setup = self.conn.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let reply = self
+            .conn
+            .get_keyboard_mapping(min, max - min + 1)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        Ok(KeyboardMapping {
+            min_keycode: min,
+            per_keycode: reply.keysyms_per_keycode.max(1) as usize,
+            keysyms: reply.keysyms,
+        })
+    }
+
+    fn fake(&self, event: u8, keycode: Keycode) -> Result<(), String> {
+        self.conn
+            .xtest_fake_input(event, keycode, x11rb::CURRENT_TIME, self.root, 0, 0, 0)
+            .map_err(|e| e.to_string())?;
+        self.conn.flush().map_err(|e| e.to_string())?;
+        thread::sleep(KEY_DELAY);
+        Ok(())
+    }
+
+    fn press(&self, keycodes: &[Keycode]) -> Result<(), String> {
+        for &keycode in keycodes {
+            self.fake(xproto::KEY_PRESS_EVENT, keycode)?;
+        }
+        for &keycode in keycodes.iter().rev() {
+            self.fake(xproto::KEY_RELEASE_EVENT, keycode)?;
+        }
+        Ok(())
+    }
+
+    fn bind(&self, keycode: Keycode, keysym: Keysym) -> Result<(), String> {
+        self.conn
+            .change_keyboard_mapping(1, keycode, 2, &[keysym, keysym])
+            .map_err(|e| e.to_string())?;
+        self.conn.flush().map_err(|e| e.to_string())?;
+        thread::sleep(REMAP_DELAY);
+        Ok(())
+    }
+}
+
+impl KeyInjector for XTestInjector {
+    fn backend(&self) -> InjectorBackend {
+        InjectorBackend::X11
+    }
+
+    fn chord(&mut self, keys: &[Key]) -> Result<(), String> {
+        let mapping = self.mapping()?;
+        let keycodes = keys
+            .iter()
+            .map(|&key| {
+                mapping
+                    .find(keysym(key))
+                    .map(|(keycode, _)| keycode)
+                    .ok_or_else(|| format!("No key produces {key:?} in the current layout"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.press(&keycodes)
+    }
+
+    fn type_char(&mut self, c: char) -> Result<(), String> {
+        let mapping = self.mapping()?;
+        let sym = char_keysym(c);
+        match mapping.find(sym) {
+            Some((keycode, false)) => self.press(&[keycode]),
+            Some((keycode, true)) => {
+                let (shift, _) = mapping
+                    .find(SHIFT_L)
+                    .ok_or("No Shift key in the current layout")?;
+                self.press(&[shift, keycode])
+            }
+            None => {
+                let spare = mapping
+                    .spare()
+                    .ok_or_else(|| format!("Cannot type {c:?}: no free keycode to bind"))?;
+                self.bind(spare, sym)?;
+                let typed = self.press(&[spare]);
+                self.bind(spare, 0)?;
+                typed
+            }
+        }
+    }
+
+    fn focused_window(&mut self) -> Option<u32> {
+        let focus = self.conn.get_input_focus().ok()?.reply().ok()?.focus;
+        // 0 is None and 1 is PointerRoot; neither is a window we can return to.
+        (focus > 1).then_some(focus)
+    }
+
+    fn focus_window(&mut self, window: u32) -> Result<(), String> {
+        self.conn
+            .set_input_focus(InputFocus::PARENT, window, x11rb::CURRENT_TIME)
+            .map_err(|e| e.to_string())?
+            .check()
+            .map_err(|e| format!("Cannot focus the target window: {e}"))
+    }
+
+    /// `WM_CLASS` of `window` or its nearest ancestor that has one (focus often sits
+    /// on a child of the top-level window).
+    fn window_class(&mut self, window: u32) -> Option<String> {
+        let mut window = window;
+        for _ in 0..MAX_TREE_DEPTH {
+            let property = self
+                .conn
+                .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+                .ok()?
+                .reply()
+                .ok()?;
+            // WM_CLASS is "instance\0class\0"; the class names the application.
+            if let Some(class) = property
+                .value
+                .split(|&b| b == 0)
+                .filter(|part| !part.is_empty())
+                .nth(1)
+            {
+                return Some(String::from_utf8_lossy(class).into_owned());
+            }
+            let tree = self.conn.query_tree(window).ok()?.reply().ok()?;
+            if tree.parent == tree.root || tree.parent == x11rb::NONE {
+                return None;
+            }
+            window = tree.parent;
+        }
+        None
+    }
+}
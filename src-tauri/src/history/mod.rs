@@ -0,0 +1,293 @@
+pub mod retention;
+pub mod search;
+pub mod store;
+
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::archive;
+use crate::delivery::{ClipboardBackend, ClipboardContents, Delivery, SystemClipboard};
+use crate::settings::SettingsState;
+use crate::stt::{unix_millis, SessionSummary};
+pub use retention::RetentionPolicy;
+pub use search::{SearchHit, SearchOptions, SearchPage, SnippetPart};
+pub use store::{DeliveryRecord, HistoryEntry, HistoryPage, HistoryStore, SessionRecord};
+
+pub const HISTORY_ENTRY_ADDED_EVENT: &str = "history-entry-added";
+pub const HISTORY_ENTRY_DELETED_EVENT: &str = "history-entry-deleted";
+/// Payload: the number of entries removed by retention or a wipe.
+pub const HISTORY_PURGED_EVENT: &str = "history-purged";
+pub const PRIVATE_SESSION_CHANGED_EVENT: &str = "private-session-changed";
+
+/// Database file in the app data dir.
+const HISTORY_FILE: &str = "history.sqlite3";
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// How often the retention policy is applied while the app runs, so age limits hold
+/// even when nothing new is dictated.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/**
+ * Struct: HistoryState
+ * Responsibility: The open history database, or none if it could not be opened; the
+ * app then works as before, without history. Also holds the private-session switch,
+ * which lasts until turned off or the app quits.
+ */
+#[derive(Default)]
+pub struct HistoryState {
+    store: Mutex<Option<HistoryStore>>,
+    private_session: AtomicBool,
+}
+
+impl HistoryState {
+    fn with_store<T>(
+        &self,
+        f: impl FnOnce(&HistoryStore) -> Result<T, String>,
+    ) -> Result<T, String> {
+        match self.store.lock().unwrap().as_ref() {
+            Some(store) => f(store),
+            None => Err("History is not available".into()),
+        }
+    }
+}
+
+impl From<&Delivery> for DeliveryRecord {
+    fn from(delivery: &Delivery) -> Self {
+        Self {
+            copied: delivery.copied,
+            pasted: delivery.pasted,
+            typed: delivery.typed,
+            insert_error: delivery.insert_error.clone(),
+            target_app: delivery.target_app.clone(),
+        }
+    }
+}
+
+fn retention_policy(app: &AppHandle) -> RetentionPolicy {
+    RetentionPolicy::from(&app.state::<SettingsState>().get())
+}
+
+/**
+ * Function: persistence_allowed
+ * Whether anything from a finished session may be written to disk: storing is on
+ * in the settings and no private session is running.
+ */
+pub fn persistence_allowed(app: &AppHandle) -> bool {
+    retention_policy(app).store
+        && !app
+            .state::<HistoryState>()
+            .private_session
+            .load(Ordering::SeqCst)
+}
+
+/**
+ * Function: enforce_retention
+ * Deletes whatever the retention settings no longer allow, from the history and from
+ * archived recordings.
+ */
+pub fn enforce_retention(app: &AppHandle) {
+    let policy = retention_policy(app);
+    let now = unix_millis(SystemTime::now());
+    archive::enforce_retention(app, &policy, now);
+    let purged = match app.state::<HistoryState>().store.lock().unwrap().as_ref() {
+        Some(store) => store.purge(&policy, now),
+        None => return,
+    };
+    match purged {
+        Ok(0) => {}
+        Ok(removed) => {
+            let _ = app.emit(HISTORY_PURGED_EVENT, removed);
+        }
+        Err(e) => eprintln!("Cannot apply history retention: {e}"),
+    }
+}
+
+/**
+ * Function: record
+ * Stores a finished session's final transcript, with where it was delivered if the
+ * backend delivered it, then applies the retention policy. Empty transcripts, and
+ * everything while storing is off or a private session runs, are not kept. Failures
+ * are logged only; history never gets in the way of a dictation.
+ */
+pub fn record(app: &AppHandle, summary: &SessionSummary, delivery: Option<&Delivery>) {
+    let text = summary.transcript.trim();
+    if text.is_empty() || !persistence_allowed(app) {
+        return;
+    }
+
+    let session = &summary.session;
+    let record = SessionRecord {
+        started_at: session.started_at,
+        ended_at: session.started_at + session.duration_ms,
+        duration_ms: session.duration_ms,
+        text: text.to_string(),
+        provider: session.provider.clone(),
+        model: session.model.clone(),
+        language: session.language.clone(),
+        finalize_reason: summary.reason.as_str().to_string(),
+        delivery: delivery.map(DeliveryRecord::from),
+    };
+
+    let state = app.state::<HistoryState>();
+    match state.with_store(|store| store.insert(&record)) {
+        Ok(id) => {
+            let _ = app.emit(HISTORY_ENTRY_ADDED_EVENT, HistoryEntry { id, record });
+            enforce_retention(app);
+        }
+        Err(e) => eprintln!("Cannot save transcript to history: {e}"),
+    }
+}
+
+/**
+ * Function: setup
+ * Responsibility: Opens (creating or migrating) the history database, applies the
+ * retention policy, and reapplies it every `PURGE_INTERVAL`.
+ * Called once from the Tauri `setup` hook, after the settings are loaded.
+ */
+pub fn setup(app: &AppHandle) {
+    let opened = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| {
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            HistoryStore::open(&dir.join(HISTORY_FILE))
+        });
+    match opened {
+        Ok(store) => *app.state::<HistoryState>().store.lock().unwrap() = Some(store),
+        Err(e) => {
+            eprintln!("History disabled: {e}");
+            return;
+        }
+    }
+
+    enforce_retention(app);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(PURGE_INTERVAL).await;
+            enforce_retention(&app);
+        }
+    });
+}
+
+/**
+ * Command: list_history
+ * Responsibility: One page of past dictations, newest first. Start without a cursor
+ * and pass each page's `nextCursor` to continue.
+ */
+#[tauri::command]
+pub fn list_history(
+    state: State<'_, HistoryState>,
+    cursor: Option<i64>,
+    limit: Option<u32>,
+) -> Result<HistoryPage, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    state.with_store(|store| store.page(cursor, limit))
+}
+
+/**
+ * Command: get_history_entry
+ * Responsibility: One entry by id, or `null` if it no longer exists.
+ */
+#[tauri::command]
+pub fn get_history_entry(
+    state: State<'_, HistoryState>,
+    id: i64,
+) -> Result<Option<HistoryEntry>, String> {
+    state.with_store(|store| store.get(id))
+}
+
+/**
+ * Command: copy_history_entry
+ * Responsibility: Puts an entry's text back on the clipboard, e.g. after it was pasted
+ * into the wrong window.
+ */
+#[tauri::command]
+pub async fn copy_history_entry(app: AppHandle, id: i64) -> Result<(), String> {
+    let entry = app
+        .state::<HistoryState>()
+        .with_store(|store| store.get(id))?
+        .ok_or("History entry not found")?;
+    SystemClipboard(app)
+        .write(&ClipboardContents::Text(entry.record.text))
+        .map_err(|e| format!("Failed to copy transcript: {e}"))
+}
+
+/**
+ * Command: delete_history_entry
+ * Responsibility: Removes one entry. Resolves with whether it existed.
+ */
+#[tauri::command]
+pub fn delete_history_entry(
+    app: AppHandle,
+    state: State<'_, HistoryState>,
+    id: i64,
+) -> Result<bool, String> {
+    let deleted = state.with_store(|store| store.delete(id))?;
+    if deleted {
+        let _ = app.emit(HISTORY_ENTRY_DELETED_EVENT, id);
+    }
+    Ok(deleted)
+}
+
+/**
+ * Command: search_history
+ * Responsibility: Full-text search over past transcripts, optionally limited to a
+ * date range and a target application. Returns ranked hits with highlighted
+ * snippets, a page at a time; pass `nextOffset` back as `offset` to continue.
+ */
+#[tauri::command]
+pub fn search_history(
+    state: State<'_, HistoryState>,
+    options: SearchOptions,
+) -> Result<SearchPage, String> {
+    let limit = options
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    state.with_store(|store| store.search(&options, limit))
+}
+
+/**
+ * Command: get_private_session
+ * Responsibility: Whether a private session is running.
+ */
+#[tauri::command]
+pub fn get_private_session(state: State<'_, HistoryState>) -> bool {
+    state.private_session.load(Ordering::SeqCst)
+}
+
+/**
+ * Command: set_private_session
+ * Responsibility: Starts or ends a private session. While one runs, nothing from a
+ * dictation is written to disk; transcripts are still delivered as usual.
+ */
+#[tauri::command]
+pub fn set_private_session(app: AppHandle, state: State<'_, HistoryState>, enabled: bool) -> bool {
+    if state.private_session.swap(enabled, Ordering::SeqCst) != enabled {
+        let _ = app.emit(PRIVATE_SESSION_CHANGED_EVENT, enabled);
+    }
+    enabled
+}
+
+/**
+ * Command: wipe_history
+ * Responsibility: Deletes every stored transcript and vacuums the database file, and
+ * destroys all archived recordings. Resolves with the number of entries removed.
+ */
+#[tauri::command]
+pub async fn wipe_history(app: AppHandle) -> Result<u64, String> {
+    archive::wipe(&app)?;
+    let removed = app
+        .state::<HistoryState>()
+        .with_store(|store| store.wipe())?;
+    let _ = app.emit(HISTORY_PURGED_EVENT, removed);
+    Ok(removed)
+}
@@ -0,0 +1,219 @@
+use rusqlite::params;
+
+use super::store::{db_error, HistoryStore};
+use crate::settings::Settings;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/**
+ * Struct: RetentionPolicy
+ * How much dictation may stay on disk, taken from the history settings. It covers
+ * both the history and archived audio. A limit of 0 means no limit; with `store` off
+ * nothing is kept at all.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub store: bool,
+    pub max_age_days: u32,
+    pub max_entries: u32,
+    /// Total size archived recordings may take up.
+    pub archive_max_bytes: u64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            store: true,
+            max_age_days: 0,
+            max_entries: 0,
+            archive_max_bytes: u64::MAX,
+        }
+    }
+}
+
+impl From<&Settings> for RetentionPolicy {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            store: settings.history_enabled,
+            max_age_days: settings.history_max_age_days,
+            max_entries: settings.history_max_entries,
+            archive_max_bytes: settings.archive_max_mb as u64 * 1024 * 1024,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Sessions started before this Unix time (ms) have expired.
+    pub fn cutoff(&self, now_ms: i64) -> Option<i64> {
+        (self.max_age_days > 0).then(|| now_ms - self.max_age_days as i64 * DAY_MS)
+    }
+}
+
+impl HistoryStore {
+    /**
+     * Function: purge
+     * Deletes every entry the policy no longer allows: those older than its age limit
+     * and all but the newest `max_entries`. When storing is off, the history is wiped.
+     * Returns how many entries were removed.
+     */
+    pub fn purge(&self, policy: &RetentionPolicy, now_ms: i64) -> Result<u64, String> {
+        if !policy.store {
+            return match self.count()? {
+                0 => Ok(0),
+                _ => self.wipe(),
+            };
+        }
+
+        let mut removed = 0;
+        if let Some(cutoff) = policy.cutoff(now_ms) {
+            removed += self
+                .conn
+                .execute("DELETE FROM entries WHERE started_at < ?1", [cutoff])
+                .map_err(db_error)?;
+        }
+        if policy.max_entries > 0 {
+            removed += self
+                .conn
+                .execute(
+                    "DELETE FROM entries WHERE id NOT IN (
+                         SELECT id FROM entries ORDER BY id DESC LIMIT ?1
+                     )",
+                    params![policy.max_entries],
+                )
+                .map_err(db_error)?;
+        }
+        Ok(removed as u64)
+    }
+
+    fn clear(&self) -> Result<u64, String> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM entries", [])
+            .map_err(db_error)?;
+        Ok(removed as u64)
+    }
+
+    /**
+     * Function: wipe
+     * Deletes every entry, rebuilds the (now empty) full-text index, and vacuums the
+     * file so no page of the database still holds an old transcript.
+     * Returns how many entries were removed.
+     */
+    pub fn wipe(&self) -> Result<u64, String> {
+        let removed = self.clear()?;
+        self.conn
+            .execute_batch(
+                "INSERT INTO entries_fts (entries_fts) VALUES ('rebuild');
+                 VACUUM;",
+            )
+            .map_err(db_error)?;
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::search::SearchOptions;
+    use crate::history::store::SessionRecord;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn insert(store: &HistoryStore, text: &str, started_at: i64) -> i64 {
+        store
+            .insert(&SessionRecord {
+                started_at,
+                ended_at: started_at + 1_000,
+                duration_ms: 1_000,
+                text: text.into(),
+                provider: "deepgram".into(),
+                model: None,
+                language: None,
+                finalize_reason: "finalReceived".into(),
+                delivery: None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn expired_entries_are_purged() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        insert(&store, "Ten days old.", NOW - 10 * DAY_MS);
+        let recent = insert(&store, "One day old.", NOW - DAY_MS);
+        let policy = RetentionPolicy {
+            max_age_days: 7,
+            ..RetentionPolicy::default()
+        };
+
+        assert_eq!(store.purge(&policy, NOW), Ok(1));
+        assert_eq!(store.count(), Ok(1));
+        assert!(store.get(recent).unwrap().is_some());
+    }
+
+    #[test]
+    fn only_the_newest_entries_are_kept() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let ids: Vec<i64> = (0..5)
+            .map(|i| insert(&store, &format!("Entry {i}"), NOW))
+            .collect();
+        let policy = RetentionPolicy {
+            max_entries: 2,
+            ..RetentionPolicy::default()
+        };
+
+        assert_eq!(store.purge(&policy, NOW), Ok(3));
+        let kept: Vec<i64> = store
+            .page(None, 10)
+            .unwrap()
+            .entries
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(kept, [ids[4], ids[3]]);
+    }
+
+    #[test]
+    fn no_limits_keep_everything() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        insert(&store, "Ancient.", 0);
+        assert_eq!(store.purge(&RetentionPolicy::default(), NOW), Ok(0));
+        assert_eq!(store.count(), Ok(1));
+    }
+
+    #[test]
+    fn storing_off_wipes_the_history_and_its_index() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        insert(&store, "Private note one.", NOW);
+        insert(&store, "Private note two.", NOW);
+        let policy = RetentionPolicy {
+            store: false,
+            ..RetentionPolicy::default()
+        };
+
+        assert_eq!(store.purge(&policy, NOW), Ok(2));
+        assert_eq!(store.count(), Ok(0));
+        let search = SearchOptions {
+            query: Some("private".into()),
+            ..SearchOptions::default()
+        };
+        assert_eq!(store.search(&search, 10).unwrap().total, 0);
+        assert_eq!(store.purge(&policy, NOW), Ok(0));
+    }
+
+    #[test]
+    fn wipe_leaves_a_usable_empty_store() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        insert(&store, "Before the wipe.", NOW);
+
+        assert_eq!(store.wipe(), Ok(1));
+        assert_eq!(store.count(), Ok(0));
+        let id = insert(&store, "After the wipe.", NOW);
+        let search = SearchOptions {
+            query: Some("wipe".into()),
+            ..SearchOptions::default()
+        };
+        let hits = store.search(&search, 10).unwrap().hits;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.id, id);
+    }
+}
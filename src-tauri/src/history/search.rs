@@ -0,0 +1,361 @@
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+use super::store::{db_error, HistoryEntry, HistoryStore};
+
+/// Private-use characters marking matches inside snippets; never typed by speech.
+const MATCH_START: char = '\u{e000}';
+const MATCH_END: char = '\u{e001}';
+
+/// Tokens of context around the best match in each snippet.
+const SNIPPET_TOKENS: u32 = 12;
+
+/// Snippet length, in characters, when there is no text query to center it on.
+const PREVIEW_CHARS: usize = 80;
+
+/**
+ * Struct: SearchOptions
+ * Options accepted by `search_history`. Every filter is optional; without `query`,
+ * entries are listed newest first.
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    /// Words all of which must occur; `"quoted words"` must occur in that order, and a
+    /// trailing `*` matches any word starting with what precedes it.
+    pub query: Option<String>,
+    /// Sessions started at or after this Unix time (ms).
+    pub from: Option<i64>,
+    /// Sessions started before this Unix time (ms).
+    pub to: Option<i64>,
+    /// Application class the transcript was inserted into (case-insensitive).
+    pub target_app: Option<String>,
+    pub offset: u32,
+    pub limit: Option<u32>,
+}
+
+/**
+ * Struct: SnippetPart
+ * A run of snippet text; `matched` runs are the words the query found.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnippetPart {
+    pub text: String,
+    pub matched: bool,
+}
+
+/**
+ * Struct: SearchHit
+ * One result: the entry and the part of its text around the best match.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub entry: HistoryEntry,
+    pub snippet: Vec<SnippetPart>,
+}
+
+/**
+ * Struct: SearchPage
+ * Best-ranked results first. `next_offset` continues the search, and is `None` once
+ * all `total` results have been returned.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPage {
+    pub hits: Vec<SearchHit>,
+    pub total: u64,
+    pub next_offset: Option<u32>,
+}
+
+/**
+ * Function: fts_query
+ * Turns what the user typed into an FTS5 match expression. Every word and phrase is
+ * quoted, so operators and punctuation in the input cannot break the query.
+ * Returns `None` when nothing searchable is left.
+ */
+pub fn fts_query(input: &str) -> Option<String> {
+    let quote = |term: &str| format!("\"{}\"", term.replace('"', ""));
+    let mut terms = Vec::new();
+    for (index, part) in input.split('"').enumerate() {
+        if index % 2 == 1 {
+            if !part.trim().is_empty() {
+                terms.push(quote(part.trim()));
+            }
+            continue;
+        }
+        for word in part.split_whitespace() {
+            let prefix = word.ends_with('*');
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+            if word.is_empty() {
+                continue;
+            }
+            terms.push(if prefix {
+                format!("{}*", quote(word))
+            } else {
+                quote(word)
+            });
+        }
+    }
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Splits a snippet marked with `MATCH_START`/`MATCH_END` into parts.
+fn snippet_parts(marked: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut matched = false;
+    for piece in marked.split([MATCH_START, MATCH_END]) {
+        if !piece.is_empty() {
+            parts.push(SnippetPart {
+                text: piece.to_string(),
+                matched,
+            });
+        }
+        matched = !matched;
+    }
+    parts
+}
+
+fn preview(text: &str) -> Vec<SnippetPart> {
+    let mut preview: String = text.chars().take(PREVIEW_CHARS).collect();
+    if preview.len() < text.len() {
+        preview.push('…');
+    }
+    vec![SnippetPart {
+        text: preview,
+        matched: false,
+    }]
+}
+
+impl HistoryStore {
+    /**
+     * Function: search
+     * Runs the text query against the full-text index, ranked by BM25 (then newest
+     * first), with the date and application filters applied in the same statement.
+     */
+    pub fn search(&self, options: &SearchOptions, limit: u32) -> Result<SearchPage, String> {
+        let text_query = options.query.as_deref().and_then(fts_query);
+        let mut filters = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+        if let Some(text_query) = &text_query {
+            filters.push("entries_fts MATCH ?");
+            params.push(text_query.clone().into());
+        }
+        if let Some(from) = options.from {
+            filters.push("entries.started_at >= ?");
+            params.push(from.into());
+        }
+        if let Some(to) = options.to {
+            filters.push("entries.started_at < ?");
+            params.push(to.into());
+        }
+        if let Some(app) = options.target_app.as_deref().filter(|app| !app.is_empty()) {
+            filters.push("entries.target_app = ? COLLATE NOCASE");
+            params.push(app.to_string().into());
+        }
+
+        let source = if text_query.is_some() {
+            "entries JOIN entries_fts ON entries_fts.rowid = entries.id"
+        } else {
+            "entries"
+        };
+        let condition = if filters.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", filters.join(" AND "))
+        };
+
+        let total: u64 = self
+            .conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {source} {condition}"),
+                rusqlite::params_from_iter(&params),
+                |row| row.get(0),
+            )
+            .map_err(db_error)?;
+
+        let (snippet, order) = if text_query.is_some() {
+            (
+                format!(
+                    "snippet(entries_fts, 0, '{MATCH_START}', '{MATCH_END}', '…', {SNIPPET_TOKENS})"
+                ),
+                "bm25(entries_fts), entries.id DESC",
+            )
+        } else {
+            ("NULL".to_string(), "entries.id DESC")
+        };
+        let mut statement = self
+            .conn
+            .prepare(&format!(
+                "SELECT entries.*, {snippet} AS snippet FROM {source} {condition}
+                 ORDER BY {order} LIMIT ? OFFSET ?"
+            ))
+            .map_err(db_error)?;
+        params.push(limit.into());
+        params.push(options.offset.into());
+        let hits = statement
+            .query_map(rusqlite::params_from_iter(&params), |row| {
+                let entry = HistoryEntry::from_row(row)?;
+                let snippet: Option<String> = row.get("snippet")?;
+                let snippet = match snippet {
+                    Some(marked) => snippet_parts(&marked),
+                    None => preview(&entry.record.text),
+                };
+                Ok(SearchHit { entry, snippet })
+            })
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+
+        let returned = options.offset as u64 + hits.len() as u64;
+        Ok(SearchPage {
+            next_offset: (returned < total).then_some(returned as u32),
+            total,
+            hits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::store::{DeliveryRecord, SessionRecord};
+
+    fn insert(store: &HistoryStore, text: &str, started_at: i64, app: Option<&str>) -> i64 {
+        store
+            .insert(&SessionRecord {
+                started_at,
+                ended_at: started_at + 1_000,
+                duration_ms: 1_000,
+                text: text.into(),
+                provider: "deepgram".into(),
+                model: None,
+                language: None,
+                finalize_reason: "finalReceived".into(),
+                delivery: app.map(|app| DeliveryRecord {
+                    pasted: true,
+                    target_app: Some(app.into()),
+                    ..DeliveryRecord::default()
+                }),
+            })
+            .unwrap()
+    }
+
+    fn ids(store: &HistoryStore, options: SearchOptions) -> Vec<i64> {
+        let page = store.search(&options, 10).unwrap();
+        page.hits.iter().map(|hit| hit.entry.id).collect()
+    }
+
+    fn query(text: &str) -> SearchOptions {
+        SearchOptions {
+            query: Some(text.into()),
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn fts_query_quotes_words_and_keeps_phrases_and_prefixes() {
+        assert_eq!(
+            fts_query("hello world").as_deref(),
+            Some("\"hello\" \"world\"")
+        );
+        assert_eq!(
+            fts_query("\"quick brown\" fox*").as_deref(),
+            Some("\"quick brown\" \"fox\"*")
+        );
+        assert_eq!(
+            fts_query("NOT (a OR").as_deref(),
+            Some("\"NOT\" \"a\" \"OR\"")
+        );
+        assert_eq!(fts_query(" - * \"\" "), None);
+    }
+
+    #[test]
+    fn text_search_matches_words_phrases_and_prefixes() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let meeting = insert(&store, "Schedule the meeting for Tuesday.", 1_000, None);
+        let notes = insert(&store, "Meeting notes: the budget is final.", 2_000, None);
+        insert(&store, "Buy milk on the way home.", 3_000, None);
+
+        assert_eq!(ids(&store, query("tuesday")), [meeting]);
+        let mut both = ids(&store, query("meeting"));
+        both.sort();
+        assert_eq!(both, [meeting, notes]);
+        assert_eq!(ids(&store, query("\"meeting notes\"")), [notes]);
+        assert_eq!(ids(&store, query("budg*")), [notes]);
+        assert!(ids(&store, query("meeting milk")).is_empty());
+    }
+
+    #[test]
+    fn snippets_mark_the_matched_words() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        insert(&store, "Call Alice about the invoice.", 1_000, None);
+
+        let page = store.search(&query("alice"), 10).unwrap();
+        let matched: Vec<&str> = page.hits[0]
+            .snippet
+            .iter()
+            .filter(|part| part.matched)
+            .map(|part| part.text.as_str())
+            .collect();
+        assert_eq!(matched, ["Alice"]);
+    }
+
+    #[test]
+    fn filters_apply_with_and_without_a_query() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let early = insert(
+            &store,
+            "Report draft in the terminal.",
+            1_000,
+            Some("XTerm"),
+        );
+        let late = insert(
+            &store,
+            "Report draft in the browser.",
+            5_000,
+            Some("firefox"),
+        );
+
+        let in_xterm = SearchOptions {
+            target_app: Some("xterm".into()),
+            ..query("report")
+        };
+        assert_eq!(ids(&store, in_xterm), [early]);
+        let since = SearchOptions {
+            from: Some(2_000),
+            ..SearchOptions::default()
+        };
+        assert_eq!(ids(&store, since), [late]);
+    }
+
+    #[test]
+    fn deleted_entries_leave_the_index() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let id = insert(&store, "Secret plans for the weekend.", 1_000, None);
+        store.delete(id).unwrap();
+
+        let page = store.search(&query("secret"), 10).unwrap();
+        assert!(page.hits.is_empty());
+        assert_eq!(page.total, 0);
+    }
+
+    #[test]
+    fn results_are_paged_by_offset() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        for i in 0..3 {
+            insert(&store, &format!("Standup update number {i}."), i, None);
+        }
+
+        let first = store.search(&query("standup"), 2).unwrap();
+        assert_eq!((first.hits.len(), first.total), (2, 3));
+        assert_eq!(first.next_offset, Some(2));
+        let rest = SearchOptions {
+            offset: 2,
+            ..query("standup")
+        };
+        let second = store.search(&rest, 2).unwrap();
+        assert_eq!(second.hits.len(), 1);
+        assert_eq!(second.next_offset, None);
+    }
+}
@@ -0,0 +1,372 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+/// Schema steps, applied in order; `PRAGMA user_version` counts how many have run.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE entries (
+        id              INTEGER PRIMARY KEY,
+        started_at      INTEGER NOT NULL,
+        ended_at        INTEGER NOT NULL,
+        duration_ms     INTEGER NOT NULL,
+        text            TEXT    NOT NULL,
+        provider        TEXT    NOT NULL,
+        model           TEXT,
+        language        TEXT,
+        finalize_reason TEXT    NOT NULL,
+        delivered       INTEGER NOT NULL DEFAULT 0,
+        copied          INTEGER NOT NULL DEFAULT 0,
+        pasted          INTEGER NOT NULL DEFAULT 0,
+        typed           INTEGER NOT NULL DEFAULT 0,
+        insert_error    TEXT,
+        target_app      TEXT
+    );
+    CREATE INDEX entries_started_at ON entries (started_at);
+",
+    "
+    CREATE INDEX entries_target_app ON entries (target_app COLLATE NOCASE);
+    CREATE VIRTUAL TABLE entries_fts USING fts5 (
+        text,
+        content = 'entries',
+        content_rowid = 'id',
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+    INSERT INTO entries_fts (rowid, text) SELECT id, text FROM entries;
+    CREATE TRIGGER entries_fts_insert AFTER INSERT ON entries BEGIN
+        INSERT INTO entries_fts (rowid, text) VALUES (new.id, new.text);
+    END;
+    CREATE TRIGGER entries_fts_delete AFTER DELETE ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, text) VALUES ('delete', old.id, old.text);
+    END;
+    CREATE TRIGGER entries_fts_update AFTER UPDATE OF text ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, text) VALUES ('delete', old.id, old.text);
+        INSERT INTO entries_fts (rowid, text) VALUES (new.id, new.text);
+    END;
+",
+    "
+    -- Drop a deleted transcript's words from the index at once, not at the next merge.
+    INSERT INTO entries_fts (entries_fts, rank) VALUES ('secure-delete', 1);
+",
+];
+
+const COLUMNS: &str = "id, started_at, ended_at, duration_ms, text, provider, model, language, \
+     finalize_reason, delivered, copied, pasted, typed, insert_error, target_app";
+
+/**
+ * Struct: DeliveryRecord
+ * Where a transcript went. Absent when nothing was delivered: the session was
+ * stopped with `stop_transcription`, or the recording failed before delivery.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryRecord {
+    pub copied: bool,
+    pub pasted: bool,
+    pub typed: bool,
+    pub insert_error: Option<String>,
+    /// Application class of the window it was inserted into, when known.
+    pub target_app: Option<String>,
+}
+
+/**
+ * Struct: SessionRecord
+ * One finished dictation as stored. Times are Unix milliseconds.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecord {
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub duration_ms: i64,
+    pub text: String,
+    pub provider: String,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    /// `FinalizeReason` as serialized (`finalReceived`, `timeout`, `connectionClosed`).
+    pub finalize_reason: String,
+    pub delivery: Option<DeliveryRecord>,
+}
+
+/**
+ * Struct: HistoryEntry
+ * A stored `SessionRecord` and its id.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: i64,
+    #[serde(flatten)]
+    pub record: SessionRecord,
+}
+
+impl HistoryEntry {
+    pub(super) fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let delivered: bool = row.get("delivered")?;
+        let delivery = if delivered {
+            Some(DeliveryRecord {
+                copied: row.get("copied")?,
+                pasted: row.get("pasted")?,
+                typed: row.get("typed")?,
+                insert_error: row.get("insert_error")?,
+                target_app: row.get("target_app")?,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            id: row.get("id")?,
+            record: SessionRecord {
+                started_at: row.get("started_at")?,
+                ended_at: row.get("ended_at")?,
+                duration_ms: row.get("duration_ms")?,
+                text: row.get("text")?,
+                provider: row.get("provider")?,
+                model: row.get("model")?,
+                language: row.get("language")?,
+                finalize_reason: row.get("finalize_reason")?,
+                delivery,
+            },
+        })
+    }
+}
+
+/**
+ * Struct: HistoryPage
+ * Newest-first slice of the history. Pass `next_cursor` back to get the following page;
+ * it is `None` on the last one.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    pub total: u64,
+    pub next_cursor: Option<i64>,
+}
+
+/**
+ * Struct: HistoryStore
+ * Responsibility: The SQLite database of past dictations. Works on any connection,
+ * so it can run against an in-memory database as well as the file in the app data dir.
+ */
+pub struct HistoryStore {
+    pub(super) conn: Connection,
+}
+
+pub(super) fn db_error(e: rusqlite::Error) -> String {
+    format!("History database error: {e}")
+}
+
+impl HistoryStore {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Self::with_connection(Connection::open(path).map_err(db_error)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, String> {
+        Self::with_connection(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, String> {
+        // Overwrite deleted rows with zeros instead of leaving them in free pages.
+        conn.pragma_update(None, "secure_delete", true)
+            .map_err(db_error)?;
+        let mut store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    fn migrate(&mut self) -> Result<(), String> {
+        let version: usize = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(db_error)?;
+        if version > MIGRATIONS.len() {
+            return Err("History database was created by a newer version".into());
+        }
+
+        let tx = self.conn.transaction().map_err(db_error)?;
+        for step in &MIGRATIONS[version..] {
+            tx.execute_batch(step).map_err(db_error)?;
+        }
+        tx.pragma_update(None, "user_version", MIGRATIONS.len())
+            .map_err(db_error)?;
+        tx.commit().map_err(db_error)
+    }
+
+    pub fn insert(&self, record: &SessionRecord) -> Result<i64, String> {
+        let delivery = record.delivery.clone().unwrap_or_default();
+        self.conn
+            .execute(
+                "INSERT INTO entries (started_at, ended_at, duration_ms, text, provider, model,
+                     language, finalize_reason, delivered, copied, pasted, typed, insert_error,
+                     target_app)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                params![
+                    record.started_at,
+                    record.ended_at,
+                    record.duration_ms,
+                    record.text,
+                    record.provider,
+                    record.model,
+                    record.language,
+                    record.finalize_reason,
+                    record.delivery.is_some(),
+                    delivery.copied,
+                    delivery.pasted,
+                    delivery.typed,
+                    delivery.insert_error,
+                    delivery.target_app,
+                ],
+            )
+            .map_err(db_error)?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<HistoryEntry>, String> {
+        self.conn
+            .query_row(
+                &format!("SELECT {COLUMNS} FROM entries WHERE id = ?1"),
+                [id],
+                HistoryEntry::from_row,
+            )
+            .optional()
+            .map_err(db_error)
+    }
+
+    /**
+     * Function: page
+     * Up to `limit` entries older than `cursor` (an entry id), newest first.
+     * Ids grow with every insert, so paging by id stays stable while new entries arrive.
+     */
+    pub fn page(&self, cursor: Option<i64>, limit: u32) -> Result<HistoryPage, String> {
+        let mut statement = self
+            .conn
+            .prepare(&format!(
+                "SELECT {COLUMNS} FROM entries WHERE id < ?1 ORDER BY id DESC LIMIT ?2"
+            ))
+            .map_err(db_error)?;
+        let entries = statement
+            .query_map(
+                params![cursor.unwrap_or(i64::MAX), limit],
+                HistoryEntry::from_row,
+            )
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+
+        let next_cursor = entries
+            .last()
+            .filter(|_| entries.len() == limit as usize)
+            .map(|last| last.id)
+            .filter(|&id| self.has_older(id).unwrap_or(false));
+        Ok(HistoryPage {
+            total: self.count()?,
+            entries,
+            next_cursor,
+        })
+    }
+
+    fn has_older(&self, id: i64) -> Result<bool, String> {
+        self.conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM entries WHERE id < ?1)",
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(db_error)
+    }
+
+    pub fn count(&self) -> Result<u64, String> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))
+            .map_err(db_error)
+    }
+
+    /// Returns whether an entry was deleted.
+    pub fn delete(&self, id: i64) -> Result<bool, String> {
+        self.conn
+            .execute("DELETE FROM entries WHERE id = ?1", [id])
+            .map(|deleted| deleted > 0)
+            .map_err(db_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(text: &str, delivery: Option<DeliveryRecord>) -> SessionRecord {
+        SessionRecord {
+            started_at: 1_700_000_000_000,
+            ended_at: 1_700_000_004_500,
+            duration_ms: 4_500,
+            text: text.into(),
+            provider: "deepgram".into(),
+            model: Some("nova-2".into()),
+            language: None,
+            finalize_reason: "finalReceived".into(),
+            delivery,
+        }
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let delivered = record(
+            "Hello world.",
+            Some(DeliveryRecord {
+                copied: true,
+                pasted: false,
+                typed: true,
+                insert_error: Some("Escape pressed".into()),
+                target_app: Some("xterm".into()),
+            }),
+        );
+        let undelivered = record("Not delivered.", None);
+
+        let first = store.insert(&delivered).unwrap();
+        let second = store.insert(&undelivered).unwrap();
+
+        assert_eq!(store.get(first).unwrap().unwrap().record, delivered);
+        assert_eq!(store.get(second).unwrap().unwrap().record, undelivered);
+        assert_eq!(store.get(second + 1).unwrap(), None);
+        assert_eq!(store.count().unwrap(), 2);
+    }
+
+    #[test]
+    fn pages_run_newest_first_until_exhausted() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let ids: Vec<i64> = (0..5)
+            .map(|i| store.insert(&record(&format!("Entry {i}"), None)).unwrap())
+            .collect();
+
+        let first = store.page(None, 2).unwrap();
+        assert_eq!(first.total, 5);
+        assert_eq!(
+            first.entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+            [ids[4], ids[3]]
+        );
+        let second = store.page(first.next_cursor, 2).unwrap();
+        assert_eq!(
+            second.entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+            [ids[2], ids[1]]
+        );
+        let last = store.page(second.next_cursor, 2).unwrap();
+        assert_eq!(last.entries.len(), 1);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn delete_removes_one_entry() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let id = store.insert(&record("Delete me.", None)).unwrap();
+        store.insert(&record("Keep me.", None)).unwrap();
+
+        assert!(store.delete(id).unwrap());
+        assert!(!store.delete(id).unwrap());
+        assert_eq!(store.get(id).unwrap(), None);
+        assert_eq!(store.count().unwrap(), 1);
+    }
+}
@@ -1,4 +1,14 @@
-use tauri::Manager;
+pub mod archive;
+pub mod audio;
+pub mod delivery;
+pub mod history;
+pub mod placement;
+pub mod recording;
+pub mod retranscribe;
+pub mod secrets;
+pub mod settings;
+pub mod shortcuts;
+pub mod stt;
 
 /**
  * Command: start_drag
@@ -14,21 +24,68 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_clipboard_manager::init())
-        .invoke_handler(tauri::generate_handler![start_drag])
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(shortcuts::handle)
+                .build(),
+        )
+        .manage(audio::AudioState::default())
+        .manage(stt::TranscriptionState::default())
+        .manage(recording::Recorder::default())
+        .manage(delivery::DeliveryState::default())
+        .manage(shortcuts::ShortcutRegistry::default())
+        .manage(settings::SettingsState::default())
+        .manage(secrets::SecretsState::default())
+        .manage(placement::WindowPlacement::default())
+        .manage(history::HistoryState::default())
+        .manage(archive::ArchiveState::default())
+        .invoke_handler(tauri::generate_handler![
+            start_drag,
+            audio::start_capture,
+            audio::stop_capture,
+            audio::list_input_devices,
+            audio::get_preferred_device,
+            audio::set_preferred_device,
+            stt::start_transcription,
+            stt::stop_transcription,
+            stt::list_stt_providers,
+            stt::list_local_models,
+            recording::start_recording,
+            recording::stop_recording,
+            recording::cancel_recording,
+            recording::get_recording_state,
+            shortcuts::set_global_shortcut,
+            settings::get_settings,
+            settings::update_settings,
+            settings::reset_settings,
+            settings::import_legacy_settings,
+            secrets::get_api_key_status,
+            secrets::set_api_key,
+            secrets::clear_api_key,
+            secrets::test_api_key,
+            history::list_history,
+            history::get_history_entry,
+            history::copy_history_entry,
+            history::delete_history_entry,
+            history::search_history,
+            history::get_private_session,
+            history::set_private_session,
+            history::wipe_history,
+            archive::list_recordings,
+            archive::get_recording,
+            archive::read_recording_audio,
+            archive::export_recording,
+            archive::delete_recording,
+            retranscribe::retranscribe_file
+        ])
         .setup(|app| {
-            let window = app.get_webview_window("main").unwrap();
-
-            if let Ok(Some(monitor)) = window.current_monitor() {
-                let screen_size = monitor.size();
-                let window_size = window
-                    .outer_size()
-                    .unwrap_or(tauri::PhysicalSize::new(400, 280));
-
-                let x = (screen_size.width as i32 - window_size.width as i32) / 2;
-                let y = screen_size.height as i32 - window_size.height as i32 - 60;
-
-                let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
-            }
+            placement::setup(app.handle());
+            settings::setup(app.handle());
+            secrets::setup(app.handle());
+            archive::setup(app.handle());
+            history::setup(app.handle());
+            audio::setup(app.handle());
+            recording::setup(app.handle());
 
             Ok(())
         })
@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WindowEvent};
+
+const PLACEMENT_FILE: &str = "window-state.json";
+
+/// Moves arrive continuously while dragging; save once they have stopped this long.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Gap between the widget and the bottom edge of the screen in the default position.
+const BOTTOM_MARGIN: i32 = 60;
+
+/// Used until the window reports its real size.
+const FALLBACK_SIZE: PhysicalSize<u32> = PhysicalSize::new(400, 280);
+
+/**
+ * Struct: Offset
+ * Window position relative to the top-left corner of its monitor, in physical pixels.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Offset {
+    pub x: i32,
+    pub y: i32,
+}
+
+/**
+ * Struct: PlacementFile
+ * Contents of `window-state.json`: one saved position per monitor, and the monitor
+ * the widget was last left on.
+ */
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct PlacementFile {
+    last_monitor: Option<String>,
+    positions: BTreeMap<String, Offset>,
+}
+
+/**
+ * Struct: MonitorArea
+ * The part of the desktop one monitor covers, with the key its position is saved under.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorArea {
+    pub key: String,
+    pub origin: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+}
+
+impl MonitorArea {
+    pub fn new(monitor: &Monitor) -> Self {
+        Self {
+            key: monitor_key(monitor.name().map(String::as_str), *monitor.size()),
+            origin: *monitor.position(),
+            size: *monitor.size(),
+        }
+    }
+
+    pub fn contains(&self, point: PhysicalPosition<i32>) -> bool {
+        let (dx, dy) = (point.x - self.origin.x, point.y - self.origin.y);
+        (0..self.size.width as i32).contains(&dx) && (0..self.size.height as i32).contains(&dy)
+    }
+
+    /// Whether a window of `size` at `offset` lies entirely on this monitor.
+    pub fn fits(&self, offset: Offset, size: PhysicalSize<u32>) -> bool {
+        offset.x >= 0
+            && offset.y >= 0
+            && offset.x + size.width as i32 <= self.size.width as i32
+            && offset.y + size.height as i32 <= self.size.height as i32
+    }
+
+    pub fn at(&self, offset: Offset) -> PhysicalPosition<i32> {
+        PhysicalPosition::new(self.origin.x + offset.x, self.origin.y + offset.y)
+    }
+
+    pub fn offset_of(&self, position: PhysicalPosition<i32>) -> Offset {
+        Offset {
+            x: position.x - self.origin.x,
+            y: position.y - self.origin.y,
+        }
+    }
+
+    /// The default spot: centered horizontally, `BOTTOM_MARGIN` above the bottom edge.
+    pub fn bottom_center(&self, size: PhysicalSize<u32>) -> PhysicalPosition<i32> {
+        self.at(Offset {
+            x: (self.size.width as i32 - size.width as i32) / 2,
+            y: self.size.height as i32 - size.height as i32 - BOTTOM_MARGIN,
+        })
+    }
+}
+
+/// Monitors are told apart by name and resolution, so a different screen on the same
+/// port, or the same screen at another resolution, starts from the default position.
+pub fn monitor_key(name: Option<&str>, size: PhysicalSize<u32>) -> String {
+    format!(
+        "{}@{}x{}",
+        name.unwrap_or("unnamed"),
+        size.width,
+        size.height
+    )
+}
+
+/**
+ * Function: restored_position
+ * The saved position on the monitor the widget was last left on, if that monitor is
+ * connected and the window still fits on it entirely. Otherwise the position saved for
+ * `current` (the monitor the window opened on), under the same condition.
+ */
+fn restored_position(
+    file: &PlacementFile,
+    monitors: &[MonitorArea],
+    current: Option<&MonitorArea>,
+    size: PhysicalSize<u32>,
+) -> Option<PhysicalPosition<i32>> {
+    let saved_on = |monitor: &MonitorArea| {
+        let offset = *file.positions.get(&monitor.key)?;
+        monitor.fits(offset, size).then(|| monitor.at(offset))
+    };
+    file.last_monitor
+        .as_ref()
+        .and_then(|key| monitors.iter().find(|m| &m.key == key))
+        .and_then(saved_on)
+        .or_else(|| current.and_then(saved_on))
+}
+
+/**
+ * Struct: WindowPlacement
+ * Responsibility: In-memory copy of `window-state.json`, and a counter of move events
+ * so only the last move of a drag is saved.
+ */
+#[derive(Default)]
+pub struct WindowPlacement {
+    file: Mutex<PlacementFile>,
+    moves: AtomicU64,
+}
+
+fn placement_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(PLACEMENT_FILE))
+}
+
+fn load(app: &AppHandle) -> PlacementFile {
+    placement_path(app)
+        .and_then(|path| fs::read_to_string(path).map_err(|e| e.to_string()))
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, file: &PlacementFile) -> Result<(), String> {
+    let path = placement_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/**
+ * Function: remember
+ * Records where the widget now sits, under the monitor holding its center.
+ */
+fn remember(app: &AppHandle, position: PhysicalPosition<i32>) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window is gone")?;
+    let size = window.outer_size().unwrap_or(FALLBACK_SIZE);
+    let center = PhysicalPosition::new(
+        position.x + size.width as i32 / 2,
+        position.y + size.height as i32 / 2,
+    );
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    let Some(monitor) = monitors
+        .iter()
+        .map(MonitorArea::new)
+        .find(|m| m.contains(center))
+    else {
+        return Ok(());
+    };
+
+    let placement = app.state::<WindowPlacement>();
+    let mut file = placement.file.lock().unwrap();
+    let offset = monitor.offset_of(position);
+    if file.last_monitor.as_ref() == Some(&monitor.key)
+        && file.positions.get(&monitor.key) == Some(&offset)
+    {
+        return Ok(());
+    }
+    file.positions.insert(monitor.key.clone(), offset);
+    file.last_monitor = Some(monitor.key);
+    save(app, &file)
+}
+
+/**
+ * Function: setup
+ * Responsibility: Puts the widget where it was last left, falling back to bottom-center
+ * of the current monitor, then saves its position whenever a drag (or the window
+ * manager) moves it.
+ * Called once from the Tauri `setup` hook.
+ */
+pub fn setup(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let file = load(app);
+    let size = window.outer_size().unwrap_or(FALLBACK_SIZE);
+    let monitors: Vec<MonitorArea> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(MonitorArea::new)
+        .collect();
+
+    let current = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| MonitorArea::new(&monitor));
+
+    let position = restored_position(&file, &monitors, current.as_ref(), size)
+        .or_else(|| Some(current?.bottom_center(size)));
+    if let Some(position) = position {
+        let _ = window.set_position(position);
+    }
+    *app.state::<WindowPlacement>().file.lock().unwrap() = file;
+
+    let handle = app.clone();
+    window.on_window_event(move |event| {
+        let WindowEvent::Moved(position) = event else {
+            return;
+        };
+        let position = *position;
+        let generation = handle
+            .state::<WindowPlacement>()
+            .moves
+            .fetch_add(1, Ordering::SeqCst)
+            + 1;
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(SAVE_DEBOUNCE).await;
+            if app.state::<WindowPlacement>().moves.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            if let Err(e) = remember(&app, position) {
+                eprintln!("Cannot save window position: {e}");
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDGET: PhysicalSize<u32> = PhysicalSize::new(400, 280);
+
+    fn monitor(name: &str, x: i32, width: u32, height: u32) -> MonitorArea {
+        let size = PhysicalSize::new(width, height);
+        MonitorArea {
+            key: monitor_key(Some(name), size),
+            origin: PhysicalPosition::new(x, 0),
+            size,
+        }
+    }
+
+    fn file(last: &MonitorArea, positions: &[(&MonitorArea, Offset)]) -> PlacementFile {
+        PlacementFile {
+            last_monitor: Some(last.key.clone()),
+            positions: positions
+                .iter()
+                .map(|(m, offset)| (m.key.clone(), *offset))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn fits_only_when_the_whole_window_is_on_the_monitor() {
+        let laptop = monitor("eDP-1", 0, 1920, 1080);
+        assert!(laptop.fits(Offset { x: 0, y: 0 }, WIDGET));
+        assert!(laptop.fits(Offset { x: 1520, y: 800 }, WIDGET));
+        assert!(!laptop.fits(Offset { x: 1521, y: 800 }, WIDGET));
+        assert!(!laptop.fits(Offset { x: 100, y: 801 }, WIDGET));
+        assert!(!laptop.fits(Offset { x: -1, y: 100 }, WIDGET));
+    }
+
+    #[test]
+    fn at_is_relative_to_the_monitor_origin() {
+        let external = monitor("HDMI-1", 1920, 2560, 1440);
+        let position = external.at(Offset { x: 100, y: 50 });
+        assert_eq!(position, PhysicalPosition::new(2020, 50));
+        assert_eq!(external.offset_of(position), Offset { x: 100, y: 50 });
+    }
+
+    #[test]
+    fn restores_the_position_on_the_last_monitor() {
+        let laptop = monitor("eDP-1", 0, 1920, 1080);
+        let external = monitor("HDMI-1", 1920, 2560, 1440);
+        let saved = file(
+            &external,
+            &[
+                (&laptop, Offset { x: 10, y: 10 }),
+                (&external, Offset { x: 100, y: 50 }),
+            ],
+        );
+
+        let monitors = [laptop.clone(), external];
+        assert_eq!(
+            restored_position(&saved, &monitors, Some(&laptop), WIDGET),
+            Some(PhysicalPosition::new(2020, 50))
+        );
+    }
+
+    #[test]
+    fn disconnected_last_monitor_falls_back_to_the_current_monitors_entry() {
+        let laptop = monitor("eDP-1", 0, 1920, 1080);
+        let external = monitor("HDMI-1", 1920, 2560, 1440);
+        let saved = file(
+            &external,
+            &[
+                (&laptop, Offset { x: 10, y: 10 }),
+                (&external, Offset { x: 100, y: 50 }),
+            ],
+        );
+
+        let monitors = [laptop.clone()];
+        assert_eq!(
+            restored_position(&saved, &monitors, Some(&laptop), WIDGET),
+            Some(PhysicalPosition::new(10, 10))
+        );
+        let unsaved = monitor("DP-2", 0, 1920, 1080);
+        assert_eq!(
+            restored_position(
+                &saved,
+                std::slice::from_ref(&unsaved),
+                Some(&unsaved),
+                WIDGET
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn position_that_no_longer_fits_is_not_restored() {
+        let external = monitor("HDMI-1", 0, 2560, 1440);
+        let saved = file(&external, &[(&external, Offset { x: 2000, y: 1000 })]);
+
+        // Same screen at a lower resolution: a different key, so nothing is saved for it.
+        let lowered = monitor("HDMI-1", 0, 1920, 1080);
+        assert_eq!(
+            restored_position(
+                &saved,
+                std::slice::from_ref(&lowered),
+                Some(&lowered),
+                WIDGET
+            ),
+            None
+        );
+        // Same key but a taller widget that would now run off the bottom edge.
+        let tall = PhysicalSize::new(400, 600);
+        assert_eq!(
+            restored_position(
+                &saved,
+                std::slice::from_ref(&external),
+                Some(&external),
+                tall
+            ),
+            None
+        );
+    }
+}
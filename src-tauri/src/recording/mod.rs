@@ -0,0 +1,475 @@
+use std::fmt;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+
+use crate::audio::vad::SILENCE_TIMEOUT_EVENT;
+use crate::audio::{AudioState, CaptureConfig, CAPTURE_ERROR_EVENT};
+use crate::delivery::{self, Delivery, DeliveryOptions, DeliveryState};
+use crate::history;
+use crate::settings::SettingsState;
+use crate::shortcuts;
+use crate::stt::{
+    FinalizeReason, SessionInfo, SessionSummary, TranscriptionOptions, TranscriptionState,
+    TRANSCRIPTION_CLOSED_EVENT,
+};
+
+pub const RECORDING_STATE_EVENT: &str = "recording-state";
+
+/**
+ * Enum: RecordingState
+ * Lifecycle of one dictation: Idle → Arming → Recording → Finalizing → Delivering → Idle.
+ * Any active state can fall into `Error`, which is left by starting again or resetting.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum RecordingState {
+    #[default]
+    Idle,
+    /// Opening the microphone and the provider session.
+    Arming,
+    Recording,
+    /// Capture stopped; waiting for the provider's last results.
+    Finalizing,
+    /// Results are complete and being handed to the user.
+    Delivering,
+    Error {
+        message: String,
+    },
+}
+
+/**
+ * Enum: Transition
+ * Inputs that move the machine. Commands and backend events map onto these.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transition {
+    Start,
+    Armed,
+    Stop,
+    Finalized,
+    Delivered,
+    Fail(String),
+    Reset,
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Transition::Start => "start",
+            Transition::Armed => "finish arming",
+            Transition::Stop => "stop",
+            Transition::Finalized => "finish finalizing",
+            Transition::Delivered => "finish delivering",
+            Transition::Fail(_) => "fail",
+            Transition::Reset => "reset",
+        };
+        f.write_str(name)
+    }
+}
+
+impl RecordingState {
+    fn name(&self) -> &'static str {
+        match self {
+            RecordingState::Idle => "idle",
+            RecordingState::Arming => "arming",
+            RecordingState::Recording => "recording",
+            RecordingState::Finalizing => "finalizing",
+            RecordingState::Delivering => "delivering",
+            RecordingState::Error { .. } => "in error",
+        }
+    }
+
+    /**
+     * Function: next
+     * The transition table. Pure, so the lifecycle can be reasoned about (and tested)
+     * without a webview, devices or a network.
+     */
+    pub fn next(&self, transition: &Transition) -> Result<RecordingState, String> {
+        use RecordingState as S;
+
+        let next = match (self, transition) {
+            (S::Idle | S::Error { .. }, Transition::Start) => S::Arming,
+            (S::Arming, Transition::Armed) => S::Recording,
+            (S::Recording, Transition::Stop) => S::Finalizing,
+            (S::Finalizing, Transition::Finalized) => S::Delivering,
+            (S::Delivering, Transition::Delivered) => S::Idle,
+            (S::Idle, Transition::Fail(_)) => {
+                return Err("Cannot fail while idle".into());
+            }
+            (_, Transition::Fail(message)) => S::Error {
+                message: message.clone(),
+            },
+            (_, Transition::Reset) => S::Idle,
+            (state, transition) => {
+                return Err(format!("Cannot {transition} while {}", state.name()));
+            }
+        };
+        Ok(next)
+    }
+
+    pub fn is_active(&self) -> bool {
+        !matches!(self, RecordingState::Idle | RecordingState::Error { .. })
+    }
+}
+
+/**
+ * Struct: RecordingOptions
+ * Options accepted by `start_recording`, forwarded to capture and transcription.
+ * Whatever is left out comes from Settings, so the shortcut and the mic button record
+ * the same way.
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingOptions {
+    /// Defaults to capture with the Settings' silence timeout.
+    pub capture: Option<CaptureConfig>,
+    #[serde(default)]
+    pub transcription: TranscriptionOptions,
+    /// Defaults to the delivery settings (Auto Copy, Auto Paste, ...).
+    pub delivery: Option<DeliveryOptions>,
+}
+
+/**
+ * Struct: Recorder
+ * Responsibility: Holds the current `RecordingState` and emits `recording-state`
+ * whenever it changes. The only place the state is written.
+ */
+#[derive(Default)]
+pub struct Recorder {
+    state: Mutex<RecordingState>,
+    /// Delivery settings of the current recording, captured at start.
+    delivery: Mutex<DeliveryOptions>,
+    /// Held for the whole of `start`, `stop` and `fail`, so a failure reported while
+    /// stopping cannot take the session (and its final transcript) from under `stop`.
+    lifecycle: tokio::sync::Mutex<()>,
+}
+
+impl Recorder {
+    pub fn state(&self) -> RecordingState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /**
+     * Function: apply
+     * Runs one transition and announces the new state. Rejected transitions leave
+     * the state untouched.
+     */
+    pub fn apply(&self, app: &AppHandle, transition: Transition) -> Result<RecordingState, String> {
+        let next = {
+            let mut state = self.state.lock().unwrap();
+            let next = state.next(&transition)?;
+            *state = next.clone();
+            next
+        };
+        let _ = app.emit(RECORDING_STATE_EVENT, &next);
+        if !next.is_active() {
+            shortcuts::recording_ended(app);
+        }
+        Ok(next)
+    }
+}
+
+/**
+ * Function: start
+ * Idle → Arming → Recording: opens the microphone, then the provider session.
+ * Any failure releases what was opened and leaves the machine in `Error`.
+ */
+pub async fn start(app: &AppHandle, options: RecordingOptions) -> Result<RecordingState, String> {
+    let recorder = app.state::<Recorder>();
+    let audio = app.state::<AudioState>();
+    let transcription = app.state::<TranscriptionState>();
+    let _lifecycle = recorder.lifecycle.lock().await;
+    let settings = app.state::<SettingsState>().get();
+    let capture = options
+        .capture
+        .unwrap_or_else(|| CaptureConfig::from(&settings));
+    let delivery = options
+        .delivery
+        .unwrap_or_else(|| DeliveryOptions::from(&settings));
+
+    recorder.apply(app, Transition::Start)?;
+    app.state::<DeliveryState>().remember_target(app, &delivery);
+    *recorder.delivery.lock().unwrap() = delivery;
+
+    let armed = async {
+        audio.start(app, capture)?;
+        if let Err(e) = transcription
+            .start(app, &audio, options.transcription)
+            .await
+        {
+            audio.stop();
+            return Err(e);
+        }
+        Ok(())
+    };
+    if let Err(e) = armed.await {
+        let _ = recorder.apply(app, Transition::Fail(e.clone()));
+        return Err(e);
+    }
+
+    match recorder.apply(app, Transition::Armed) {
+        Ok(state) => Ok(state),
+        Err(e) => {
+            // A cancel while arming already moved the machine on; release what we opened.
+            audio.stop();
+            let _ = transcription.stop().await;
+            Err(e)
+        }
+    }
+}
+
+/**
+ * Function: stop
+ * Recording → Finalizing → Delivering → Idle: stops capture, waits for the provider's
+ * final result (bounded by the flush timeout), then delivers the assembled transcript.
+ * Delivery never starts before finalization has finished.
+ */
+pub async fn stop(app: &AppHandle) -> Result<Delivery, String> {
+    let recorder = app.state::<Recorder>();
+    let _lifecycle = recorder.lifecycle.lock().await;
+
+    recorder.apply(app, Transition::Stop)?;
+    app.state::<AudioState>().stop();
+    let summary = match app.state::<TranscriptionState>().stop().await {
+        Ok(summary) => summary.unwrap_or(SessionSummary {
+            transcript: String::new(),
+            segments: Vec::new(),
+            reason: FinalizeReason::ConnectionClosed,
+            session: SessionInfo::default(),
+        }),
+        Err(e) => {
+            let _ = recorder.apply(app, Transition::Fail(e.clone()));
+            return Err(e);
+        }
+    };
+
+    recorder.apply(app, Transition::Finalized)?;
+    let options = recorder.delivery.lock().unwrap().clone();
+    match delivery::deliver(app, summary.clone(), &options).await {
+        Ok(delivery) => {
+            history::record(app, &summary, Some(&delivery));
+            recorder.apply(app, Transition::Delivered)?;
+            Ok(delivery)
+        }
+        Err(e) => {
+            // Keep the text even though it never reached its destination.
+            history::record(app, &summary, None);
+            let _ = recorder.apply(app, Transition::Fail(e.clone()));
+            Err(e)
+        }
+    }
+}
+
+/**
+ * Function: cancel
+ * Tears down whatever is running and returns to Idle without delivering. A stop that
+ * is already finalizing is allowed to finish first, so its transcript is not lost.
+ */
+pub async fn cancel(app: &AppHandle) -> Result<RecordingState, String> {
+    let recorder = app.state::<Recorder>();
+    let _lifecycle = recorder.lifecycle.lock().await;
+    app.state::<AudioState>().stop();
+    app.state::<TranscriptionState>().stop().await?;
+    recorder.apply(app, Transition::Reset)
+}
+
+/// Releases capture and transcription after a backend failure while recording. What
+/// was transcribed until then is kept in the history.
+fn fail(app: &AppHandle, message: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let recorder = app.state::<Recorder>();
+        let _lifecycle = recorder.lifecycle.lock().await;
+        // Once a stop has begun, it finalizes and reports the outcome itself.
+        if !matches!(
+            recorder.state(),
+            RecordingState::Arming | RecordingState::Recording
+        ) {
+            return;
+        }
+        app.state::<AudioState>().stop();
+        if let Ok(Some(summary)) = app.state::<TranscriptionState>().stop().await {
+            history::record(&app, &summary, None);
+        }
+        let _ = recorder.apply(&app, Transition::Fail(message));
+    });
+}
+
+/**
+ * Function: setup
+ * Responsibility: Lets backend events drive the machine: the VAD's silence timeout
+ * stops a recording, and capture errors or a provider giving up move it to `Error`.
+ * Called once from the Tauri `setup` hook.
+ */
+pub fn setup(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any(SILENCE_TIMEOUT_EVENT, move |_| {
+        if handle.state::<Recorder>().state() != RecordingState::Recording {
+            return;
+        }
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = stop(&app).await;
+        });
+    });
+
+    let handle = app.clone();
+    app.listen_any(CAPTURE_ERROR_EVENT, move |event| {
+        let message = serde_json::from_str::<String>(event.payload())
+            .unwrap_or_else(|_| "Microphone capture failed".into());
+        fail(&handle, message);
+    });
+
+    let handle = app.clone();
+    app.listen_any(TRANSCRIPTION_CLOSED_EVENT, move |event| {
+        // Closing is expected while finalizing; only an unprompted close is a failure.
+        if handle.state::<Recorder>().state() != RecordingState::Recording {
+            return;
+        }
+        let reason = serde_json::from_str::<serde_json::Value>(event.payload())
+            .ok()
+            .and_then(|v| v.get("reason")?.as_str().map(str::to_string))
+            .filter(|reason| !reason.is_empty())
+            .unwrap_or_else(|| "Transcription service closed the connection".into());
+        fail(&handle, reason);
+    });
+}
+
+/**
+ * Command: start_recording
+ * Responsibility: Starts a dictation (microphone + transcription) from Idle or Error.
+ */
+#[tauri::command]
+pub async fn start_recording(
+    app: AppHandle,
+    options: Option<RecordingOptions>,
+) -> Result<RecordingState, String> {
+    start(&app, options.unwrap_or_default()).await
+}
+
+/**
+ * Command: stop_recording
+ * Responsibility: Stops the dictation and resolves with what was delivered.
+ */
+#[tauri::command]
+pub async fn stop_recording(app: AppHandle) -> Result<Delivery, String> {
+    stop(&app).await
+}
+
+/**
+ * Command: cancel_recording
+ * Responsibility: Aborts from any state and returns to Idle.
+ */
+#[tauri::command]
+pub async fn cancel_recording(app: AppHandle) -> Result<RecordingState, String> {
+    cancel(&app).await
+}
+
+/**
+ * Command: get_recording_state
+ * Responsibility: Current state, for views that mount mid-session.
+ */
+#[tauri::command]
+pub fn get_recording_state(recorder: State<'_, Recorder>) -> RecordingState {
+    recorder.state()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error() -> RecordingState {
+        RecordingState::Error {
+            message: "boom".into(),
+        }
+    }
+
+    #[test]
+    fn transition_table() {
+        use RecordingState as S;
+        use Transition as T;
+
+        let states = [
+            S::Idle,
+            S::Arming,
+            S::Recording,
+            S::Finalizing,
+            S::Delivering,
+            error(),
+        ];
+        let fail = T::Fail("boom".into());
+        let transitions = [
+            T::Start,
+            T::Armed,
+            T::Stop,
+            T::Finalized,
+            T::Delivered,
+            fail.clone(),
+            T::Reset,
+        ];
+
+        // Every allowed move; anything not listed must be rejected.
+        let mut allowed = vec![
+            (S::Idle, T::Start, S::Arming),
+            (error(), T::Start, S::Arming),
+            (S::Arming, T::Armed, S::Recording),
+            (S::Recording, T::Stop, S::Finalizing),
+            (S::Finalizing, T::Finalized, S::Delivering),
+            (S::Delivering, T::Delivered, S::Idle),
+        ];
+        for state in &states {
+            if *state != S::Idle {
+                allowed.push((state.clone(), fail.clone(), error()));
+            }
+            allowed.push((state.clone(), T::Reset, S::Idle));
+        }
+        let expected = |state: &S, transition: &T| {
+            allowed
+                .iter()
+                .find(|(from, via, _)| from == state && via == transition)
+                .map(|(_, _, to)| to.clone())
+        };
+
+        for state in &states {
+            for transition in &transitions {
+                assert_eq!(
+                    state.next(transition).ok(),
+                    expected(state, transition),
+                    "{state:?} + {transition:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn happy_path_returns_to_idle() {
+        let steps = [
+            Transition::Start,
+            Transition::Armed,
+            Transition::Stop,
+            Transition::Finalized,
+            Transition::Delivered,
+        ];
+        let end = steps.iter().try_fold(RecordingState::Idle, |state, t| {
+            let next = state.next(t)?;
+            assert_eq!(next.is_active(), *t != Transition::Delivered);
+            Ok::<_, String>(next)
+        });
+        assert_eq!(end, Ok(RecordingState::Idle));
+    }
+
+    #[test]
+    fn rejections_name_the_state() {
+        assert_eq!(
+            RecordingState::Finalizing.next(&Transition::Start),
+            Err("Cannot start while finalizing".into())
+        );
+        assert_eq!(
+            error().next(&Transition::Stop),
+            Err("Cannot stop while in error".into())
+        );
+        assert!(!error().is_active());
+    }
+}
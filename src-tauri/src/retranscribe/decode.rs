@@ -0,0 +1,140 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use crate::audio::{LinearResampler, DEFAULT_SAMPLE_RATE};
+
+/**
+ * Enum: AudioInput
+ * A file ready to send to a provider: decoded to mono PCM at `DEFAULT_SAMPLE_RATE`, or,
+ * when its codec cannot be decoded here (Opus), the file as is. `Encoded` input only
+ * works with providers that decode audio themselves, i.e. Deepgram; Whisper rejects it.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioInput {
+    Pcm(Vec<i16>),
+    Encoded(Vec<u8>),
+}
+
+impl AudioInput {
+    /// Length of the audio, when it is known without decoding.
+    pub fn duration_ms(&self) -> Option<u64> {
+        match self {
+            AudioInput::Pcm(samples) => {
+                Some(samples.len() as u64 * 1000 / DEFAULT_SAMPLE_RATE as u64)
+            }
+            AudioInput::Encoded(_) => None,
+        }
+    }
+}
+
+fn decode_error(e: Error) -> String {
+    format!("Cannot decode audio: {e}")
+}
+
+/**
+ * Function: load
+ * Reads a WAV, Ogg, WebM/Matroska or FLAC file. The first audio track is downmixed
+ * and resampled to `DEFAULT_SAMPLE_RATE` if its codec is PCM, FLAC or Vorbis. Any other
+ * codec, notably Opus (the usual one in WebM), leaves the file encoded for providers
+ * that decode it themselves.
+ */
+pub fn load(path: &Path) -> Result<AudioInput, String> {
+    let file = File::open(path).map_err(|e| format!("Cannot open {}: {e}", path.display()))?;
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            MediaSourceStream::new(Box::new(file), Default::default()),
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| match e {
+            Error::Unsupported(_) => {
+                "Unsupported audio format; use WAV, FLAC, Ogg or WebM (Opus needs Deepgram)".into()
+            }
+            e => decode_error(e),
+        })?;
+    let mut format = probed.format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("The file has no audio track")?;
+    let track_id = track.id;
+    let mut decoder = match symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+    {
+        Ok(decoder) => decoder,
+        Err(Error::Unsupported(_)) => {
+            let bytes = fs::read(path).map_err(|e| format!("Cannot read audio: {e}"))?;
+            return Ok(AudioInput::Encoded(bytes));
+        }
+        Err(e) => return Err(decode_error(e)),
+    };
+
+    let mut resampler: Option<(u32, LinearResampler)> = None;
+    let mut mono = Vec::new();
+    let mut resampled = Vec::new();
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(Error::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(Error::ResetRequired) => break,
+            Err(e) => return Err(decode_error(e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A damaged packet loses a few milliseconds; keep the rest.
+            Err(Error::DecodeError(_)) => continue,
+            Err(e) => return Err(decode_error(e)),
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        let mut interleaved = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        interleaved.copy_interleaved_ref(decoded);
+        mono.clear();
+        mono.extend(
+            interleaved
+                .samples()
+                .chunks(channels)
+                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32),
+        );
+
+        let rate = spec.rate;
+        let block = if rate == DEFAULT_SAMPLE_RATE {
+            &mono
+        } else {
+            if resampler.as_ref().is_some_and(|(from, _)| *from != rate) {
+                resampler = None;
+            }
+            let (_, resampler) = resampler
+                .get_or_insert_with(|| (rate, LinearResampler::new(rate, DEFAULT_SAMPLE_RATE)));
+            resampled.clear();
+            resampler.process(&mono, &mut resampled);
+            &resampled
+        };
+        samples.extend(
+            block
+                .iter()
+                .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16),
+        );
+    }
+    Ok(AudioInput::Pcm(samples))
+}
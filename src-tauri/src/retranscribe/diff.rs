@@ -0,0 +1,168 @@
+use serde::Serialize;
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/**
+ * Enum: DiffKind
+ * How a run of words changed from the earlier transcript to the new one.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffKind {
+    Equal,
+    /// Only in the new transcript.
+    Inserted,
+    /// Only in the earlier transcript.
+    Deleted,
+}
+
+/**
+ * Struct: DiffPart
+ * A run of words with the same `kind`, joined by single spaces.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffPart {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/**
+ * Struct: WordDiff
+ * The new transcript against an earlier one, in reading order, with word counts.
+ */
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct WordDiff {
+    pub parts: Vec<DiffPart>,
+    pub equal: usize,
+    pub inserted: usize,
+    pub deleted: usize,
+}
+
+impl WordDiff {
+    fn push(&mut self, kind: DiffKind, words: &[&str]) {
+        if words.is_empty() {
+            return;
+        }
+        match kind {
+            DiffKind::Equal => self.equal += words.len(),
+            DiffKind::Inserted => self.inserted += words.len(),
+            DiffKind::Deleted => self.deleted += words.len(),
+        }
+        let text = words.join(" ");
+        match self.parts.last_mut() {
+            Some(last) if last.kind == kind => {
+                last.text.push(' ');
+                last.text.push_str(&text);
+            }
+            _ => self.parts.push(DiffPart { kind, text }),
+        }
+    }
+}
+
+/// Case and surrounding punctuation are formatting, not what was heard.
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/**
+ * Function: word_diff
+ * Compares two transcripts word by word, ignoring case and punctuation around words.
+ * Unchanged runs are shown as they appear in `new`.
+ */
+pub fn word_diff(old: &str, new: &str) -> WordDiff {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    let old_keys: Vec<String> = old_words.iter().map(|w| normalize(w)).collect();
+    let new_keys: Vec<String> = new_words.iter().map(|w| normalize(w)).collect();
+
+    let mut diff = WordDiff::default();
+    for op in capture_diff_slices(Algorithm::Patience, &old_keys, &new_keys) {
+        match op {
+            DiffOp::Equal { new_index, len, .. } => {
+                diff.push(DiffKind::Equal, &new_words[new_index..new_index + len]);
+            }
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => {
+                diff.push(
+                    DiffKind::Deleted,
+                    &old_words[old_index..old_index + old_len],
+                );
+            }
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => {
+                diff.push(
+                    DiffKind::Inserted,
+                    &new_words[new_index..new_index + new_len],
+                );
+            }
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                diff.push(
+                    DiffKind::Deleted,
+                    &old_words[old_index..old_index + old_len],
+                );
+                diff.push(
+                    DiffKind::Inserted,
+                    &new_words[new_index..new_index + new_len],
+                );
+            }
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(kind: DiffKind, text: &str) -> DiffPart {
+        DiffPart {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    #[test]
+    fn identical_transcripts_are_one_equal_run() {
+        let diff = word_diff("hello there world", "hello there world");
+        assert_eq!(diff.parts, [part(DiffKind::Equal, "hello there world")]);
+        assert_eq!((diff.equal, diff.inserted, diff.deleted), (3, 0, 0));
+    }
+
+    #[test]
+    fn case_and_punctuation_are_not_changes() {
+        let diff = word_diff("hello, world", "Hello world.");
+        assert_eq!(diff.parts, [part(DiffKind::Equal, "Hello world.")]);
+    }
+
+    #[test]
+    fn replaced_words_show_as_deleted_then_inserted() {
+        let diff = word_diff("send it to Jon today", "send it to John today please");
+        assert_eq!(
+            diff.parts,
+            [
+                part(DiffKind::Equal, "send it to"),
+                part(DiffKind::Deleted, "Jon"),
+                part(DiffKind::Inserted, "John"),
+                part(DiffKind::Equal, "today"),
+                part(DiffKind::Inserted, "please"),
+            ]
+        );
+        assert_eq!((diff.equal, diff.inserted, diff.deleted), (4, 2, 1));
+    }
+
+    #[test]
+    fn empty_sides_are_all_inserted_or_deleted() {
+        let added = word_diff("", "brand new text");
+        assert_eq!(added.parts, [part(DiffKind::Inserted, "brand new text")]);
+        let removed = word_diff("  old   words ", "");
+        assert_eq!(removed.parts, [part(DiffKind::Deleted, "old words")]);
+        assert_eq!(word_diff("", ""), WordDiff::default());
+    }
+}
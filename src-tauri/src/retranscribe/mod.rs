@@ -0,0 +1,343 @@
+pub mod decode;
+pub mod diff;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::audio::DEFAULT_SAMPLE_RATE;
+use crate::settings::SettingsState;
+use crate::stt::provider::{SessionOptions, SttProvider};
+use crate::stt::{
+    self, SttEvent, TranscriptAssembler, TranscriptSegment, TranscriptionOptions, DEFAULT_PROVIDER,
+};
+pub use decode::AudioInput;
+pub use diff::{word_diff, DiffKind, DiffPart, WordDiff};
+
+/// PCM sent per push: 250 ms.
+const CHUNK_SAMPLES: usize = DEFAULT_SAMPLE_RATE as usize / 4;
+
+/// Bytes of an encoded file sent per push.
+const CHUNK_BYTES: usize = 32 * 1024;
+
+/// How long the provider may take beyond the length of the audio.
+const RESULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/**
+ * Struct: RetranscribeOptions
+ * Options accepted by `retranscribe_file`. Provider, model and language default to
+ * those in Settings, as in `start_transcription`.
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetranscribeOptions {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    /// Transcript to compare the new one against, e.g. the live one from the history.
+    pub previous_transcript: Option<String>,
+}
+
+/**
+ * Struct: Retranscription
+ * The new transcript of a file, and how it differs from the earlier one if given.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Retranscription {
+    pub transcript: String,
+    pub segments: Vec<TranscriptSegment>,
+    pub provider: String,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    /// `None` when the file was sent to the provider undecoded.
+    pub audio_ms: Option<u64>,
+    pub diff: Option<WordDiff>,
+}
+
+/**
+ * Function: transcribe
+ * Runs a whole file through one session of `provider`, as fast as it accepts the
+ * audio, and assembles the final results. `AudioInput::Encoded` files (Opus) need a
+ * provider that decodes them itself, which today is only Deepgram. Fails if the audio could not all be sent,
+ * or if nothing was recognized and the provider reported an error.
+ */
+pub async fn transcribe(
+    provider: &dyn SttProvider,
+    input: &AudioInput,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<(String, Vec<TranscriptSegment>), String> {
+    let mut session = provider
+        .open(&SessionOptions {
+            model,
+            language,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            encoded: matches!(input, AudioInput::Encoded(_)),
+        })
+        .await?;
+    // If the provider drops the connection mid-file, its events say why. Close even
+    // then, so a session that refused the audio outright still ends.
+    let pushed = match input {
+        AudioInput::Pcm(samples) => samples
+            .chunks(CHUNK_SAMPLES)
+            .try_for_each(|chunk| session.push_audio(chunk)),
+        AudioInput::Encoded(bytes) => bytes
+            .chunks(CHUNK_BYTES)
+            .try_for_each(|chunk| session.push_encoded(chunk)),
+    };
+    let pushed = pushed.and(session.close());
+
+    let mut assembler = TranscriptAssembler::default();
+    // The first problem reported is the cause; later ones tend to be fallout.
+    let mut failure = None;
+    let collect = async {
+        while let Some(event) = session.next_event().await {
+            match &event {
+                SttEvent::Error(message) => {
+                    failure.get_or_insert_with(|| message.clone());
+                }
+                SttEvent::Closed { code, reason } => {
+                    if code.is_some_and(|code| code != 1000) {
+                        failure.get_or_insert_with(|| {
+                            if reason.is_empty() {
+                                format!("Connection closed with code {}", code.unwrap_or_default())
+                            } else {
+                                reason.clone()
+                            }
+                        });
+                    }
+                    break;
+                }
+                _ => assembler.push(&event),
+            }
+        }
+    };
+    let timeout = RESULT_TIMEOUT + Duration::from_millis(input.duration_ms().unwrap_or(0));
+    let collected = tokio::time::timeout(timeout, collect).await;
+
+    if let Err(e) = pushed {
+        return Err(failure.unwrap_or(e));
+    }
+    collected.map_err(|_| "Timed out waiting for the transcript".to_string())?;
+    match failure {
+        Some(failure) if assembler.segments().is_empty() => Err(failure),
+        _ => Ok((assembler.text(), assembler.segments().to_vec())),
+    }
+}
+
+/**
+ * Command: retranscribe_file
+ * Responsibility: Transcribes an audio file on disk (WAV, FLAC, Ogg or WebM) again with
+ * the given provider, model and language, e.g. a recording from `list_recordings`.
+ * Opus audio cannot be decoded here and only works with Deepgram.
+ * With `previousTranscript`, also returns a word-level diff against it.
+ */
+#[tauri::command]
+pub async fn retranscribe_file(
+    app: AppHandle,
+    path: String,
+    options: Option<RetranscribeOptions>,
+) -> Result<Retranscription, String> {
+    let options = options.unwrap_or_default();
+    let selected = TranscriptionOptions {
+        provider: options.provider,
+        model: options.model,
+        language: options.language,
+        ..TranscriptionOptions::default()
+    }
+    .with_defaults(&app.state::<SettingsState>().get());
+    let provider_id = selected.provider.as_deref().unwrap_or(DEFAULT_PROVIDER);
+    let provider = stt::create_provider(&app, provider_id)?;
+
+    let path = PathBuf::from(path);
+    let input = tokio::task::spawn_blocking(move || decode::load(&path))
+        .await
+        .map_err(|e| e.to_string())??;
+    let (transcript, segments) = transcribe(
+        provider.as_ref(),
+        &input,
+        selected.model.clone(),
+        selected.language.clone(),
+    )
+    .await?;
+
+    Ok(Retranscription {
+        diff: options
+            .previous_transcript
+            .as_deref()
+            .map(|previous| word_diff(previous, &transcript)),
+        transcript,
+        segments,
+        provider: provider_id.to_string(),
+        model: selected.model,
+        language: selected.language,
+        audio_ms: input.duration_ms(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::stt::provider::SttSession;
+    use crate::stt::Transcript;
+
+    /**
+     * Struct: StubProvider
+     * Opens sessions that take PCM only, record how much audio they were given, and
+     * replay `events` once closed. Without a `Closed` event they never finish.
+     */
+    struct StubProvider {
+        events: Vec<SttEvent>,
+        pushed: Arc<Mutex<usize>>,
+    }
+
+    struct StubSession {
+        events: VecDeque<SttEvent>,
+        pushed: Arc<Mutex<usize>>,
+        closed: bool,
+    }
+
+    impl StubProvider {
+        fn new(events: Vec<SttEvent>) -> Self {
+            Self {
+                events,
+                pushed: Arc::default(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SttProvider for StubProvider {
+        fn id(&self) -> &'static str {
+            "stub"
+        }
+
+        async fn open(&self, _options: &SessionOptions) -> Result<Box<dyn SttSession>, String> {
+            Ok(Box::new(StubSession {
+                events: self.events.clone().into(),
+                pushed: self.pushed.clone(),
+                closed: false,
+            }))
+        }
+    }
+
+    #[async_trait]
+    impl SttSession for StubSession {
+        fn push_audio(&mut self, samples: &[i16]) -> Result<(), String> {
+            *self.pushed.lock().unwrap() += samples.len();
+            Ok(())
+        }
+
+        async fn next_event(&mut self) -> Option<SttEvent> {
+            match self.events.pop_front() {
+                Some(event) if self.closed => Some(event),
+                _ => std::future::pending().await,
+            }
+        }
+
+        fn close(&mut self) -> Result<(), String> {
+            self.closed = true;
+            Ok(())
+        }
+    }
+
+    fn result(text: &str, start: f64, is_final: bool) -> SttEvent {
+        SttEvent::Transcript(Transcript {
+            transcript: text.into(),
+            is_final,
+            speech_final: is_final,
+            confidence: 0.9,
+            start,
+            duration: 1.0,
+            words: Vec::new(),
+        })
+    }
+
+    fn closed(code: Option<u16>) -> SttEvent {
+        SttEvent::Closed {
+            code,
+            reason: String::new(),
+        }
+    }
+
+    /// Two seconds of silence.
+    fn pcm() -> AudioInput {
+        AudioInput::Pcm(vec![0; 2 * DEFAULT_SAMPLE_RATE as usize])
+    }
+
+    #[tokio::test]
+    async fn finals_are_assembled_from_the_whole_file() {
+        let provider = StubProvider::new(vec![
+            result("Hello", 0.0, false),
+            result("Hello world.", 0.0, true),
+            SttEvent::UtteranceEnd,
+            result("Second line.", 1.0, true),
+            closed(None),
+        ]);
+
+        let (text, segments) = transcribe(&provider, &pcm(), None, None).await.unwrap();
+        assert_eq!(text, "Hello world. Second line.");
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].start, 1.0);
+        assert_eq!(
+            *provider.pushed.lock().unwrap(),
+            2 * DEFAULT_SAMPLE_RATE as usize
+        );
+    }
+
+    #[tokio::test]
+    async fn encoded_files_need_a_provider_that_decodes() {
+        let provider = StubProvider::new(vec![closed(None)]);
+        let opus = AudioInput::Encoded(b"OggS".to_vec());
+
+        let error = transcribe(&provider, &opus, None, None).await.unwrap_err();
+        assert_eq!(error, "This provider only accepts PCM audio");
+    }
+
+    #[tokio::test]
+    async fn provider_errors_fail_only_an_empty_transcript() {
+        let failed = StubProvider::new(vec![
+            SttEvent::Error("Invalid model".into()),
+            SttEvent::Error("Later fallout".into()),
+            closed(Some(1011)),
+        ]);
+        assert_eq!(
+            transcribe(&failed, &pcm(), None, None).await,
+            Err("Invalid model".into())
+        );
+
+        let partial = StubProvider::new(vec![
+            result("Got this far.", 0.0, true),
+            SttEvent::Error("Stream interrupted".into()),
+            closed(Some(1011)),
+        ]);
+        let (text, _) = transcribe(&partial, &pcm(), None, None).await.unwrap();
+        assert_eq!(text, "Got this far.");
+    }
+
+    #[tokio::test]
+    async fn abnormal_close_without_a_reason_names_its_code() {
+        let provider = StubProvider::new(vec![closed(Some(1011))]);
+        assert_eq!(
+            transcribe(&provider, &pcm(), None, None).await,
+            Err("Connection closed with code 1011".into())
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn provider_that_never_closes_times_out() {
+        let provider = StubProvider::new(vec![result("Hello.", 0.0, true)]);
+        assert_eq!(
+            transcribe(&provider, &pcm(), None, None).await,
+            Err("Timed out waiting for the transcript".into())
+        );
+    }
+}
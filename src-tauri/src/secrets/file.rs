@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{KeySource, SecretStore};
+
+/// Where systemd and D-Bus keep the per-installation machine id.
+const MACHINE_ID_PATHS: [&str; 2] = ["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+/// Separates this key derivation from any other use of the machine id.
+const KEY_CONTEXT: &[u8] = b"wispr-flow-clone secrets v1\0";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/**
+ * Struct: SecretsFile
+ * On-disk layout: a random salt and, per secret name, the hex of nonce ‖ ciphertext.
+ */
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretsFile {
+    salt: String,
+    entries: BTreeMap<String, String>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// The per-installation machine id the secrets key is derived from.
+pub fn machine_id() -> Result<Vec<u8>, String> {
+    MACHINE_ID_PATHS
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|id| id.trim().as_bytes().to_vec())
+        .find(|id| !id.is_empty())
+        .ok_or_else(|| "No machine id to derive the secrets key from".to_string())
+}
+
+/**
+ * Struct: EncryptedFileStore
+ * Responsibility: Fallback for sessions without a keyring (headless Linux, SSH, CI).
+ * Secrets are sealed with ChaCha20-Poly1305 under a key derived from the machine id and
+ * a per-file salt, with the secret name as associated data, and the file is only
+ * readable by its owner. This keeps keys out of plain text, backups and copies taken
+ * to another machine; it does not stop other processes of the same user, which could
+ * derive the same key.
+ */
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    machine_id: Vec<u8>,
+    /// Serializes read-modify-write cycles on the file.
+    lock: Mutex<()>,
+}
+
+impl EncryptedFileStore {
+    /// A store in `path`, keyed to `machine_id` (see `machine_id()`).
+    pub fn open(path: PathBuf, machine_id: Vec<u8>) -> Self {
+        Self {
+            path,
+            machine_id,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> Result<SecretsFile, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Unreadable {}: {e}", self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SecretsFile::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn write(&self, file: &SecretsFile) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_vec_pretty(file).map_err(|e| e.to_string())?;
+        write_private(&self.path, &json).map_err(|e| e.to_string())
+    }
+
+    fn cipher(&self, salt: &str) -> Result<ChaCha20Poly1305, String> {
+        let salt = from_hex(salt).ok_or("Corrupt salt in the secrets file")?;
+        let key = Sha256::new()
+            .chain_update(KEY_CONTEXT)
+            .chain_update(&self.machine_id)
+            .chain_update(salt)
+            .finalize();
+        Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+    }
+}
+
+/// Writes `contents` to a file only its owner can read, replacing any previous file.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // `mode` only applies to new files; tighten one left by an older build too.
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(contents)
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn kind(&self) -> KeySource {
+        KeySource::EncryptedFile
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        let _guard = self.lock.lock().unwrap();
+        let file = self.read()?;
+        let Some(sealed) = file.entries.get(name) else {
+            return Ok(None);
+        };
+
+        let sealed = from_hex(sealed)
+            .filter(|bytes| bytes.len() > NONCE_LEN)
+            .ok_or_else(|| format!("Corrupt secret {name}"))?;
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plain = self
+            .cipher(&file.salt)?
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| {
+                format!("Cannot decrypt secret {name}; it was stored on another machine or has been modified")
+            })?;
+        String::from_utf8(plain)
+            .map(Some)
+            .map_err(|_| format!("Corrupt secret {name}"))
+    }
+
+    fn set(&self, name: &str, secret: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+        let mut file = self.read()?;
+        if file.salt.is_empty() {
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            file.salt = to_hex(&salt);
+        }
+
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher(&file.salt)?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: secret.as_bytes(),
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| format!("Cannot encrypt secret {name}"))?;
+        let sealed = [nonce.as_slice(), &ciphertext].concat();
+        file.entries.insert(name.into(), to_hex(&sealed));
+        self.write(&file)
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+        let mut file = self.read()?;
+        if file.entries.remove(name).is_none() {
+            return Ok(());
+        }
+        self.write(&file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MACHINE: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    fn store(dir: &Path) -> EncryptedFileStore {
+        EncryptedFileStore::open(dir.join("secrets.json"), MACHINE.to_vec())
+    }
+
+    fn edit_entries(store: &EncryptedFileStore, edit: impl FnOnce(&mut SecretsFile)) {
+        let mut file = store.read().unwrap();
+        edit(&mut file);
+        store.write(&file).unwrap();
+    }
+
+    #[test]
+    fn secrets_round_trip_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        assert_eq!(store.get("deepgram-api-key"), Ok(None));
+
+        store.set("deepgram-api-key", "dg-secret").unwrap();
+        store.set("other", "second").unwrap();
+        assert_eq!(store.get("deepgram-api-key"), Ok(Some("dg-secret".into())));
+        store.set("deepgram-api-key", "dg-rotated").unwrap();
+        assert_eq!(store.get("deepgram-api-key"), Ok(Some("dg-rotated".into())));
+
+        store.delete("deepgram-api-key").unwrap();
+        store.delete("deepgram-api-key").unwrap();
+        assert_eq!(store.get("deepgram-api-key"), Ok(None));
+        assert_eq!(store.get("other"), Ok(Some("second".into())));
+    }
+
+    #[test]
+    fn file_holds_no_plain_text_and_is_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        store.set("deepgram-api-key", "dg-secret").unwrap();
+
+        let contents = fs::read_to_string(dir.path().join("secrets.json")).unwrap();
+        assert!(!contents.contains("dg-secret"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.path().join("secrets.json"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn tampered_ciphertext_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        store.set("deepgram-api-key", "dg-secret").unwrap();
+        edit_entries(&store, |file| {
+            let sealed = file.entries.get_mut("deepgram-api-key").unwrap();
+            let last = sealed.pop().unwrap();
+            sealed.push(if last == '0' { '1' } else { '0' });
+        });
+
+        let error = store.get("deepgram-api-key").unwrap_err();
+        assert!(error.contains("Cannot decrypt"), "{error}");
+    }
+
+    #[test]
+    fn ciphertext_cannot_be_read_under_another_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        store.set("deepgram-api-key", "dg-secret").unwrap();
+        edit_entries(&store, |file| {
+            let sealed = file.entries["deepgram-api-key"].clone();
+            file.entries.insert("other-api-key".into(), sealed);
+        });
+
+        assert!(store.get("other-api-key").is_err());
+        assert_eq!(store.get("deepgram-api-key"), Ok(Some("dg-secret".into())));
+    }
+
+    #[test]
+    fn file_from_another_machine_cannot_be_read() {
+        let dir = tempfile::tempdir().unwrap();
+        store(dir.path())
+            .set("deepgram-api-key", "dg-secret")
+            .unwrap();
+
+        let elsewhere =
+            EncryptedFileStore::open(dir.path().join("secrets.json"), b"another machine".to_vec());
+        assert!(elsewhere.get("deepgram-api-key").is_err());
+    }
+}
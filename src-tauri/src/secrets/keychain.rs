@@ -0,0 +1,64 @@
+use keyring::{Entry, Error};
+
+use super::{KeySource, SecretStore};
+
+/// Entry looked up once at startup to see whether the platform store answers.
+const PROBE_ENTRY: &str = "probe";
+
+/**
+ * Struct: KeychainStore
+ * Responsibility: Keeps secrets in the platform credential store: the Secret Service
+ * (GNOME Keyring, KWallet) on Linux, the Keychain on macOS, the Credential Manager on
+ * Windows. Entries are filed under `service`, one per secret name.
+ */
+pub struct KeychainStore {
+    service: String,
+}
+
+impl KeychainStore {
+    /**
+     * Function: probe
+     * Returns a store if the platform store can be reached. Headless Linux sessions
+     * usually have no Secret Service on the session bus and fail here.
+     */
+    pub fn probe(service: &str) -> Result<Self, String> {
+        let store = Self {
+            service: service.into(),
+        };
+        match store.entry(PROBE_ENTRY)?.get_password() {
+            Ok(_) | Err(Error::NoEntry) => Ok(store),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn entry(&self, name: &str) -> Result<Entry, String> {
+        Entry::new(&self.service, name).map_err(|e| e.to_string())
+    }
+}
+
+impl SecretStore for KeychainStore {
+    fn kind(&self) -> KeySource {
+        KeySource::Keychain
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        match self.entry(name)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Cannot read from the system keyring: {e}")),
+        }
+    }
+
+    fn set(&self, name: &str, secret: &str) -> Result<(), String> {
+        self.entry(name)?
+            .set_password(secret)
+            .map_err(|e| format!("Cannot write to the system keyring: {e}"))
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        match self.entry(name)?.delete_credential() {
+            Ok(()) | Err(Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Cannot delete from the system keyring: {e}")),
+        }
+    }
+}
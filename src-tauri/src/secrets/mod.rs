@@ -0,0 +1,301 @@
+pub mod file;
+pub mod keychain;
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::stt;
+use file::{machine_id, EncryptedFileStore};
+use keychain::KeychainStore;
+
+/// Keyring service the entries are filed under; matches the bundle identifier.
+const SERVICE: &str = "com.wisprflow.clone";
+
+/// Fallback store in the app data dir, used when no keyring is reachable.
+const SECRETS_FILE: &str = "secrets.json";
+
+/// Providers that authenticate with an API key.
+pub const KEYED_PROVIDERS: &[&str] = &["deepgram"];
+
+/**
+ * Enum: KeySource
+ * Where a provider's key was found.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeySource {
+    Keychain,
+    EncryptedFile,
+    /// `<PROVIDER>_API_KEY` in the backend's environment, for development.
+    Environment,
+}
+
+/**
+ * Trait: SecretStore
+ * Responsibility: Named secrets at rest. Deleting a missing secret is not an error.
+ */
+pub trait SecretStore: Send + Sync {
+    fn kind(&self) -> KeySource;
+    fn get(&self, name: &str) -> Result<Option<String>, String>;
+    fn set(&self, name: &str, secret: &str) -> Result<(), String>;
+    fn delete(&self, name: &str) -> Result<(), String>;
+}
+
+/**
+ * Struct: ApiKeyStatus
+ * What the Settings panel may know about a key: whether there is one and where it
+ * lives. The key itself never leaves the backend.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyStatus {
+    pub provider: String,
+    pub configured: bool,
+    pub source: Option<KeySource>,
+    /// Where `set_api_key` would store a key.
+    pub store: Option<KeySource>,
+}
+
+/**
+ * Struct: SecretsState
+ * Responsibility: The store chosen at startup, or none if neither the keyring nor the
+ * fallback file could be used.
+ */
+#[derive(Default)]
+pub struct SecretsState {
+    store: OnceLock<Box<dyn SecretStore>>,
+}
+
+impl SecretsState {
+    fn store(&self) -> Result<&dyn SecretStore, String> {
+        self.store
+            .get()
+            .map(|store| store.as_ref())
+            .ok_or_else(|| "No secret storage is available".to_string())
+    }
+}
+
+fn entry_name(provider: &str) -> Result<String, String> {
+    if !KEYED_PROVIDERS.contains(&provider) {
+        return Err(format!("{provider} does not use an API key"));
+    }
+    Ok(format!("{provider}-api-key"))
+}
+
+fn env_var(provider: &str) -> String {
+    format!("{}_API_KEY", provider.to_uppercase())
+}
+
+/**
+ * Function: find_key
+ * Looks `name` up in `store`, then falls back to `env_key`. A store that cannot be
+ * read (locked keyring, undecryptable file) counts as having no key; its error is
+ * only returned when there is no key in the environment either.
+ */
+fn find_key(
+    store: Option<&dyn SecretStore>,
+    name: &str,
+    env_key: Option<String>,
+) -> Result<Option<(String, KeySource)>, String> {
+    let mut read_error = None;
+    if let Some(store) = store {
+        match store.get(name) {
+            Ok(Some(key)) => return Ok(Some((key, store.kind()))),
+            Ok(None) => {}
+            Err(e) => read_error = Some(e),
+        }
+    }
+    match env_key.filter(|key| !key.trim().is_empty()) {
+        Some(key) => Ok(Some((key, KeySource::Environment))),
+        None => read_error.map_or(Ok(None), Err),
+    }
+}
+
+/**
+ * Function: api_key
+ * The key for `provider` and where it came from: the secret store first, then the
+ * environment. For the backend only; nothing returns it to the webview.
+ */
+pub fn api_key(app: &AppHandle, provider: &str) -> Result<Option<(String, KeySource)>, String> {
+    let name = entry_name(provider)?;
+    let state = app.state::<SecretsState>();
+    find_key(
+        state.store().ok(),
+        &name,
+        std::env::var(env_var(provider)).ok(),
+    )
+}
+
+fn status(app: &AppHandle, provider: &str) -> Result<ApiKeyStatus, String> {
+    let source = api_key(app, provider)?.map(|(_, source)| source);
+    Ok(ApiKeyStatus {
+        provider: provider.into(),
+        configured: source.is_some(),
+        source,
+        store: app.state::<SecretsState>().store().ok().map(|s| s.kind()),
+    })
+}
+
+/**
+ * Function: setup
+ * Responsibility: Picks the secret store: the platform keyring when it answers,
+ * otherwise the encrypted file in the app data dir.
+ * Called once from the Tauri `setup` hook.
+ */
+pub fn setup(app: &AppHandle) {
+    let store: Result<Box<dyn SecretStore>, String> = match KeychainStore::probe(SERVICE) {
+        Ok(keychain) => Ok(Box::new(keychain)),
+        Err(e) => {
+            eprintln!("System keyring unavailable ({e}); using the encrypted secrets file");
+            app.path()
+                .app_data_dir()
+                .map_err(|e| e.to_string())
+                .and_then(|dir| Ok((dir.join(SECRETS_FILE), machine_id()?)))
+                .map(|(path, id)| {
+                    Box::new(EncryptedFileStore::open(path, id)) as Box<dyn SecretStore>
+                })
+        }
+    };
+
+    match store {
+        Ok(store) => {
+            let _ = app.state::<SecretsState>().store.set(store);
+        }
+        Err(e) => eprintln!("No secret storage available: {e}"),
+    }
+}
+
+/**
+ * Command: get_api_key_status
+ * Responsibility: Whether `provider` has a key, without revealing it.
+ */
+#[tauri::command]
+pub fn get_api_key_status(app: AppHandle, provider: String) -> Result<ApiKeyStatus, String> {
+    status(&app, &provider)
+}
+
+/**
+ * Command: set_api_key
+ * Responsibility: Stores a key typed into the Settings panel, replacing any previous one.
+ */
+#[tauri::command]
+pub fn set_api_key(
+    app: AppHandle,
+    state: State<'_, SecretsState>,
+    provider: String,
+    key: String,
+) -> Result<ApiKeyStatus, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("API key is empty".into());
+    }
+    state.store()?.set(&entry_name(&provider)?, key)?;
+    status(&app, &provider)
+}
+
+/**
+ * Command: clear_api_key
+ * Responsibility: Deletes the stored key. A key from the environment still applies.
+ */
+#[tauri::command]
+pub fn clear_api_key(
+    app: AppHandle,
+    state: State<'_, SecretsState>,
+    provider: String,
+) -> Result<ApiKeyStatus, String> {
+    state.store()?.delete(&entry_name(&provider)?)?;
+    status(&app, &provider)
+}
+
+/**
+ * Command: test_api_key
+ * Responsibility: Checks a key against the provider by opening and closing a session.
+ * Tests `key` if given (before saving it), otherwise the key in use.
+ */
+#[tauri::command]
+pub async fn test_api_key(
+    app: AppHandle,
+    provider: String,
+    key: Option<String>,
+) -> Result<(), String> {
+    let key = match key.map(|k| k.trim().to_string()).filter(|k| !k.is_empty()) {
+        Some(key) => key,
+        None => {
+            api_key(&app, &provider)?
+                .ok_or_else(|| format!("No API key set for {provider}"))?
+                .0
+        }
+    };
+    stt::check_api_key(&provider, key).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A store that holds at most one key, or cannot be read at all.
+    struct StubStore(Result<Option<String>, String>);
+
+    impl SecretStore for StubStore {
+        fn kind(&self) -> KeySource {
+            KeySource::Keychain
+        }
+
+        fn get(&self, _name: &str) -> Result<Option<String>, String> {
+            self.0.clone()
+        }
+
+        fn set(&self, _name: &str, _secret: &str) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn delete(&self, _name: &str) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn env(key: &str) -> Option<String> {
+        Some(key.into())
+    }
+
+    #[test]
+    fn stored_key_wins_over_the_environment() {
+        let store = StubStore(Ok(Some("stored".into())));
+        assert_eq!(
+            find_key(Some(&store), "deepgram-api-key", env("from-env")),
+            Ok(Some(("stored".into(), KeySource::Keychain)))
+        );
+    }
+
+    #[test]
+    fn environment_is_used_without_a_stored_key() {
+        let empty = StubStore(Ok(None));
+        assert_eq!(
+            find_key(Some(&empty), "deepgram-api-key", env("from-env")),
+            Ok(Some(("from-env".into(), KeySource::Environment)))
+        );
+        assert_eq!(
+            find_key(None, "deepgram-api-key", env("from-env")),
+            Ok(Some(("from-env".into(), KeySource::Environment)))
+        );
+        assert_eq!(
+            find_key(Some(&empty), "deepgram-api-key", env("  ")),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn unreadable_store_falls_back_to_the_environment() {
+        let locked = StubStore(Err("Keyring is locked".into()));
+        assert_eq!(
+            find_key(Some(&locked), "deepgram-api-key", env("from-env")),
+            Ok(Some(("from-env".into(), KeySource::Environment)))
+        );
+        assert_eq!(
+            find_key(Some(&locked), "deepgram-api-key", None),
+            Err("Keyring is locked".into())
+        );
+    }
+}
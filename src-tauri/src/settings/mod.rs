@@ -0,0 +1,571 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::audio::AudioState;
+use crate::delivery::{InsertMode, DEFAULT_RESTORE_DELAY_MS};
+use crate::history::{self, RetentionPolicy};
+use crate::shortcuts::{self, ShortcutMode, DEFAULT_MIN_HOLD_MS};
+use crate::stt::{DEFAULT_PROVIDER, PROVIDER_IDS};
+
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Where the device choice lived before `settings.json` existed.
+const LEGACY_AUDIO_FILE: &str = "audio.json";
+
+/// Layout version written to the file. Bump it and add a step to `migrate` whenever
+/// a field is renamed or reinterpreted.
+pub const SETTINGS_VERSION: u32 = 1;
+
+const MAX_SILENCE_TIMEOUT: u32 = 60;
+const MAX_MIN_HOLD_MS: u32 = 2_000;
+const MAX_RESTORE_DELAY_MS: u32 = 10_000;
+const MAX_HISTORY_AGE_DAYS: u32 = 3_650;
+const MAX_HISTORY_ENTRIES: u32 = 1_000_000;
+const MAX_ARCHIVE_MB: u32 = 100_000;
+
+/**
+ * Struct: Settings
+ * Everything the Settings panel configures, persisted as `settings.json` in the app
+ * config dir. Field names match the webview's `AppSettings`. Missing fields take
+ * their defaults, so the file can be trimmed or edited by hand.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub version: u32,
+    pub shortcut_enabled: bool,
+    pub shortcut: String,
+    pub shortcut_mode: ShortcutMode,
+    pub min_hold_ms: u32,
+    pub auto_copy_paste: bool,
+    /// Put the transcript into the window that had focus when recording started.
+    pub auto_paste: bool,
+    /// Whether Auto Paste pastes or types the transcript.
+    pub insert_mode: InsertMode,
+    /// `insert_mode` overrides keyed by window class (case-insensitive), e.g.
+    /// `{ "xterm": "type" }`.
+    pub app_insert_modes: HashMap<String, InsertMode>,
+    /// After pasting, put back what the clipboard held before (text and images only).
+    pub restore_clipboard: bool,
+    /// How long the pasted transcript stays on the clipboard before it is restored.
+    pub restore_delay_ms: u32,
+    /// Seconds of silence before auto-stop (0 = disabled).
+    pub silence_timeout: u32,
+    /// Capture device name; `None` follows the system default.
+    pub preferred_device: Option<String>,
+    /// Speech-to-text engine, one of `stt::PROVIDER_IDS`.
+    pub provider: String,
+    /// Model for `provider`; `None` uses the provider's default.
+    pub model: Option<String>,
+    /// Language code such as `en-US`; `None` uses the provider's default.
+    pub language: Option<String>,
+    /// Whether finished transcripts are kept at all.
+    pub history_enabled: bool,
+    /// Days a transcript is kept (0 = no limit).
+    pub history_max_age_days: u32,
+    /// Number of transcripts kept, newest first (0 = no limit).
+    pub history_max_entries: u32,
+    /// Whether each session's audio is kept alongside its transcript.
+    pub archive_audio: bool,
+    /// Space all kept recordings may take up together, in MiB.
+    pub archive_max_mb: u32,
+    /// Set once the webview's old `localStorage` settings have been imported.
+    pub imported_local_storage: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            shortcut_enabled: true,
+            shortcut: "Ctrl+Shift+R".into(),
+            shortcut_mode: ShortcutMode::default(),
+            min_hold_ms: DEFAULT_MIN_HOLD_MS,
+            auto_copy_paste: false,
+            auto_paste: false,
+            insert_mode: InsertMode::default(),
+            app_insert_modes: HashMap::new(),
+            restore_clipboard: true,
+            restore_delay_ms: DEFAULT_RESTORE_DELAY_MS,
+            silence_timeout: 2,
+            preferred_device: None,
+            provider: DEFAULT_PROVIDER.into(),
+            model: None,
+            language: None,
+            history_enabled: true,
+            history_max_age_days: 0,
+            history_max_entries: 0,
+            archive_audio: false,
+            archive_max_mb: 500,
+            imported_local_storage: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        shortcuts::parse_accelerator(&self.shortcut)?;
+        if self.silence_timeout > MAX_SILENCE_TIMEOUT {
+            return Err(format!(
+                "silenceTimeout must be at most {MAX_SILENCE_TIMEOUT} seconds"
+            ));
+        }
+        if !PROVIDER_IDS.contains(&self.provider.as_str()) {
+            return Err(format!(
+                "provider must be one of: {}",
+                PROVIDER_IDS.join(", ")
+            ));
+        }
+        if self.model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return Err("model must not be blank; use null for the default".into());
+        }
+        if self
+            .language
+            .as_deref()
+            .is_some_and(|l| l.trim().is_empty())
+        {
+            return Err("language must not be blank; use null for the default".into());
+        }
+        if self.min_hold_ms > MAX_MIN_HOLD_MS {
+            return Err(format!("minHoldMs must be at most {MAX_MIN_HOLD_MS}"));
+        }
+        if self
+            .app_insert_modes
+            .keys()
+            .any(|app| app.trim().is_empty())
+        {
+            return Err("appInsertModes must not have a blank application".into());
+        }
+        if self.restore_delay_ms > MAX_RESTORE_DELAY_MS {
+            return Err(format!(
+                "restoreDelayMs must be at most {MAX_RESTORE_DELAY_MS}"
+            ));
+        }
+        if self.history_max_age_days > MAX_HISTORY_AGE_DAYS {
+            return Err(format!(
+                "historyMaxAgeDays must be at most {MAX_HISTORY_AGE_DAYS}"
+            ));
+        }
+        if !(1..=MAX_ARCHIVE_MB).contains(&self.archive_max_mb) {
+            return Err(format!(
+                "archiveMaxMb must be between 1 and {MAX_ARCHIVE_MB}"
+            ));
+        }
+        if self.history_max_entries > MAX_HISTORY_ENTRIES {
+            return Err(format!(
+                "historyMaxEntries must be at most {MAX_HISTORY_ENTRIES}"
+            ));
+        }
+        Ok(())
+    }
+
+    /**
+     * Function: merged
+     * Returns these settings with the fields of `patch` (a partial, camelCase object)
+     * applied and validated. Bookkeeping fields cannot be patched.
+     */
+    pub fn merged(&self, patch: Map<String, Value>) -> Result<Settings, String> {
+        let Value::Object(mut fields) = serde_json::to_value(self).map_err(|e| e.to_string())?
+        else {
+            unreachable!("settings serialize to an object");
+        };
+        for (key, value) in patch {
+            if key == "version" || key == "importedLocalStorage" {
+                continue;
+            }
+            fields.insert(key, value);
+        }
+
+        let settings: Settings = serde_json::from_value(Value::Object(fields))
+            .map_err(|e| format!("Invalid settings: {e}"))?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// The one-time import of the webview's `localStorage` settings; a no-op once an
+    /// import has happened.
+    fn with_local_storage(&self, values: Map<String, Value>) -> Settings {
+        if self.imported_local_storage {
+            return self.clone();
+        }
+        Settings {
+            imported_local_storage: true,
+            ..self.merged_leniently(values)
+        }
+    }
+
+    /// Like `merged`, but one field at a time, skipping (and logging) fields that are
+    /// rejected instead of failing the whole patch.
+    fn merged_leniently(&self, patch: Map<String, Value>) -> Settings {
+        patch
+            .into_iter()
+            .fold(self.clone(), |settings, (key, value)| {
+                let field = Map::from_iter([(key.clone(), value)]);
+                settings.merged(field).unwrap_or_else(|e| {
+                    eprintln!("Ignoring setting {key}: {e}");
+                    settings
+                })
+            })
+    }
+}
+
+/**
+ * Struct: SettingsState
+ * Responsibility: In-memory copy of `settings.json`; every change goes through `update`,
+ * which validates, writes the file and announces the result.
+ */
+#[derive(Default)]
+pub struct SettingsState {
+    settings: Mutex<Settings>,
+}
+
+impl SettingsState {
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+}
+
+fn config_path(app: &AppHandle, file: &str) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(file))
+}
+
+fn save(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = config_path(app, SETTINGS_FILE)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    write_atomically(&path, &json).map_err(|e| e.to_string())
+}
+
+/// Writes `contents` next to `path` and renames it into place, so a crash or a full
+/// disk mid-write leaves the previous file intact rather than a truncated one.
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+/// Device choice from the pre-settings `audio.json`, if there is one.
+fn legacy_preferred_device(app: &AppHandle) -> Option<String> {
+    let contents = fs::read_to_string(config_path(app, LEGACY_AUDIO_FILE).ok()?).ok()?;
+    serde_json::from_str::<Value>(&contents)
+        .ok()?
+        .get("preferredDevice")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/**
+ * Function: migrate
+ * Upgrades a settings object of any earlier layout to `SETTINGS_VERSION`, one version
+ * at a time. A missing version means no settings file existed yet; `legacy_device` is
+ * the device choice from `audio.json`, which version 1 took over.
+ */
+fn migrate(mut fields: Map<String, Value>, legacy_device: Option<String>) -> Map<String, Value> {
+    let version = fields.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+    if version < 1 {
+        if let Some(device) = legacy_device {
+            fields
+                .entry("preferredDevice")
+                .or_insert(Value::String(device));
+        }
+    }
+
+    fields.insert("version".into(), SETTINGS_VERSION.into());
+    fields
+}
+
+/**
+ * Function: load
+ * Reads and migrates `settings.json`, creating it on first run. Invalid values fall
+ * back to their defaults; a file that cannot be parsed at all is left alone for the
+ * user to fix, and defaults are used meanwhile.
+ */
+fn load(app: &AppHandle) -> Settings {
+    let path = match config_path(app, SETTINGS_FILE) {
+        Ok(path) => path,
+        Err(_) => return Settings::default(),
+    };
+
+    let stored = match fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str::<Map<String, Value>>(&contents) {
+            Ok(fields) => Some(fields),
+            Err(e) => {
+                eprintln!("Ignoring unreadable {}: {e}", path.display());
+                return Settings::default();
+            }
+        },
+        Err(_) => None,
+    };
+    let needs_save = stored
+        .as_ref()
+        .and_then(|f| f.get("version")?.as_u64())
+        .is_none_or(|version| version < SETTINGS_VERSION as u64);
+
+    let legacy_device = needs_save.then(|| legacy_preferred_device(app)).flatten();
+    let fields = migrate(stored.unwrap_or_default(), legacy_device);
+    let imported_local_storage = fields
+        .get("importedLocalStorage")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let settings = Settings {
+        imported_local_storage,
+        ..Settings::default().merged_leniently(fields)
+    };
+
+    if needs_save && save(app, &settings).is_ok() {
+        if let Ok(legacy) = config_path(app, LEGACY_AUDIO_FILE) {
+            let _ = fs::remove_file(legacy);
+        }
+    }
+    settings
+}
+
+/// Pushes settings the backend acts on to their owners.
+fn apply(app: &AppHandle, previous: Option<&Settings>, settings: &Settings) {
+    if previous.map(|p| &p.preferred_device) != Some(&settings.preferred_device) {
+        app.state::<AudioState>()
+            .set_preferred_device(settings.preferred_device.clone());
+    }
+
+    let shortcut_changed = previous.is_none_or(|p| {
+        (
+            &p.shortcut,
+            p.shortcut_enabled,
+            p.shortcut_mode,
+            p.min_hold_ms,
+        ) != (
+            &settings.shortcut,
+            settings.shortcut_enabled,
+            settings.shortcut_mode,
+            settings.min_hold_ms,
+        )
+    });
+    if shortcut_changed {
+        if let Err(e) = shortcuts::register(
+            app,
+            settings.shortcut.clone(),
+            settings.shortcut_enabled,
+            settings.shortcut_mode,
+            settings.min_hold_ms,
+        ) {
+            eprintln!("{e}");
+        }
+    }
+
+    // At startup the history applies the policy itself, once its database is open.
+    if previous.is_some_and(|p| RetentionPolicy::from(p) != RetentionPolicy::from(settings)) {
+        history::enforce_retention(app);
+    }
+}
+
+/**
+ * Function: update
+ * Applies `change` to the current settings, validates and persists the result, and
+ * emits `settings-changed`. Nothing changes if validation or the write fails.
+ */
+pub fn update(
+    app: &AppHandle,
+    change: impl FnOnce(&Settings) -> Result<Settings, String>,
+) -> Result<Settings, String> {
+    let state = app.state::<SettingsState>();
+    let (previous, settings) = {
+        let mut current = state.settings.lock().unwrap();
+        let next = change(&current)?;
+        next.validate()?;
+        if next == *current {
+            return Ok(next);
+        }
+        save(app, &next)?;
+        (std::mem::replace(&mut *current, next.clone()), next)
+    };
+
+    apply(app, Some(&previous), &settings);
+    let _ = app.emit(SETTINGS_CHANGED_EVENT, &settings);
+    Ok(settings)
+}
+
+/**
+ * Function: setup
+ * Responsibility: Loads settings before anything else starts, so the device choice and
+ * the global shortcut are in place before the webview has loaded.
+ * Called once from the Tauri `setup` hook.
+ */
+pub fn setup(app: &AppHandle) {
+    let settings = load(app);
+    apply(app, None, &settings);
+    *app.state::<SettingsState>().settings.lock().unwrap() = settings;
+}
+
+/**
+ * Command: get_settings
+ * Responsibility: Current settings, for the webview to render.
+ */
+#[tauri::command]
+pub fn get_settings(state: State<'_, SettingsState>) -> Settings {
+    state.get()
+}
+
+/**
+ * Command: update_settings
+ * Responsibility: Applies a partial update (`{ "silenceTimeout": 3 }`) and returns the
+ * resulting settings, or why they were rejected.
+ */
+#[tauri::command]
+pub fn update_settings(app: AppHandle, patch: Map<String, Value>) -> Result<Settings, String> {
+    update(&app, |current| current.merged(patch))
+}
+
+/**
+ * Command: reset_settings
+ * Responsibility: Restores defaults, keeping the device choice.
+ */
+#[tauri::command]
+pub fn reset_settings(app: AppHandle) -> Result<Settings, String> {
+    update(&app, |current| {
+        Ok(Settings {
+            preferred_device: current.preferred_device.clone(),
+            imported_local_storage: current.imported_local_storage,
+            ..Settings::default()
+        })
+    })
+}
+
+/**
+ * Command: import_legacy_settings
+ * Responsibility: One-time import of the settings the webview used to keep in
+ * `localStorage`. Ignored once an import has happened; values that fail validation
+ * are dropped and the defaults kept.
+ */
+#[tauri::command]
+pub fn import_legacy_settings(
+    app: AppHandle,
+    values: Map<String, Value>,
+) -> Result<Settings, String> {
+    update(&app, |current| Ok(current.with_local_storage(values)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(fields) => fields,
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn first_run_takes_over_the_legacy_device() {
+        let fields = migrate(Map::new(), Some("USB Mic".into()));
+        assert_eq!(fields["version"], json!(SETTINGS_VERSION));
+        assert_eq!(fields["preferredDevice"], json!("USB Mic"));
+
+        let settings = Settings::default().merged_leniently(fields);
+        assert_eq!(settings.preferred_device.as_deref(), Some("USB Mic"));
+    }
+
+    #[test]
+    fn unversioned_file_keeps_its_own_device_choice() {
+        let stored = object(json!({ "preferredDevice": "Headset", "silenceTimeout": 5 }));
+        let fields = migrate(stored, Some("USB Mic".into()));
+        assert_eq!(fields["version"], json!(SETTINGS_VERSION));
+        assert_eq!(fields["preferredDevice"], json!("Headset"));
+        assert_eq!(fields["silenceTimeout"], json!(5));
+    }
+
+    #[test]
+    fn current_file_ignores_the_legacy_device() {
+        let stored = object(json!({ "version": SETTINGS_VERSION }));
+        let fields = migrate(stored, Some("USB Mic".into()));
+        assert!(!fields.contains_key("preferredDevice"));
+    }
+
+    #[test]
+    fn local_storage_import_keeps_valid_values_and_drops_the_rest() {
+        let values = object(json!({
+            "silenceTimeout": 4,
+            "shortcut": "Ctrl+Nonsense+",
+            "provider": "no-such-provider",
+            "autoPaste": true,
+            "importedLocalStorage": false,
+        }));
+        let imported = Settings::default().with_local_storage(values);
+
+        assert!(imported.imported_local_storage);
+        assert_eq!(imported.silence_timeout, 4);
+        assert!(imported.auto_paste);
+        assert_eq!(imported.shortcut, Settings::default().shortcut);
+        assert_eq!(imported.provider, DEFAULT_PROVIDER);
+    }
+
+    #[test]
+    fn local_storage_is_imported_only_once() {
+        let first = Settings::default().with_local_storage(object(json!({ "silenceTimeout": 4 })));
+        let second = first.with_local_storage(object(json!({ "silenceTimeout": 9 })));
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    fn invalid_updates_are_rejected() {
+        let settings = Settings::default();
+        for patch in [
+            json!({ "silenceTimeout": MAX_SILENCE_TIMEOUT + 1 }),
+            json!({ "silenceTimeout": "two" }),
+            json!({ "shortcut": "" }),
+            json!({ "provider": "no-such-provider" }),
+            json!({ "model": "  " }),
+            json!({ "minHoldMs": MAX_MIN_HOLD_MS + 1 }),
+            json!({ "appInsertModes": { " ": "type" } }),
+            json!({ "archiveMaxMb": 0 }),
+        ] {
+            assert!(
+                settings.merged(object(patch.clone())).is_err(),
+                "accepted {patch}"
+            );
+        }
+    }
+
+    #[test]
+    fn updates_cannot_touch_bookkeeping_fields() {
+        let merged = Settings::default()
+            .merged(object(json!({
+                "version": 0,
+                "importedLocalStorage": true,
+                "silenceTimeout": 3,
+            })))
+            .unwrap();
+        assert_eq!(merged.version, SETTINGS_VERSION);
+        assert!(!merged.imported_local_storage);
+        assert_eq!(merged.silence_timeout, 3);
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_file_and_leaves_no_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE);
+        fs::write(&path, "old").unwrap();
+
+        write_atomically(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,258 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Second press of a double tap must follow the first within this window.
+const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(400);
+
+pub const DEFAULT_MIN_HOLD_MS: u32 = 200;
+
+/**
+ * Enum: ShortcutMode
+ * How presses of the recording shortcut map onto recording.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShortcutMode {
+    /// Every press starts or stops recording.
+    #[default]
+    Toggle,
+    /// Push-to-talk: records while held, stops on release.
+    Hold,
+    /// Hands-free: a double tap starts a locked recording, the next press stops it.
+    DoubleTap,
+}
+
+/**
+ * Enum: ShortcutAction
+ * What the shortcut asks the recorder to do.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    Toggle,
+    Start,
+    Stop,
+}
+
+/**
+ * Enum: Reaction
+ * Result of a press: act now, or act only if the key is still down after `delay`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reaction {
+    None,
+    Act(ShortcutAction),
+    ActAfterHold { press: u64, delay: Duration },
+}
+
+/**
+ * Struct: Gesture
+ * Responsibility: Turns raw presses and releases of one shortcut into recording actions
+ * for its mode. Pure apart from the timestamps passed in, so timing rules stay in one place.
+ */
+#[derive(Debug, Clone)]
+pub struct Gesture {
+    mode: ShortcutMode,
+    /// Hold mode: presses shorter than this are ignored, so taps never start a session.
+    min_hold: Duration,
+    pressed: bool,
+    /// Bumped on every press, so a pending hold can tell whether its press is still down.
+    presses: u64,
+    /// Hold mode: the current press started a recording.
+    holding: bool,
+    /// Double-tap mode: time of a first tap waiting for its second.
+    last_tap: Option<Instant>,
+    /// Double-tap mode: a hands-free recording is running.
+    locked: bool,
+}
+
+impl Gesture {
+    pub fn new(mode: ShortcutMode, min_hold_ms: u32) -> Self {
+        Self {
+            mode,
+            min_hold: Duration::from_millis(min_hold_ms as u64),
+            pressed: false,
+            presses: 0,
+            holding: false,
+            last_tap: None,
+            locked: false,
+        }
+    }
+
+    pub fn mode(&self) -> ShortcutMode {
+        self.mode
+    }
+
+    pub fn min_hold_ms(&self) -> u32 {
+        self.min_hold.as_millis() as u32
+    }
+
+    pub fn press(&mut self, now: Instant) -> Reaction {
+        // Some platforms repeat the press while the key is held.
+        if self.pressed {
+            return Reaction::None;
+        }
+        self.pressed = true;
+        self.presses += 1;
+
+        match self.mode {
+            ShortcutMode::Toggle => Reaction::Act(ShortcutAction::Toggle),
+            ShortcutMode::Hold if self.min_hold.is_zero() => {
+                self.holding = true;
+                Reaction::Act(ShortcutAction::Start)
+            }
+            ShortcutMode::Hold => Reaction::ActAfterHold {
+                press: self.presses,
+                delay: self.min_hold,
+            },
+            ShortcutMode::DoubleTap if self.locked => {
+                self.locked = false;
+                Reaction::Act(ShortcutAction::Stop)
+            }
+            ShortcutMode::DoubleTap => match self.last_tap.take() {
+                Some(first) if now.duration_since(first) <= DOUBLE_TAP_WINDOW => {
+                    self.locked = true;
+                    Reaction::Act(ShortcutAction::Start)
+                }
+                _ => {
+                    self.last_tap = Some(now);
+                    Reaction::None
+                }
+            },
+        }
+    }
+
+    /**
+     * Function: held
+     * Called once the minimum hold of `press` has elapsed. Starts recording if that
+     * press is still down.
+     */
+    pub fn held(&mut self, press: u64) -> Option<ShortcutAction> {
+        if !self.pressed || self.presses != press || self.holding {
+            return None;
+        }
+        self.holding = true;
+        Some(ShortcutAction::Start)
+    }
+
+    pub fn release(&mut self) -> Option<ShortcutAction> {
+        self.pressed = false;
+        std::mem::take(&mut self.holding).then_some(ShortcutAction::Stop)
+    }
+
+    /**
+     * Function: recording_ended
+     * Called when the recording went back to idle (or failed) by other means: the mic
+     * button, a silence timeout, an error. Without this a hands-free lock would outlive
+     * its recording and swallow the next double tap as a stop.
+     */
+    pub fn recording_ended(&mut self) {
+        self.locked = false;
+        self.holding = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_tap_locks_until_the_next_press() {
+        let mut gesture = Gesture::new(ShortcutMode::DoubleTap, 0);
+        let now = Instant::now();
+        assert_eq!(gesture.press(now), Reaction::None);
+        gesture.release();
+        assert_eq!(
+            gesture.press(now + Duration::from_millis(100)),
+            Reaction::Act(ShortcutAction::Start)
+        );
+        gesture.release();
+        assert_eq!(
+            gesture.press(now + Duration::from_secs(5)),
+            Reaction::Act(ShortcutAction::Stop)
+        );
+    }
+
+    #[test]
+    fn lock_is_dropped_when_the_recording_ends_elsewhere() {
+        let mut gesture = Gesture::new(ShortcutMode::DoubleTap, 0);
+        let now = Instant::now();
+        gesture.press(now);
+        gesture.release();
+        gesture.press(now + Duration::from_millis(100));
+        gesture.release();
+
+        // Stopped by silence: the next double tap starts a new recording.
+        gesture.recording_ended();
+        let later = now + Duration::from_secs(5);
+        assert_eq!(gesture.press(later), Reaction::None);
+        gesture.release();
+        assert_eq!(
+            gesture.press(later + Duration::from_millis(100)),
+            Reaction::Act(ShortcutAction::Start)
+        );
+    }
+
+    #[test]
+    fn hold_released_after_the_recording_ended_does_not_stop() {
+        let mut gesture = Gesture::new(ShortcutMode::Hold, 0);
+        assert_eq!(
+            gesture.press(Instant::now()),
+            Reaction::Act(ShortcutAction::Start)
+        );
+        gesture.recording_ended();
+        assert_eq!(gesture.release(), None);
+    }
+
+    #[test]
+    fn tap_shorter_than_min_hold_never_records() {
+        let mut gesture = Gesture::new(ShortcutMode::Hold, 200);
+        let Reaction::ActAfterHold { press, delay } = gesture.press(Instant::now()) else {
+            panic!("hold with a minimum must wait");
+        };
+        assert_eq!(delay, Duration::from_millis(200));
+
+        // Released before the delay elapsed: neither the release nor the timer acts.
+        assert_eq!(gesture.release(), None);
+        assert_eq!(gesture.held(press), None);
+    }
+
+    #[test]
+    fn held_only_starts_the_press_that_is_still_down() {
+        let mut gesture = Gesture::new(ShortcutMode::Hold, 200);
+        let now = Instant::now();
+        let Reaction::ActAfterHold { press: first, .. } = gesture.press(now) else {
+            panic!("hold with a minimum must wait");
+        };
+        gesture.release();
+        let Reaction::ActAfterHold { press: second, .. } =
+            gesture.press(now + Duration::from_millis(100))
+        else {
+            panic!("hold with a minimum must wait");
+        };
+
+        // The first press's timer fires while the second press is down.
+        assert_eq!(gesture.held(first), None);
+        assert_eq!(gesture.held(second), Some(ShortcutAction::Start));
+        assert_eq!(gesture.held(second), None);
+        assert_eq!(gesture.release(), Some(ShortcutAction::Stop));
+    }
+
+    #[test]
+    fn toggle_acts_on_every_press_but_not_on_repeats() {
+        let mut gesture = Gesture::new(ShortcutMode::Toggle, 200);
+        let now = Instant::now();
+        assert_eq!(gesture.press(now), Reaction::Act(ShortcutAction::Toggle));
+        // Auto-repeat while the key is down.
+        assert_eq!(
+            gesture.press(now + Duration::from_millis(50)),
+            Reaction::None
+        );
+        assert_eq!(gesture.release(), None);
+        assert_eq!(
+            gesture.press(now + Duration::from_secs(3)),
+            Reaction::Act(ShortcutAction::Toggle)
+        );
+        assert_eq!(gesture.release(), None);
+    }
+}
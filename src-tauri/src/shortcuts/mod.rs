@@ -0,0 +1,290 @@
+mod gesture;
+
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::recording::{self, Recorder, RecordingOptions};
+use gesture::{Gesture, Reaction};
+pub use gesture::{ShortcutAction, ShortcutMode, DEFAULT_MIN_HOLD_MS};
+
+pub const GLOBAL_SHORTCUT_EVENT: &str = "global-shortcut";
+
+/**
+ * Struct: ShortcutPayload
+ * Payload of `global-shortcut`, emitted on every press and release.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShortcutPayload {
+    accelerator: String,
+    pressed: bool,
+}
+
+/// The registered shortcut, the settings string it came from, and its gesture state.
+struct Registered {
+    accelerator: String,
+    shortcut: Shortcut,
+    gesture: Gesture,
+}
+
+/**
+ * Struct: ShortcutRegistry
+ * Responsibility: Remembers the registered shortcut and how its presses are interpreted,
+ * so it can be swapped out when settings change.
+ */
+#[derive(Default)]
+pub struct ShortcutRegistry {
+    current: Mutex<Option<Registered>>,
+}
+
+/// Maps keys the webview records (`KeyboardEvent.key`) to names the accelerator parser knows.
+fn normalize_key(key: &str) -> &str {
+    match key {
+        " " => "Space",
+        "Meta" | "OS" => "Super",
+        "!" => "1",
+        "@" => "2",
+        "#" => "3",
+        "$" => "4",
+        "%" => "5",
+        "^" => "6",
+        "&" => "7",
+        "*" => "8",
+        "(" => "9",
+        ")" => "0",
+        "_" => "-",
+        "+" => "=",
+        "{" => "[",
+        "}" => "]",
+        "|" => "\\",
+        ":" => ";",
+        "\"" => "'",
+        "<" => ",",
+        ">" => ".",
+        "?" => "/",
+        "~" => "`",
+        other => other,
+    }
+}
+
+/**
+ * Function: parse_accelerator
+ * Parses the settings format (`"Ctrl+Shift+R"`, as recorded by the Settings panel) into
+ * a global shortcut. Shifted symbols map to their physical key, so `"Ctrl+Shift+!"`
+ * means Ctrl+Shift+1.
+ */
+pub fn parse_accelerator(accelerator: &str) -> Result<Shortcut, String> {
+    let mut tokens: Vec<&str> = accelerator.split('+').collect();
+    // "Ctrl++" splits into ["Ctrl", "", ""]: the key itself was '+'.
+    if tokens.len() >= 2
+        && tokens[tokens.len() - 1].is_empty()
+        && tokens[tokens.len() - 2].is_empty()
+    {
+        tokens.truncate(tokens.len() - 2);
+        tokens.push("+");
+    }
+
+    let normalized: Vec<&str> = tokens
+        .into_iter()
+        .map(|token| match token.trim() {
+            "" if !token.is_empty() => " ",
+            trimmed => trimmed,
+        })
+        .map(normalize_key)
+        .collect();
+    if normalized.iter().any(|token| token.is_empty()) {
+        return Err(format!("Invalid shortcut \"{accelerator}\""));
+    }
+
+    Shortcut::try_from(normalized.join("+").as_str())
+        .map_err(|e| format!("Invalid shortcut \"{accelerator}\": {e}"))
+}
+
+/**
+ * Function: handle
+ * Responsibility: Global shortcut handler installed with the plugin in `run()`.
+ * Forwards presses and releases of the registered shortcut as `global-shortcut` events
+ * and, through its gesture, starts and stops recording.
+ */
+pub fn handle(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    let registry = app.state::<ShortcutRegistry>();
+    let pressed = event.state() == ShortcutState::Pressed;
+    let (accelerator, reaction) = {
+        let mut current = registry.current.lock().unwrap();
+        let Some(registered) = current.as_mut().filter(|r| &r.shortcut == shortcut) else {
+            return;
+        };
+        let reaction = if pressed {
+            registered.gesture.press(Instant::now())
+        } else {
+            registered
+                .gesture
+                .release()
+                .map_or(Reaction::None, Reaction::Act)
+        };
+        (registered.accelerator.clone(), reaction)
+    };
+
+    let _ = app.emit(
+        GLOBAL_SHORTCUT_EVENT,
+        ShortcutPayload {
+            accelerator,
+            pressed,
+        },
+    );
+
+    match reaction {
+        Reaction::None => {}
+        Reaction::Act(action) => perform(app, action),
+        Reaction::ActAfterHold { press, delay } => {
+            let app = app.clone();
+            let shortcut = *shortcut;
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let action = app
+                    .state::<ShortcutRegistry>()
+                    .current
+                    .lock()
+                    .unwrap()
+                    .as_mut()
+                    .filter(|r| r.shortcut == shortcut)
+                    .and_then(|r| r.gesture.held(press));
+                if let Some(action) = action {
+                    perform(&app, action);
+                }
+            });
+        }
+    }
+}
+
+/**
+ * Function: perform
+ * Runs a gesture's action on the recorder, the same way the mic button does. Failures
+ * already show as the recorder's `Error` state, so they are only logged here.
+ */
+fn perform(app: &AppHandle, action: ShortcutAction) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let stop = match action {
+            ShortcutAction::Toggle => app.state::<Recorder>().state().is_active(),
+            ShortcutAction::Start => false,
+            ShortcutAction::Stop => true,
+        };
+        let result = if stop {
+            recording::stop(&app).await.map(drop)
+        } else {
+            recording::start(&app, RecordingOptions::default())
+                .await
+                .map(drop)
+        };
+        if let Err(e) = result {
+            eprintln!("Shortcut {action:?} failed: {e}");
+        }
+    });
+}
+
+/**
+ * Function: recording_ended
+ * Responsibility: Tells the shortcut's gesture that the recording is over, however it
+ * ended. Called by the recorder whenever it returns to idle or fails.
+ */
+pub fn recording_ended(app: &AppHandle) {
+    if let Some(registered) = app
+        .state::<ShortcutRegistry>()
+        .current
+        .lock()
+        .unwrap()
+        .as_mut()
+    {
+        registered.gesture.recording_ended();
+    }
+}
+
+/**
+ * Function: register
+ * Responsibility: Replaces the system-wide recording shortcut and its mode.
+ * With `enabled: false` it only unregisters. If the new shortcut cannot be registered
+ * (invalid, or taken by another application) the previous one is restored and the
+ * error is returned. `min_hold_ms` only applies to `hold`.
+ */
+pub fn register(
+    app: &AppHandle,
+    accelerator: String,
+    enabled: bool,
+    mode: ShortcutMode,
+    min_hold_ms: u32,
+) -> Result<(), String> {
+    let shortcuts = app.global_shortcut();
+    let registry = app.state::<ShortcutRegistry>();
+    let mut current = registry.current.lock().unwrap();
+
+    let shortcut = if enabled {
+        Some(parse_accelerator(&accelerator)?)
+    } else {
+        None
+    };
+    if current.as_ref().map(|r| r.shortcut) == shortcut {
+        if let Some(registered) = current.as_mut() {
+            let gesture = &registered.gesture;
+            if gesture.mode() != mode || gesture.min_hold_ms() != min_hold_ms {
+                registered.gesture = Gesture::new(mode, min_hold_ms);
+            }
+        }
+        return Ok(());
+    }
+
+    let previous = current.take();
+    if let Some(old) = &previous {
+        let _ = shortcuts.unregister(old.shortcut);
+    }
+
+    let Some(shortcut) = shortcut else {
+        return Ok(());
+    };
+    match shortcuts.register(shortcut) {
+        Ok(()) => {
+            *current = Some(Registered {
+                accelerator,
+                shortcut,
+                gesture: Gesture::new(mode, min_hold_ms),
+            });
+            Ok(())
+        }
+        Err(e) => {
+            if let Some(old) = previous {
+                if shortcuts.register(old.shortcut).is_ok() {
+                    *current = Some(old);
+                }
+            }
+            Err(format!(
+                "Could not register \"{accelerator}\" ({e}). It may already be used by another application."
+            ))
+        }
+    }
+}
+
+/**
+ * Command: set_global_shortcut
+ * Responsibility: Registers the shortcut from the Settings panel (see `register`); the
+ * error is shown there. Also used to suspend it while a new one is being recorded.
+ */
+#[tauri::command]
+pub fn set_global_shortcut(
+    app: AppHandle,
+    accelerator: String,
+    enabled: bool,
+    mode: Option<ShortcutMode>,
+    min_hold_ms: Option<u32>,
+) -> Result<(), String> {
+    register(
+        &app,
+        accelerator,
+        enabled,
+        mode.unwrap_or_default(),
+        min_hold_ms.unwrap_or(DEFAULT_MIN_HOLD_MS),
+    )
+}
@@ -0,0 +1,633 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::{mpsc, Notify};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use url::Url;
+
+use super::provider::{SessionOptions, SttProvider, SttSession};
+use super::{SttEvent, Transcript, Word};
+
+pub const DEFAULT_BASE_URL: &str = "wss://api.deepgram.com/v1/listen";
+pub const DEFAULT_MODEL: &str = "nova-2";
+pub const DEFAULT_LANGUAGE: &str = "en-US";
+
+/// Deepgram closes a stream after ~10 s without audio; stay well under that.
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(4);
+
+/// How long to wait for the `from_finalize` result before sending `CloseStream` anyway.
+const FINALIZE_TIMEOUT: Duration = Duration::from_secs(2);
+
+const KEEPALIVE_MESSAGE: &str = r#"{"type":"KeepAlive"}"#;
+const FINALIZE_MESSAGE: &str = r#"{"type":"Finalize"}"#;
+const CLOSE_STREAM_MESSAGE: &str = r#"{"type":"CloseStream"}"#;
+
+/**
+ * Struct: DeepgramConfig
+ * Connection parameters for the streaming `listen` endpoint.
+ * Audio is sent as mono linear16 at `sample_rate`, unless `encoded` is set; Deepgram
+ * then detects the container and codec itself.
+ */
+#[derive(Debug, Clone)]
+pub struct DeepgramConfig {
+    pub api_key: String,
+    pub base_url: String,
+    pub model: String,
+    pub language: String,
+    pub sample_rate: u32,
+    pub encoded: bool,
+    pub smart_format: bool,
+    pub interim_results: bool,
+    /// Send `KeepAlive` after this long without outgoing audio. `None` disables it.
+    pub keepalive_interval: Option<Duration>,
+}
+
+impl DeepgramConfig {
+    pub fn new(api_key: impl Into<String>, sample_rate: u32) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.into(),
+            model: DEFAULT_MODEL.into(),
+            language: DEFAULT_LANGUAGE.into(),
+            sample_rate,
+            encoded: false,
+            smart_format: true,
+            interim_results: true,
+            keepalive_interval: Some(DEFAULT_KEEPALIVE_INTERVAL),
+        }
+    }
+
+    /// `base_url` with the query for this session. Model and language are user input,
+    /// so they are percent-encoded rather than pasted in.
+    pub fn listen_url(&self) -> Result<Url, String> {
+        let mut url = Url::parse(&self.base_url)
+            .map_err(|e| format!("Invalid Deepgram URL {}: {e}", self.base_url))?;
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("model", &self.model)
+                .append_pair("language", &self.language);
+            if !self.encoded {
+                query
+                    .append_pair("encoding", "linear16")
+                    .append_pair("sample_rate", &self.sample_rate.to_string())
+                    .append_pair("channels", "1");
+            }
+            query
+                .append_pair("smart_format", &self.smart_format.to_string())
+                .append_pair("interim_results", &self.interim_results.to_string());
+        }
+        Ok(url)
+    }
+}
+
+/**
+ * Enum: ServerMessage
+ * The subset of Deepgram's streaming responses we act on. Everything else
+ * (Metadata, SpeechStarted, future message types) is ignored.
+ */
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum ServerMessage {
+    Results(ResultsMessage),
+    UtteranceEnd {},
+    /// Sent before Deepgram closes the connection over a problem with the stream,
+    /// e.g. audio it cannot decode.
+    Error {
+        #[serde(default)]
+        description: String,
+        /// Deepgram's error code, such as `DATA-0000`.
+        #[serde(default)]
+        message: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultsMessage {
+    #[serde(default)]
+    start: f64,
+    #[serde(default)]
+    duration: f64,
+    #[serde(default)]
+    is_final: bool,
+    #[serde(default)]
+    speech_final: bool,
+    /// Set on the result that answers a `Finalize` request.
+    #[serde(default)]
+    from_finalize: bool,
+    channel: ResultsChannel,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultsChannel {
+    alternatives: Vec<Alternative>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Alternative {
+    transcript: String,
+    #[serde(default)]
+    confidence: f64,
+    #[serde(default)]
+    words: Vec<Word>,
+}
+
+/**
+ * Function: parse_message
+ * Converts a raw text frame into an `SttEvent`. Returns `Ok(None)` for messages we ignore.
+ */
+pub fn parse_message(text: &str) -> Result<Option<SttEvent>, serde_json::Error> {
+    decode_message(text).map(|(event, _)| event)
+}
+
+/// Like `parse_message`, also reporting whether this answers a `Finalize` request.
+fn decode_message(text: &str) -> Result<(Option<SttEvent>, bool), serde_json::Error> {
+    let event = match serde_json::from_str::<ServerMessage>(text)? {
+        ServerMessage::Results(results) => {
+            let from_finalize = results.from_finalize;
+            let Some(best) = results.channel.alternatives.into_iter().next() else {
+                return Ok((None, from_finalize));
+            };
+            let event = SttEvent::Transcript(Transcript {
+                transcript: best.transcript,
+                is_final: results.is_final,
+                speech_final: results.speech_final,
+                confidence: best.confidence,
+                start: results.start,
+                duration: results.duration,
+                words: best.words,
+            });
+            return Ok((Some(event), from_finalize));
+        }
+        ServerMessage::UtteranceEnd {} => SttEvent::UtteranceEnd,
+        ServerMessage::Error {
+            description,
+            message,
+        } => SttEvent::Error(match (description.is_empty(), message.is_empty()) {
+            (false, false) => format!("Deepgram error: {description} ({message})"),
+            (true, false) => format!("Deepgram error: {message}"),
+            (false, true) => format!("Deepgram error: {description}"),
+            (true, true) => "Deepgram reported an error".into(),
+        }),
+        ServerMessage::Other => return Ok((None, false)),
+    };
+    Ok((Some(event), false))
+}
+
+enum Outgoing {
+    Audio(Vec<u8>),
+    /// Flush with `Finalize`, wait for its result, then `CloseStream`.
+    Finish,
+}
+
+/**
+ * Function: write_loop
+ * Responsibility: Sends queued audio, inserts `KeepAlive` while no audio flows, and runs
+ * the Finalize → CloseStream shutdown sequence.
+ */
+async fn write_loop<S>(
+    mut sink: S,
+    mut outgoing: mpsc::UnboundedReceiver<Outgoing>,
+    finalized: Arc<Notify>,
+    keepalive_interval: Option<Duration>,
+) where
+    S: SinkExt<Message> + Unpin,
+{
+    let mut last_sent = Instant::now();
+    let mut ticker = tokio::time::interval(Duration::from_millis(500));
+
+    loop {
+        tokio::select! {
+            message = outgoing.recv() => match message {
+                Some(Outgoing::Audio(bytes)) => {
+                    if sink.send(Message::binary(bytes)).await.is_err() {
+                        return;
+                    }
+                    last_sent = Instant::now();
+                }
+                Some(Outgoing::Finish) => {
+                    if sink.send(Message::text(FINALIZE_MESSAGE)).await.is_err() {
+                        return;
+                    }
+                    let _ = tokio::time::timeout(FINALIZE_TIMEOUT, finalized.notified()).await;
+                    // Deepgram closes the socket itself once the remaining results are sent.
+                    let _ = sink.send(Message::text(CLOSE_STREAM_MESSAGE)).await;
+                    return;
+                }
+                None => break,
+            },
+            _ = ticker.tick() => {
+                let due = keepalive_interval.is_some_and(|interval| last_sent.elapsed() >= interval);
+                if due {
+                    if sink.send(Message::text(KEEPALIVE_MESSAGE)).await.is_err() {
+                        return;
+                    }
+                    last_sent = Instant::now();
+                }
+            }
+        }
+    }
+
+    let _ = sink.close().await;
+}
+
+/**
+ * Struct: DeepgramSession
+ * Responsibility: Owns one streaming WebSocket.
+ * A writer task drains queued audio/control messages into the socket and a reader task
+ * turns incoming frames into `SttEvent`s. The session ends with exactly one `Closed` event.
+ */
+pub struct DeepgramSession {
+    outgoing: mpsc::UnboundedSender<Outgoing>,
+    events: mpsc::UnboundedReceiver<SttEvent>,
+}
+
+impl DeepgramSession {
+    pub async fn connect(config: &DeepgramConfig) -> Result<Self, String> {
+        let mut request = config
+            .listen_url()?
+            .as_str()
+            .into_client_request()
+            .map_err(|e| e.to_string())?;
+        let auth = HeaderValue::from_str(&format!("Token {}", config.api_key))
+            .map_err(|_| "API key contains invalid characters".to_string())?;
+        request.headers_mut().insert("Authorization", auth);
+
+        let (socket, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(|e| format!("Failed to connect to Deepgram: {e}"))?;
+        let (sink, mut stream) = socket.split();
+
+        let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let finalized = Arc::new(Notify::new());
+
+        tokio::spawn(write_loop(
+            sink,
+            outgoing_rx,
+            finalized.clone(),
+            config.keepalive_interval,
+        ));
+
+        tokio::spawn(async move {
+            let closed = loop {
+                match stream.next().await {
+                    Some(Ok(Message::Text(text))) => match decode_message(&text) {
+                        Ok((event, from_finalize)) => {
+                            if let Some(event) = event {
+                                let _ = events_tx.send(event);
+                            }
+                            if from_finalize {
+                                finalized.notify_one();
+                            }
+                        }
+                        Err(e) => {
+                            let _ = events_tx
+                                .send(SttEvent::Error(format!("Malformed Deepgram message: {e}")));
+                        }
+                    },
+                    Some(Ok(Message::Close(frame))) => {
+                        break match frame {
+                            Some(frame) => SttEvent::Closed {
+                                code: Some(frame.code.into()),
+                                reason: frame.reason.to_string(),
+                            },
+                            None => SttEvent::Closed {
+                                code: None,
+                                reason: String::new(),
+                            },
+                        };
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        let _ = events_tx.send(SttEvent::Error(e.to_string()));
+                        break SttEvent::Closed {
+                            code: None,
+                            reason: e.to_string(),
+                        };
+                    }
+                    None => {
+                        break SttEvent::Closed {
+                            code: None,
+                            reason: String::new(),
+                        }
+                    }
+                }
+            };
+            let _ = events_tx.send(closed);
+        });
+
+        Ok(Self { outgoing, events })
+    }
+
+    fn send(&self, message: Outgoing) -> Result<(), String> {
+        self.outgoing
+            .send(message)
+            .map_err(|_| "Deepgram connection is closed".to_string())
+    }
+}
+
+#[async_trait]
+impl SttSession for DeepgramSession {
+    /**
+     * Function: push_audio
+     * Queues one frame of PCM as little-endian linear16.
+     */
+    fn push_audio(&mut self, samples: &[i16]) -> Result<(), String> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        self.send(Outgoing::Audio(bytes))
+    }
+
+    /**
+     * Function: push_encoded
+     * Queues part of a file as is, for sessions opened with `encoded` set.
+     */
+    fn push_encoded(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.send(Outgoing::Audio(bytes.to_vec()))
+    }
+
+    async fn next_event(&mut self) -> Option<SttEvent> {
+        self.events.recv().await
+    }
+
+    /**
+     * Function: close
+     * Sends `Finalize` so the last words are flushed, then `CloseStream`.
+     * Pending `Results` keep arriving until the `Closed` event.
+     */
+    fn close(&mut self) -> Result<(), String> {
+        self.send(Outgoing::Finish)
+    }
+}
+
+/**
+ * Struct: DeepgramProvider
+ * `SttProvider` for Deepgram's hosted streaming API.
+ */
+pub struct DeepgramProvider {
+    api_key: String,
+    base_url: String,
+}
+
+impl DeepgramProvider {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.into(),
+        }
+    }
+
+    /// Points the provider at another `listen` endpoint (e.g. the local mock server).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+}
+
+#[async_trait]
+impl SttProvider for DeepgramProvider {
+    fn id(&self) -> &'static str {
+        "deepgram"
+    }
+
+    async fn open(&self, options: &SessionOptions) -> Result<Box<dyn SttSession>, String> {
+        let mut config = DeepgramConfig::new(self.api_key.clone(), options.sample_rate);
+        config.base_url = self.base_url.clone();
+        config.encoded = options.encoded;
+        if let Some(model) = &options.model {
+            config.model = model.clone();
+        }
+        if let Some(language) = &options.language {
+            config.language = language.clone();
+        }
+
+        let session = DeepgramSession::connect(&config).await?;
+        Ok(Box::new(session))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    /// Runs `write_loop` against a sink that forwards every message to the returned
+    /// receiver.
+    fn spawn_writer(
+        keepalive_interval: Option<Duration>,
+    ) -> (
+        mpsc::UnboundedSender<Outgoing>,
+        mpsc::UnboundedReceiver<Message>,
+        Arc<Notify>,
+    ) {
+        let (sent_tx, sent) = mpsc::unbounded_channel();
+        let sink = Box::pin(futures_util::sink::unfold(
+            sent_tx,
+            |tx, message: Message| async move {
+                let _ = tx.send(message);
+                Ok::<_, Infallible>(tx)
+            },
+        ));
+        let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+        let finalized = Arc::new(Notify::new());
+        tokio::spawn(write_loop(
+            sink,
+            outgoing_rx,
+            finalized.clone(),
+            keepalive_interval,
+        ));
+        (outgoing, sent, finalized)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn keepalive_when_idle_then_finalize_then_close() {
+        let (outgoing, mut sent, finalized) = spawn_writer(Some(Duration::from_secs(4)));
+
+        outgoing.send(Outgoing::Audio(vec![1, 2])).unwrap();
+        assert_eq!(sent.recv().await, Some(Message::binary(vec![1, 2])));
+
+        // Nothing else is sent until the connection has been idle for the interval.
+        let idle_since = Instant::now();
+        assert_eq!(sent.recv().await, Some(Message::text(KEEPALIVE_MESSAGE)));
+        let idle = idle_since.elapsed();
+        assert!(Duration::from_secs(4) <= idle && idle <= Duration::from_millis(4_500));
+
+        outgoing.send(Outgoing::Finish).unwrap();
+        assert_eq!(sent.recv().await, Some(Message::text(FINALIZE_MESSAGE)));
+
+        // CloseStream waits for the finalize result; no KeepAlive goes out meanwhile.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(sent.try_recv().is_err());
+        finalized.notify_one();
+        assert_eq!(sent.recv().await, Some(Message::text(CLOSE_STREAM_MESSAGE)));
+        assert_eq!(sent.recv().await, None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn close_stream_follows_finalize_timeout() {
+        let (outgoing, mut sent, _finalized) = spawn_writer(None);
+
+        outgoing.send(Outgoing::Finish).unwrap();
+        assert_eq!(sent.recv().await, Some(Message::text(FINALIZE_MESSAGE)));
+
+        let waiting_since = Instant::now();
+        assert_eq!(sent.recv().await, Some(Message::text(CLOSE_STREAM_MESSAGE)));
+        assert!(waiting_since.elapsed() >= FINALIZE_TIMEOUT);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn no_keepalive_while_audio_flows() {
+        let (outgoing, mut sent, _finalized) = spawn_writer(Some(Duration::from_secs(4)));
+
+        for _ in 0..20 {
+            outgoing.send(Outgoing::Audio(vec![0; 4])).unwrap();
+            assert_eq!(sent.recv().await, Some(Message::binary(vec![0; 4])));
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        drop(outgoing);
+        assert_eq!(sent.recv().await, None);
+    }
+
+    /// A final result as Deepgram sends it, trimmed to one word-level alternative.
+    const FINAL_RESULTS: &str = r#"{
+        "type": "Results",
+        "channel_index": [0, 1],
+        "duration": 1.48,
+        "start": 2.02,
+        "is_final": true,
+        "speech_final": true,
+        "from_finalize": false,
+        "channel": {
+            "alternatives": [{
+                "transcript": "Hello world.",
+                "confidence": 0.98,
+                "words": [
+                    { "word": "hello", "start": 2.1, "end": 2.5, "confidence": 0.99, "punctuated_word": "Hello" },
+                    { "word": "world", "start": 2.6, "end": 3.1, "confidence": 0.97, "punctuated_word": "world." }
+                ]
+            }]
+        },
+        "metadata": { "request_id": "b3b1c2e4", "model_info": { "name": "general-nova-2" } }
+    }"#;
+
+    #[test]
+    fn final_results_carry_timing_and_words() {
+        let Some(SttEvent::Transcript(transcript)) = parse_message(FINAL_RESULTS).unwrap() else {
+            panic!("expected a transcript");
+        };
+        assert_eq!(transcript.transcript, "Hello world.");
+        assert!(transcript.is_final && transcript.speech_final);
+        assert_eq!((transcript.start, transcript.duration), (2.02, 1.48));
+        assert_eq!(transcript.confidence, 0.98);
+        assert_eq!(
+            transcript.words,
+            [
+                Word {
+                    word: "hello".into(),
+                    start: 2.1,
+                    end: 2.5,
+                    confidence: 0.99,
+                    punctuated_word: Some("Hello".into()),
+                },
+                Word {
+                    word: "world".into(),
+                    start: 2.6,
+                    end: 3.1,
+                    confidence: 0.97,
+                    punctuated_word: Some("world.".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn interim_results_are_not_final() {
+        let interim = r#"{
+            "type": "Results", "start": 0.0, "duration": 0.8, "is_final": false,
+            "speech_final": false,
+            "channel": { "alternatives": [{ "transcript": "hello wor", "confidence": 0.7, "words": [] }] }
+        }"#;
+        let Some(SttEvent::Transcript(transcript)) = parse_message(interim).unwrap() else {
+            panic!("expected a transcript");
+        };
+        assert_eq!(transcript.transcript, "hello wor");
+        assert!(!transcript.is_final && !transcript.speech_final);
+        assert_eq!((transcript.start, transcript.duration), (0.0, 0.8));
+        assert!(transcript.words.is_empty());
+    }
+
+    #[test]
+    fn finalize_answers_and_ignored_messages() {
+        let answer = FINAL_RESULTS.replace(r#""from_finalize": false"#, r#""from_finalize": true"#);
+        let (event, from_finalize) = decode_message(&answer).unwrap();
+        assert!(from_finalize && event.is_some());
+
+        // A finalize answer without alternatives still counts, but carries no transcript.
+        let empty = r#"{"type":"Results","from_finalize":true,"channel":{"alternatives":[]}}"#;
+        assert_eq!(decode_message(empty).unwrap(), (None, true));
+
+        let metadata = r#"{"type":"Metadata","request_id":"b3b1c2e4","duration":3.5}"#;
+        assert_eq!(parse_message(metadata).unwrap(), None);
+        assert!(parse_message(r#"{"type":"Results"}"#).is_err());
+    }
+
+    #[test]
+    fn error_message_becomes_an_error_event() {
+        let event = parse_message(
+            r#"{"type":"Error","description":"Failed to process audio","message":"DATA-0000","variant":"error"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            event,
+            Some(SttEvent::Error(
+                "Deepgram error: Failed to process audio (DATA-0000)".into()
+            ))
+        );
+        assert_eq!(
+            parse_message(r#"{"type":"Error"}"#).unwrap(),
+            Some(SttEvent::Error("Deepgram reported an error".into()))
+        );
+    }
+
+    #[test]
+    fn listen_url_describes_raw_pcm() {
+        let config = DeepgramConfig::new("key", 16_000);
+        assert_eq!(
+            config.listen_url().unwrap().as_str(),
+            "wss://api.deepgram.com/v1/listen?model=nova-2&language=en-US\
+             &encoding=linear16&sample_rate=16000&channels=1\
+             &smart_format=true&interim_results=true"
+        );
+    }
+
+    #[test]
+    fn listen_url_encodes_user_input() {
+        let mut config = DeepgramConfig::new("key", 16_000);
+        config.encoded = true;
+        config.model = "nova 2&redact=true".into();
+        config.language = "en#US".into();
+
+        let url = config.listen_url().unwrap();
+        assert!(!url.as_str().contains("encoding="));
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(pairs[0], ("model".into(), "nova 2&redact=true".into()));
+        assert_eq!(pairs[1], ("language".into(), "en#US".into()));
+        assert!(pairs.iter().all(|(key, _)| key != "redact"));
+    }
+
+    #[test]
+    fn listen_url_rejects_bad_base_url() {
+        let mut config = DeepgramConfig::new("key", 16_000);
+        config.base_url = "not a url".into();
+        assert!(config.listen_url().is_err());
+    }
+}
@@ -0,0 +1,671 @@
+pub mod deepgram;
+pub mod provider;
+pub mod resilient;
+#[cfg(feature = "whisper")]
+pub mod whisper;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::archive::{self, ArchiveRecording};
+use crate::audio::vad::SilenceGate;
+use crate::audio::{AudioFrame, AudioState, DEFAULT_SAMPLE_RATE};
+use crate::settings::{Settings, SettingsState};
+use crate::{history, secrets};
+use deepgram::DeepgramProvider;
+use provider::{SessionOptions, SttProvider, SttSession};
+use resilient::{ConnectionState, IdlePolicy, ReconnectPolicy, ResilientSession};
+
+pub const TRANSCRIPT_EVENT: &str = "transcript";
+pub const UTTERANCE_END_EVENT: &str = "utterance-end";
+pub const TRANSCRIPTION_ERROR_EVENT: &str = "transcription-error";
+pub const TRANSCRIPTION_CLOSED_EVENT: &str = "transcription-closed";
+pub const CONNECTION_STATE_EVENT: &str = "connection-state";
+
+/// Overrides the streaming endpoint, e.g. `ws://127.0.0.1:8765/v1/listen` for the mock server.
+const BASE_URL_ENV: &str = "DEEPGRAM_BASE_URL";
+
+/// Provider used when none is selected.
+pub const DEFAULT_PROVIDER: &str = "deepgram";
+
+/// Identifiers accepted by `create_provider`, in display order.
+#[cfg(feature = "whisper")]
+pub const PROVIDER_IDS: &[&str] = &["deepgram", "whisper"];
+#[cfg(not(feature = "whisper"))]
+pub const PROVIDER_IDS: &[&str] = &["deepgram"];
+
+/// Sub-directory of the app data dir scanned for local ggml model files.
+const MODELS_DIR: &str = "models";
+
+/// How long to wait for the provider to flush final results after `close()`.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Audio kept ahead of each speech onset when silence is gated.
+const GATE_PREROLL_MS: u64 = 300;
+
+/**
+ * Struct: Word
+ * Word-level timing as returned by the provider.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Word {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+    #[serde(default)]
+    pub confidence: f64,
+    #[serde(default, alias = "punctuated_word")]
+    pub punctuated_word: Option<String>,
+}
+
+/**
+ * Struct: Transcript
+ * One interim or final result. `start`/`duration` are seconds from stream start.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    pub transcript: String,
+    pub is_final: bool,
+    pub speech_final: bool,
+    pub confidence: f64,
+    pub start: f64,
+    pub duration: f64,
+    pub words: Vec<Word>,
+}
+
+/**
+ * Enum: SttEvent
+ * Everything a streaming session can report back to the backend.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum SttEvent {
+    Transcript(Transcript),
+    UtteranceEnd,
+    Error(String),
+    Connection(ConnectionState),
+    Closed { code: Option<u16>, reason: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ClosedPayload {
+    code: Option<u16>,
+    reason: String,
+}
+
+/**
+ * Struct: TranscriptionOptions
+ * Optional overrides passed to `start_transcription`. Provider, model and language
+ * left out are taken from Settings.
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionOptions {
+    /// One of `PROVIDER_IDS`. Defaults to the provider in Settings.
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub sample_rate: Option<u32>,
+    /// Keep the provider connection alive through pauses, or close and reopen lazily.
+    pub idle_policy: Option<IdlePolicy>,
+    /// Only stream frames the VAD marks as speech. Provider timestamps then count
+    /// sent audio only.
+    #[serde(default)]
+    pub gate_silence: bool,
+}
+
+impl TranscriptionOptions {
+    /**
+     * Function: with_defaults
+     * Fills in provider, model and language from `settings` where they were left out.
+     * The model in Settings belongs to the provider chosen there, so it is not used
+     * when another provider is asked for.
+     */
+    pub fn with_defaults(mut self, settings: &Settings) -> Self {
+        let provider = self
+            .provider
+            .get_or_insert_with(|| settings.provider.clone());
+        if *provider == settings.provider && self.model.is_none() {
+            self.model = settings.model.clone();
+        }
+        if self.language.is_none() {
+            self.language = settings.language.clone();
+        }
+        self
+    }
+}
+
+struct RunningSession {
+    stop_tx: oneshot::Sender<()>,
+    task: JoinHandle<SessionSummary>,
+    /// Writes the session's audio to disk, when archiving is enabled.
+    archive: Option<ArchiveRecording>,
+}
+
+impl RunningSession {
+    /// Stops the session and waits for its summary, then stores or drops its audio.
+    async fn finish(self) -> Result<SessionSummary, String> {
+        let _ = self.stop_tx.send(());
+        let summary = self.task.await.map_err(|e| e.to_string());
+        if let Some(archive) = self.archive {
+            match &summary {
+                Ok(summary) => archive.finish(summary).await,
+                Err(_) => archive.discard().await,
+            }
+        }
+        summary
+    }
+}
+
+/**
+ * Struct: TranscriptionState
+ * Responsibility: Holds the single active streaming session.
+ */
+#[derive(Default)]
+pub struct TranscriptionState {
+    session: Mutex<Option<RunningSession>>,
+}
+
+impl TranscriptionState {
+    /**
+     * Function: start
+     * Opens the provider session and starts pumping frames from `audio` into it.
+     * Fails if a session is already running. A previous session whose connection
+     * closed on its own is finished first: its transcript goes to the history and its
+     * audio to the archive.
+     */
+    pub async fn start(
+        &self,
+        app: &AppHandle,
+        audio: &AudioState,
+        options: TranscriptionOptions,
+    ) -> Result<(), String> {
+        let mut running = self.session.lock().await;
+        if running.as_ref().is_some_and(|s| !s.task.is_finished()) {
+            return Err("Transcription already running".into());
+        }
+        if let Some(previous) = running.take() {
+            if let Ok(summary) = previous.finish().await {
+                history::record(app, &summary, None);
+            }
+        }
+
+        let options = options.with_defaults(&app.state::<SettingsState>().get());
+        let provider_id = options.provider.as_deref().unwrap_or(DEFAULT_PROVIDER);
+        let provider = create_provider(app, provider_id)?;
+        let info = SessionInfo {
+            provider: provider_id.to_string(),
+            model: options.model.clone(),
+            language: options.language.clone(),
+            started_at: unix_millis(SystemTime::now()),
+            duration_ms: 0,
+        };
+        let session_options = SessionOptions {
+            model: options.model,
+            language: options.language,
+            sample_rate: options.sample_rate.unwrap_or(DEFAULT_SAMPLE_RATE),
+            encoded: false,
+        };
+        let session = ResilientSession::open(
+            provider,
+            session_options,
+            ReconnectPolicy::default(),
+            options.idle_policy.unwrap_or_default(),
+        )
+        .await?;
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let archive = archive::begin(app, audio.subscribe(), info.started_at);
+        let task = tokio::spawn(run_session(
+            app.clone(),
+            Box::new(session),
+            audio.subscribe(),
+            options
+                .gate_silence
+                .then(|| SilenceGate::new(GATE_PREROLL_MS)),
+            stop_rx,
+            info,
+        ));
+
+        *running = Some(RunningSession {
+            stop_tx,
+            task,
+            archive,
+        });
+        Ok(())
+    }
+
+    /**
+     * Function: stop
+     * Flushes final results and waits for the session to shut down, then stores its
+     * audio if it was being archived. Returns `None` when no session was running.
+     */
+    pub async fn stop(&self) -> Result<Option<SessionSummary>, String> {
+        let running = self.session.lock().await.take();
+        let Some(running) = running else {
+            return Ok(None);
+        };
+        running.finish().await.map(Some)
+    }
+}
+
+fn deepgram(api_key: String) -> DeepgramProvider {
+    let provider = DeepgramProvider::new(api_key);
+    match std::env::var(BASE_URL_ENV).ok().filter(|u| !u.is_empty()) {
+        Some(base_url) => provider.with_base_url(base_url),
+        None => provider,
+    }
+}
+
+/**
+ * Function: create_provider
+ * Responsibility: Maps a provider id from settings to its implementation.
+ * New engines only need an entry here and in `PROVIDER_IDS`.
+ */
+pub fn create_provider(app: &AppHandle, id: &str) -> Result<Box<dyn SttProvider>, String> {
+    match id {
+        "deepgram" => {
+            let (api_key, _) = secrets::api_key(app, id)?
+                .ok_or("No Deepgram API key set. Add one in Settings.")?;
+            Ok(Box::new(deepgram(api_key)))
+        }
+        #[cfg(feature = "whisper")]
+        "whisper" => Ok(Box::new(whisper::WhisperProvider::new(models_dir(app)?))),
+        #[cfg(not(feature = "whisper"))]
+        "whisper" => {
+            let _ = app;
+            Err("This build does not include the local Whisper engine".into())
+        }
+        other => Err(format!("Unknown speech-to-text provider: {other}")),
+    }
+}
+
+/**
+ * Function: check_api_key
+ * Opens and immediately closes a session with `api_key`, so a rejected key surfaces
+ * before the user starts dictating.
+ */
+pub async fn check_api_key(provider: &str, api_key: String) -> Result<(), String> {
+    let provider = match provider {
+        "deepgram" => deepgram(api_key),
+        other => return Err(format!("{other} does not use an API key")),
+    };
+    let mut session = provider
+        .open(&SessionOptions {
+            model: None,
+            language: None,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            encoded: false,
+        })
+        .await?;
+    session.close()
+}
+
+/**
+ * Function: models_dir
+ * Directory where local models are looked up (`<app data>/models`).
+ */
+pub fn models_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(MODELS_DIR))
+}
+
+/**
+ * Helper: list_model_files
+ * ggml model files (`*.bin`) in `dir`, sorted by name.
+ */
+pub fn list_model_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "bin"))
+        .collect();
+    files.sort();
+    files
+}
+
+/**
+ * Struct: LocalModel
+ * A model file the Settings panel can offer for offline transcription.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModel {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+fn emit_event(app: &AppHandle, event: SttEvent) {
+    let _ = match event {
+        SttEvent::Transcript(transcript) => app.emit(TRANSCRIPT_EVENT, transcript),
+        SttEvent::UtteranceEnd => app.emit(UTTERANCE_END_EVENT, ()),
+        SttEvent::Error(message) => app.emit(TRANSCRIPTION_ERROR_EVENT, message),
+        SttEvent::Connection(state) => app.emit(CONNECTION_STATE_EVENT, state),
+        SttEvent::Closed { code, reason } => {
+            app.emit(TRANSCRIPTION_CLOSED_EVENT, ClosedPayload { code, reason })
+        }
+    };
+}
+
+/**
+ * Enum: FinalizeReason
+ * How a session's results were completed.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FinalizeReason {
+    /// The provider confirmed the end of the stream after `close()`.
+    FinalReceived,
+    /// The provider did not confirm within `FLUSH_TIMEOUT`; later results are lost.
+    Timeout,
+    /// The stream ended on its own before it was stopped.
+    ConnectionClosed,
+}
+
+impl FinalizeReason {
+    /// The serialized name, as stored in the history and recording sidecars.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FinalizeReason::FinalReceived => "finalReceived",
+            FinalizeReason::Timeout => "timeout",
+            FinalizeReason::ConnectionClosed => "connectionClosed",
+        }
+    }
+}
+
+/// Milliseconds since the Unix epoch, the timestamp unit of summaries and history.
+pub fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+/**
+ * Struct: SessionInfo
+ * What a session ran with and when. Empty when no session was running.
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub provider: String,
+    /// `None` when the provider's default was used.
+    pub model: Option<String>,
+    pub language: Option<String>,
+    /// Unix time in milliseconds.
+    pub started_at: i64,
+    /// From opening the session until it was stopped or closed.
+    pub duration_ms: i64,
+}
+
+/**
+ * Struct: SessionSummary
+ * Outcome of a finished session: the assembled final transcript and how it ended.
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub transcript: String,
+    /// The final results the transcript was assembled from, with their timing.
+    pub segments: Vec<TranscriptSegment>,
+    pub reason: FinalizeReason,
+    #[serde(flatten)]
+    pub session: SessionInfo,
+}
+
+/**
+ * Struct: TranscriptSegment
+ * One final result. `start`/`end` are seconds from stream start, so they line up with
+ * the session's audio unless silence was gated.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSegment {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/**
+ * Struct: TranscriptAssembler
+ * Joins `is_final` results in arrival order. Interim results are superseded by the
+ * final covering the same audio, so they never reach the assembled text.
+ */
+#[derive(Debug, Default)]
+pub struct TranscriptAssembler {
+    segments: Vec<TranscriptSegment>,
+}
+
+impl TranscriptAssembler {
+    pub fn push(&mut self, event: &SttEvent) {
+        if let SttEvent::Transcript(result) = event {
+            let text = result.transcript.trim();
+            if result.is_final && !text.is_empty() {
+                self.segments.push(TranscriptSegment {
+                    text: text.to_string(),
+                    start: result.start,
+                    end: result.start + result.duration,
+                });
+            }
+        }
+    }
+
+    pub fn text(&self) -> String {
+        let texts: Vec<&str> = self.segments.iter().map(|s| s.text.as_str()).collect();
+        texts.join(" ")
+    }
+
+    pub fn segments(&self) -> &[TranscriptSegment] {
+        &self.segments
+    }
+}
+
+/**
+ * Function: run_session
+ * Responsibility: Pumps captured frames into the socket and provider events out to the UI.
+ * On stop it closes the session and keeps emitting until the provider closes or the flush
+ * times out, then reports the assembled transcript.
+ */
+async fn run_session(
+    app: AppHandle,
+    mut session: Box<dyn SttSession>,
+    mut frames: broadcast::Receiver<AudioFrame>,
+    mut gate: Option<SilenceGate>,
+    mut stop_rx: oneshot::Receiver<()>,
+    info: SessionInfo,
+) -> SessionSummary {
+    let started = Instant::now();
+    let mut assembler = TranscriptAssembler::default();
+    let summary = |assembler: &TranscriptAssembler, reason, recorded: Duration| SessionSummary {
+        transcript: assembler.text(),
+        segments: assembler.segments().to_vec(),
+        reason,
+        session: SessionInfo {
+            duration_ms: recorded.as_millis() as i64,
+            ..info.clone()
+        },
+    };
+
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(frame) => {
+                    let mut result = Ok(());
+                    let mut push = |frame: AudioFrame| {
+                        if result.is_ok() {
+                            result = session.push_audio(&frame.samples);
+                        }
+                    };
+                    match gate.as_mut() {
+                        Some(gate) => gate.admit(frame, &mut push),
+                        None => push(frame),
+                    }
+                    if result.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            event = session.next_event() => match event {
+                Some(closed @ SttEvent::Closed { .. }) => {
+                    emit_event(&app, closed);
+                    return summary(&assembler, FinalizeReason::ConnectionClosed, started.elapsed());
+                }
+                Some(event) => {
+                    assembler.push(&event);
+                    emit_event(&app, event);
+                }
+                None => {
+                    return summary(&assembler, FinalizeReason::ConnectionClosed, started.elapsed())
+                }
+            },
+            _ = &mut stop_rx => break,
+        }
+    }
+
+    let recorded = started.elapsed();
+    let _ = session.close();
+    let flush = async {
+        while let Some(event) = session.next_event().await {
+            let closed = matches!(event, SttEvent::Closed { .. });
+            assembler.push(&event);
+            emit_event(&app, event);
+            if closed {
+                return FinalizeReason::FinalReceived;
+            }
+        }
+        FinalizeReason::ConnectionClosed
+    };
+    let reason = match tokio::time::timeout(FLUSH_TIMEOUT, flush).await {
+        Ok(reason) => reason,
+        Err(_) => {
+            emit_event(
+                &app,
+                SttEvent::Closed {
+                    code: None,
+                    reason: "Timed out waiting for final results".into(),
+                },
+            );
+            FinalizeReason::Timeout
+        }
+    };
+    summary(&assembler, reason, recorded)
+}
+
+/**
+ * Command: start_transcription
+ * Responsibility: Opens a stream with the selected provider and forwards captured audio to it.
+ * Results are delivered through the `transcript` event; dropped connections are retried
+ * and reported through `connection-state`.
+ */
+#[tauri::command]
+pub async fn start_transcription(
+    app: AppHandle,
+    state: State<'_, TranscriptionState>,
+    audio: State<'_, AudioState>,
+    options: Option<TranscriptionOptions>,
+) -> Result<(), String> {
+    state.start(&app, &audio, options.unwrap_or_default()).await
+}
+
+/**
+ * Command: stop_transcription
+ * Responsibility: Flushes the final results and closes the stream.
+ * Resolves with the assembled transcript once the session has fully shut down, and
+ * adds it to the history.
+ */
+#[tauri::command]
+pub async fn stop_transcription(
+    app: AppHandle,
+    state: State<'_, TranscriptionState>,
+) -> Result<Option<SessionSummary>, String> {
+    let summary = state.stop().await?;
+    if let Some(summary) = &summary {
+        history::record(&app, summary, None);
+    }
+    Ok(summary)
+}
+
+/**
+ * Command: list_stt_providers
+ * Responsibility: Returns the provider ids the Settings panel can offer.
+ */
+#[tauri::command]
+pub fn list_stt_providers() -> Vec<&'static str> {
+    PROVIDER_IDS.to_vec()
+}
+
+/**
+ * Command: list_local_models
+ * Responsibility: Lists Whisper model files in the models directory.
+ */
+#[tauri::command]
+pub fn list_local_models(app: AppHandle) -> Result<Vec<LocalModel>, String> {
+    let dir = models_dir(&app)?;
+    Ok(list_model_files(&dir)
+        .into_iter()
+        .map(|path| LocalModel {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            size_bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            path: path.to_string_lossy().into_owned(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings {
+            provider: "deepgram".into(),
+            model: Some("nova-3".into()),
+            language: Some("de".into()),
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn options_default_to_settings() {
+        let options = TranscriptionOptions::default().with_defaults(&settings());
+        assert_eq!(options.provider.as_deref(), Some("deepgram"));
+        assert_eq!(options.model.as_deref(), Some("nova-3"));
+        assert_eq!(options.language.as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn explicit_options_win() {
+        let options = TranscriptionOptions {
+            model: Some("nova-2".into()),
+            language: Some("fr".into()),
+            ..TranscriptionOptions::default()
+        }
+        .with_defaults(&settings());
+        assert_eq!(options.model.as_deref(), Some("nova-2"));
+        assert_eq!(options.language.as_deref(), Some("fr"));
+    }
+
+    #[test]
+    fn settings_model_stays_with_its_provider() {
+        let options = TranscriptionOptions {
+            provider: Some("whisper".into()),
+            ..TranscriptionOptions::default()
+        }
+        .with_defaults(&settings());
+        assert_eq!(options.provider.as_deref(), Some("whisper"));
+        assert_eq!(options.model, None);
+        assert_eq!(options.language.as_deref(), Some("de"));
+    }
+}
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+
+use super::SttEvent;
+
+/**
+ * Struct: SessionOptions
+ * Provider-agnostic parameters for opening a session.
+ * `None` means "use the provider's default".
+ */
+#[derive(Debug, Clone)]
+pub struct SessionOptions {
+    pub model: Option<String>,
+    pub language: Option<String>,
+    /// Rate of the mono i16 PCM that will be pushed.
+    pub sample_rate: u32,
+    /// Audio arrives as an encoded file (e.g. WebM/Opus) through `push_encoded`
+    /// instead of as PCM, and the provider works out the format itself.
+    pub encoded: bool,
+}
+
+/**
+ * Trait: SttProvider
+ * Responsibility: Factory for streaming sessions of one speech-to-text vendor or engine.
+ */
+#[async_trait]
+pub trait SttProvider: Send + Sync {
+    /// Stable identifier used in settings (e.g. `"deepgram"`).
+    fn id(&self) -> &'static str;
+
+    async fn open(&self, options: &SessionOptions) -> Result<Box<dyn SttSession>, String>;
+}
+
+/**
+ * Trait: SttSession
+ * Responsibility: One live transcription stream.
+ * Audio goes in through `push_audio`; interim/final results come out of `next_event`.
+ * After `close`, remaining results are still delivered and the stream ends with
+ * a single `SttEvent::Closed`.
+ */
+#[async_trait]
+pub trait SttSession: Send {
+    fn push_audio(&mut self, samples: &[i16]) -> Result<(), String>;
+
+    /// Pushes part of an encoded file, for sessions opened with `encoded` set. Only
+    /// providers that decode audio themselves support it.
+    fn push_encoded(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let _ = bytes;
+        Err("This provider only accepts PCM audio".into())
+    }
+
+    async fn next_event(&mut self) -> Option<SttEvent>;
+
+    fn close(&mut self) -> Result<(), String>;
+}
@@ -0,0 +1,807 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use super::provider::{SessionOptions, SttProvider, SttSession};
+use super::{SttEvent, Transcript};
+
+/// Audio is replayed to a fresh connection in chunks of this many milliseconds.
+const REPLAY_CHUNK_MS: usize = 100;
+
+/// Tolerance when comparing provider timestamps (seconds).
+const TIME_EPSILON: f64 = 0.01;
+
+/// Words of final text remembered to spot repeats in results without word timings.
+const STITCH_TAIL_WORDS: usize = 32;
+
+/**
+ * Enum: ConnectionState
+ * Payload of the `connection-state` event.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum ConnectionState {
+    Connected,
+    /// Closed on purpose by the idle policy; reopens on the next audio.
+    Idle,
+    #[serde(rename_all = "camelCase")]
+    Reconnecting {
+        attempt: u32,
+        delay_ms: u64,
+        reason: String,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+/**
+ * Enum: IdlePolicy
+ * What to do when no audio has been pushed for a while (e.g. capture paused or the VAD
+ * is holding back silence). Deepgram drops idle sockets, so either keep them alive or
+ * close deliberately and reopen on the next speech.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum IdlePolicy {
+    /// Rely on provider keep-alives and hold the connection open.
+    #[default]
+    KeepAlive,
+    /// Close after `after_ms` without audio and lazily reconnect.
+    #[serde(rename_all = "camelCase")]
+    CloseWhenIdle { after_ms: u64 },
+}
+
+/**
+ * Struct: ReconnectPolicy
+ * Exponential backoff settings for dropped streaming sessions.
+ */
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub max_attempts: u32,
+    /// Upper bound on audio kept for replay (seconds since the last final result).
+    pub max_replay_secs: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(8),
+            max_attempts: 6,
+            max_replay_secs: 60,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+/**
+ * Struct: ReplayBuffer
+ * Audio not yet covered by a final result, addressed in session-global seconds.
+ */
+struct ReplayBuffer {
+    samples: VecDeque<i16>,
+    sample_rate: u32,
+    max_samples: usize,
+    /// Session time of the first buffered sample.
+    start: f64,
+}
+
+impl ReplayBuffer {
+    fn new(sample_rate: u32, max_secs: u32) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            sample_rate,
+            max_samples: sample_rate as usize * max_secs as usize,
+            start: 0.0,
+        }
+    }
+
+    fn push(&mut self, samples: &[i16]) {
+        self.samples.extend(samples);
+        let excess = self.samples.len().saturating_sub(self.max_samples);
+        self.drop_front(excess);
+    }
+
+    /// Forgets audio before session time `until`.
+    fn trim_until(&mut self, until: f64) {
+        let count = ((until - self.start) * self.sample_rate as f64).max(0.0) as usize;
+        self.drop_front(count.min(self.samples.len()));
+    }
+
+    fn drop_front(&mut self, count: usize) {
+        self.samples.drain(..count);
+        self.start += count as f64 / self.sample_rate as f64;
+    }
+
+    fn chunks(&self) -> Vec<Vec<i16>> {
+        let chunk_len = (self.sample_rate as usize * REPLAY_CHUNK_MS / 1000).max(1);
+        let contiguous: Vec<i16> = self.samples.iter().copied().collect();
+        contiguous.chunks(chunk_len).map(<[i16]>::to_vec).collect()
+    }
+}
+
+/**
+ * Struct: Stitcher
+ * Responsibility: Shifts results from connection time to session time and removes
+ * anything already covered by a previous final result.
+ */
+#[derive(Default)]
+struct Stitcher {
+    /// Session time up to which final results have been emitted.
+    committed: f64,
+    /// Last words of the final text, normalized, newest last.
+    tail: VecDeque<String>,
+}
+
+impl Stitcher {
+    /// Returns `None` when nothing new remains.
+    fn stitch(&mut self, mut result: Transcript, offset: f64) -> Option<Transcript> {
+        result.start += offset;
+        for word in &mut result.words {
+            word.start += offset;
+            word.end += offset;
+        }
+
+        let end = result.start + result.duration;
+        if end <= self.committed + TIME_EPSILON && result.duration > 0.0 {
+            return None;
+        }
+
+        if result.start < self.committed - TIME_EPSILON {
+            if result.words.is_empty() {
+                let overlap = (self.committed - result.start) / result.duration;
+                result.transcript = self.drop_repeat(&result.transcript, overlap)?;
+            } else {
+                let committed = self.committed;
+                result.words.retain(|w| w.end > committed + TIME_EPSILON);
+                result.transcript = result
+                    .words
+                    .iter()
+                    .map(|w| w.punctuated_word.as_deref().unwrap_or(&w.word))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+            result.duration = end - self.committed;
+            result.start = self.committed;
+        }
+
+        if result.is_final {
+            self.committed = end;
+            self.tail
+                .extend(result.transcript.split_whitespace().map(normalize));
+            let excess = self.tail.len().saturating_sub(STITCH_TAIL_WORDS);
+            self.tail.drain(..excess);
+        }
+        Some(result)
+    }
+
+    /**
+     * Function: drop_repeat
+     * Without word timings, finds where `text` picks up after the committed text: the
+     * longest run of its first words that repeats the end of the committed text. If
+     * none does, drops the share of words that `overlap` (0..1) of its time was
+     * already covered. Returns `None` when nothing is left.
+     */
+    fn drop_repeat(&self, text: &str, overlap: f64) -> Option<String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let repeated = (1..=words.len().min(self.tail.len()))
+            .rev()
+            .find(|&n| {
+                self.tail
+                    .range(self.tail.len() - n..)
+                    .zip(&words[..n])
+                    .all(|(committed, word)| *committed == normalize(word))
+            })
+            .unwrap_or_else(|| (words.len() as f64 * overlap.clamp(0.0, 1.0)).round() as usize);
+
+        let rest = words[repeated..].join(" ");
+        (!rest.is_empty()).then_some(rest)
+    }
+}
+
+/// Lowercase with punctuation removed, so "Hello," repeats "hello".
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+enum Input {
+    Audio(Vec<i16>),
+    Close,
+}
+
+/**
+ * Struct: ResilientSession
+ * Responsibility: Wraps a provider session and transparently reconnects when it drops.
+ * Audio since the last final result is kept and replayed into the new connection, and
+ * result timestamps are stitched so words are neither lost nor repeated.
+ * Connection changes are reported as `SttEvent::Connection`.
+ */
+pub struct ResilientSession {
+    input: mpsc::UnboundedSender<Input>,
+    events: mpsc::UnboundedReceiver<SttEvent>,
+}
+
+impl ResilientSession {
+    pub async fn open(
+        provider: Box<dyn SttProvider>,
+        options: SessionOptions,
+        policy: ReconnectPolicy,
+        idle: IdlePolicy,
+    ) -> Result<Self, String> {
+        let session = provider.open(&options).await?;
+        let (input, input_rx) = mpsc::unbounded_channel();
+        let (events_tx, events) = mpsc::unbounded_channel();
+
+        let supervisor = Supervisor {
+            replay: ReplayBuffer::new(options.sample_rate, policy.max_replay_secs),
+            provider,
+            options,
+            policy,
+            idle,
+            input: input_rx,
+            events: events_tx,
+            offset: 0.0,
+            stitcher: Stitcher::default(),
+            closing: false,
+        };
+        tokio::spawn(supervisor.run(session));
+
+        Ok(Self { input, events })
+    }
+
+    fn send(&self, input: Input) -> Result<(), String> {
+        self.input
+            .send(input)
+            .map_err(|_| "Transcription session has ended".to_string())
+    }
+}
+
+#[async_trait]
+impl SttSession for ResilientSession {
+    fn push_audio(&mut self, samples: &[i16]) -> Result<(), String> {
+        self.send(Input::Audio(samples.to_vec()))
+    }
+
+    async fn next_event(&mut self) -> Option<SttEvent> {
+        self.events.recv().await
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        self.send(Input::Close)
+    }
+}
+
+/// Where the wrapped connection is in its lifecycle.
+enum Link {
+    Open(Box<dyn SttSession>),
+    /// Closing because of the idle policy. `woken` is set if audio arrived meanwhile.
+    Parking {
+        session: Box<dyn SttSession>,
+        woken: bool,
+    },
+    Parked,
+}
+
+async fn next_event(link: &mut Link) -> Option<SttEvent> {
+    match link {
+        Link::Open(session) | Link::Parking { session, .. } => session.next_event().await,
+        Link::Parked => std::future::pending().await,
+    }
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+struct Supervisor {
+    provider: Box<dyn SttProvider>,
+    options: SessionOptions,
+    policy: ReconnectPolicy,
+    idle: IdlePolicy,
+    input: mpsc::UnboundedReceiver<Input>,
+    events: mpsc::UnboundedSender<SttEvent>,
+    replay: ReplayBuffer,
+    /// Session time at which the current connection's t=0 lies.
+    offset: f64,
+    stitcher: Stitcher,
+    closing: bool,
+}
+
+impl Supervisor {
+    async fn run(mut self, session: Box<dyn SttSession>) {
+        let mut link = Link::Open(session);
+        let mut last_audio = Instant::now();
+        self.emit(SttEvent::Connection(ConnectionState::Connected));
+
+        loop {
+            let idle_deadline = match (&self.idle, &link) {
+                (IdlePolicy::CloseWhenIdle { after_ms }, Link::Open(_)) if !self.closing => {
+                    Some(last_audio + Duration::from_millis(*after_ms))
+                }
+                _ => None,
+            };
+
+            tokio::select! {
+                input = self.input.recv(), if !self.closing => match input {
+                    Some(Input::Audio(samples)) => {
+                        last_audio = Instant::now();
+                        self.replay.push(&samples);
+                        match &mut link {
+                            // A failed push surfaces as `Closed` from the session below.
+                            Link::Open(session) => {
+                                let _ = session.push_audio(&samples);
+                            }
+                            Link::Parking { woken, .. } => *woken = true,
+                            Link::Parked => match self.resume().await {
+                                Some(session) => link = Link::Open(session),
+                                None => return,
+                            },
+                        }
+                    }
+                    Some(Input::Close) | None => {
+                        self.closing = true;
+                        match &mut link {
+                            Link::Open(session) => {
+                                let _ = session.close();
+                            }
+                            Link::Parking { .. } => {}
+                            Link::Parked => {
+                                self.emit(SttEvent::Closed {
+                                    code: None,
+                                    reason: String::new(),
+                                });
+                                return;
+                            }
+                        }
+                    }
+                },
+                _ = sleep_until(idle_deadline) => {
+                    if let Link::Open(mut session) = std::mem::replace(&mut link, Link::Parked) {
+                        let _ = session.close();
+                        link = Link::Parking { session, woken: false };
+                    }
+                },
+                event = next_event(&mut link) => match event {
+                    Some(SttEvent::Transcript(result)) => {
+                        if let Some(result) = self.stitcher.stitch(result, self.offset) {
+                            if result.is_final {
+                                self.replay.trim_until(self.stitcher.committed);
+                            }
+                            self.emit(SttEvent::Transcript(result));
+                        }
+                    }
+                    Some(SttEvent::Closed { .. }) | None if matches!(link, Link::Parking { .. }) => {
+                        let woken = matches!(link, Link::Parking { woken: true, .. });
+                        link = Link::Parked;
+                        if woken {
+                            match self.resume().await {
+                                Some(session) => link = Link::Open(session),
+                                None => return,
+                            }
+                        } else if self.closing {
+                            self.emit(SttEvent::Closed {
+                                code: None,
+                                reason: String::new(),
+                            });
+                            return;
+                        } else {
+                            self.emit(SttEvent::Connection(ConnectionState::Idle));
+                        }
+                    }
+                    Some(closed @ SttEvent::Closed { .. }) if self.closing => {
+                        self.emit(closed);
+                        return;
+                    }
+                    None if self.closing => return,
+                    Some(SttEvent::Closed { code, reason }) => {
+                        let reason = match code {
+                            Some(code) => format!("{reason} ({code})"),
+                            None => reason,
+                        };
+                        match self.reconnect(reason).await {
+                            Some(session) => link = Link::Open(session),
+                            None => return,
+                        }
+                    }
+                    None => match self.reconnect("Session ended".into()).await {
+                        Some(session) => link = Link::Open(session),
+                        None => return,
+                    },
+                    Some(event) => self.emit(event),
+                },
+            }
+        }
+    }
+
+    /**
+     * Function: resume
+     * Reopens after an idle close. Falls back to the backoff loop if the first try fails.
+     */
+    async fn resume(&mut self) -> Option<Box<dyn SttSession>> {
+        match self.provider.open(&self.options).await {
+            Ok(session) => Some(self.attach(session)),
+            Err(reason) => self.reconnect(reason).await,
+        }
+    }
+
+    /**
+     * Function: reconnect
+     * Retries with exponential backoff while continuing to buffer incoming audio.
+     * On success the buffered audio is replayed; on failure a final `Closed` is emitted.
+     */
+    async fn reconnect(&mut self, reason: String) -> Option<Box<dyn SttSession>> {
+        for attempt in 1..=self.policy.max_attempts {
+            let delay = self.policy.delay(attempt);
+            self.emit(SttEvent::Connection(ConnectionState::Reconnecting {
+                attempt,
+                delay_ms: delay.as_millis() as u64,
+                reason: reason.clone(),
+            }));
+
+            let sleep = tokio::time::sleep(delay);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    input = self.input.recv(), if !self.closing => match input {
+                        Some(Input::Audio(samples)) => self.replay.push(&samples),
+                        Some(Input::Close) | None => self.closing = true,
+                    },
+                }
+            }
+
+            if let Ok(session) = self.provider.open(&self.options).await {
+                return Some(self.attach(session));
+            }
+        }
+
+        let failure = format!("Could not reconnect: {reason}");
+        self.emit(SttEvent::Connection(ConnectionState::Failed {
+            reason: failure.clone(),
+        }));
+        self.emit(SttEvent::Closed {
+            code: None,
+            reason: failure,
+        });
+        None
+    }
+
+    /// Replays buffered audio into a fresh connection and makes it current.
+    fn attach(&mut self, mut session: Box<dyn SttSession>) -> Box<dyn SttSession> {
+        self.offset = self.replay.start;
+        for chunk in self.replay.chunks() {
+            let _ = session.push_audio(&chunk);
+        }
+        if self.closing {
+            let _ = session.close();
+        }
+        self.emit(SttEvent::Connection(ConnectionState::Connected));
+        session
+    }
+
+    fn emit(&self, event: SttEvent) {
+        let _ = self.events.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::stt::Word;
+
+    const SAMPLE_RATE: u32 = 16_000;
+
+    /// The test's end of one connection: events to deliver and audio received.
+    struct Connection {
+        events: mpsc::UnboundedSender<SttEvent>,
+        pushed: Arc<Mutex<usize>>,
+    }
+
+    /**
+     * Struct: StubProvider
+     * Accepts the first `accept` connections and refuses the rest. Each session ends
+     * with `Closed` when closed, or when the test sends one through its `Connection`.
+     */
+    struct StubProvider {
+        accept: usize,
+        connections: Arc<Mutex<Vec<Connection>>>,
+    }
+
+    struct StubSession {
+        events: mpsc::UnboundedReceiver<SttEvent>,
+        closer: mpsc::UnboundedSender<SttEvent>,
+        pushed: Arc<Mutex<usize>>,
+    }
+
+    impl StubProvider {
+        fn new(accept: usize) -> Self {
+            Self {
+                accept,
+                connections: Arc::default(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SttProvider for StubProvider {
+        fn id(&self) -> &'static str {
+            "stub"
+        }
+
+        async fn open(&self, _options: &SessionOptions) -> Result<Box<dyn SttSession>, String> {
+            let mut connections = self.connections.lock().unwrap();
+            if connections.len() >= self.accept {
+                return Err("Connection refused".into());
+            }
+            let (events_tx, events) = mpsc::unbounded_channel();
+            let pushed = Arc::new(Mutex::new(0));
+            connections.push(Connection {
+                events: events_tx.clone(),
+                pushed: pushed.clone(),
+            });
+            Ok(Box::new(StubSession {
+                events,
+                closer: events_tx,
+                pushed,
+            }))
+        }
+    }
+
+    #[async_trait]
+    impl SttSession for StubSession {
+        fn push_audio(&mut self, samples: &[i16]) -> Result<(), String> {
+            *self.pushed.lock().unwrap() += samples.len();
+            Ok(())
+        }
+
+        async fn next_event(&mut self) -> Option<SttEvent> {
+            self.events.recv().await
+        }
+
+        fn close(&mut self) -> Result<(), String> {
+            let _ = self.closer.send(SttEvent::Closed {
+                code: None,
+                reason: String::new(),
+            });
+            Ok(())
+        }
+    }
+
+    async fn open(
+        provider: StubProvider,
+        policy: ReconnectPolicy,
+        idle: IdlePolicy,
+    ) -> ResilientSession {
+        let options = SessionOptions {
+            model: None,
+            language: None,
+            sample_rate: SAMPLE_RATE,
+            encoded: false,
+        };
+        ResilientSession::open(Box::new(provider), options, policy, idle)
+            .await
+            .unwrap()
+    }
+
+    fn pushed(connections: &Mutex<Vec<Connection>>) -> Vec<usize> {
+        let connections = connections.lock().unwrap();
+        connections
+            .iter()
+            .map(|c| *c.pushed.lock().unwrap())
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_once_the_backoff_is_exhausted() {
+        let provider = StubProvider::new(1);
+        let connections = provider.connections.clone();
+        let policy = ReconnectPolicy {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            max_attempts: 3,
+            ..ReconnectPolicy::default()
+        };
+        let mut session = open(provider, policy, IdlePolicy::KeepAlive).await;
+        assert_eq!(
+            session.next_event().await,
+            Some(SttEvent::Connection(ConnectionState::Connected))
+        );
+
+        let dropped_at = Instant::now();
+        connections.lock().unwrap()[0]
+            .events
+            .send(SttEvent::Closed {
+                code: Some(1011),
+                reason: "Idle timeout".into(),
+            })
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Some(event) = session.next_event().await {
+            events.push(event);
+        }
+        let reconnecting = |attempt, delay_ms| {
+            SttEvent::Connection(ConnectionState::Reconnecting {
+                attempt,
+                delay_ms,
+                reason: "Idle timeout (1011)".into(),
+            })
+        };
+        let failure = "Could not reconnect: Idle timeout (1011)".to_string();
+        assert_eq!(
+            events,
+            [
+                reconnecting(1, 100),
+                reconnecting(2, 200),
+                reconnecting(3, 300),
+                SttEvent::Connection(ConnectionState::Failed {
+                    reason: failure.clone(),
+                }),
+                SttEvent::Closed {
+                    code: None,
+                    reason: failure,
+                },
+            ]
+        );
+        assert!(dropped_at.elapsed() >= Duration::from_millis(600));
+        assert!(session.push_audio(&[0; 160]).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn close_when_idle_parks_and_resumes_with_the_buffered_audio() {
+        let provider = StubProvider::new(2);
+        let connections = provider.connections.clone();
+        let idle = IdlePolicy::CloseWhenIdle { after_ms: 1_000 };
+        let mut session = open(provider, ReconnectPolicy::default(), idle).await;
+        assert_eq!(
+            session.next_event().await,
+            Some(SttEvent::Connection(ConnectionState::Connected))
+        );
+
+        let chunk = vec![0i16; SAMPLE_RATE as usize / 10];
+        session.push_audio(&chunk).unwrap();
+        let last_audio = Instant::now();
+        assert_eq!(
+            session.next_event().await,
+            Some(SttEvent::Connection(ConnectionState::Idle))
+        );
+        assert!(last_audio.elapsed() >= Duration::from_secs(1));
+        assert_eq!(pushed(&connections), [chunk.len()]);
+
+        // The next audio reopens, replaying what no final result has covered yet.
+        session.push_audio(&chunk).unwrap();
+        assert_eq!(
+            session.next_event().await,
+            Some(SttEvent::Connection(ConnectionState::Connected))
+        );
+        assert_eq!(pushed(&connections), [chunk.len(), 2 * chunk.len()]);
+
+        session.close().unwrap();
+        assert_eq!(
+            session.next_event().await,
+            Some(SttEvent::Closed {
+                code: None,
+                reason: String::new(),
+            })
+        );
+        assert_eq!(session.next_event().await, None);
+    }
+
+    fn result(text: &str, start: f64, duration: f64, is_final: bool) -> Transcript {
+        Transcript {
+            transcript: text.into(),
+            is_final,
+            speech_final: is_final,
+            confidence: 0.9,
+            start,
+            duration,
+            words: Vec::new(),
+        }
+    }
+
+    fn timed(words: &[(&str, f64, f64)], is_final: bool) -> Transcript {
+        let (start, end) = (words[0].1, words[words.len() - 1].2);
+        Transcript {
+            words: words
+                .iter()
+                .map(|&(word, start, end)| Word {
+                    word: word.into(),
+                    start,
+                    end,
+                    confidence: 0.9,
+                    punctuated_word: None,
+                })
+                .collect(),
+            ..result(
+                &words.iter().map(|w| w.0).collect::<Vec<_>>().join(" "),
+                start,
+                end - start,
+                is_final,
+            )
+        }
+    }
+
+    #[test]
+    fn shifts_results_to_session_time() {
+        let mut stitcher = Stitcher::default();
+        let stitched = stitcher
+            .stitch(timed(&[("hello", 0.5, 0.9)], true), 2.0)
+            .unwrap();
+        assert_eq!(stitched.start, 2.5);
+        assert_eq!(stitched.words[0].end, 2.9);
+        assert_eq!(stitcher.committed, 2.9);
+    }
+
+    #[test]
+    fn drops_results_already_covered() {
+        let mut stitcher = Stitcher::default();
+        stitcher.stitch(result("hello there", 0.0, 2.0, true), 0.0);
+        assert!(stitcher
+            .stitch(result("hello", 0.0, 1.5, true), 0.0)
+            .is_none());
+    }
+
+    #[test]
+    fn trims_overlapping_words_by_timing() {
+        let mut stitcher = Stitcher::default();
+        stitcher.stitch(timed(&[("one", 0.0, 0.5), ("two", 0.5, 1.0)], true), 0.0);
+
+        let stitched = stitcher
+            .stitch(timed(&[("two", 0.5, 1.0), ("three", 1.0, 1.5)], true), 0.0)
+            .unwrap();
+        assert_eq!(stitched.transcript, "three");
+        assert_eq!(stitched.start, 1.0);
+        assert_eq!(stitcher.committed, 1.5);
+    }
+
+    #[test]
+    fn trims_repeated_text_without_timing() {
+        let mut stitcher = Stitcher::default();
+        stitcher.stitch(result("Send it to Anna, please.", 0.0, 2.0, true), 0.0);
+
+        // Starts 1.5 s into the committed audio and repeats its last two words.
+        let stitched = stitcher
+            .stitch(result("anna please and copy Ben", 0.5, 3.0, true), 0.0)
+            .unwrap();
+        assert_eq!(stitched.transcript, "and copy Ben");
+        assert_eq!(stitched.start, 2.0);
+        assert_eq!(stitched.duration, 1.5);
+    }
+
+    #[test]
+    fn falls_back_to_time_share_without_timing() {
+        let mut stitcher = Stitcher::default();
+        stitcher.stitch(result("alpha beta", 0.0, 2.0, true), 0.0);
+
+        // Transcribed differently the second time; half its time is already covered.
+        let stitched = stitcher
+            .stitch(result("alfa better gamma delta", 1.0, 2.0, false), 0.0)
+            .unwrap();
+        assert_eq!(stitched.transcript, "gamma delta");
+        // Interim results do not move the committed point.
+        assert_eq!(stitcher.committed, 2.0);
+    }
+}
@@ -0,0 +1,411 @@
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc as async_mpsc;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
+
+use super::provider::{SessionOptions, SttProvider, SttSession};
+use super::{SttEvent, Transcript};
+
+/// whisper.cpp only accepts 16 kHz mono input.
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Re-decode the open window (interim result) after this much new audio.
+const STEP_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize * 2;
+
+/// Finalize the window once it holds this much audio, then start a new one.
+const WINDOW_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize * 10;
+
+/// A full window is cut at the quietest point of its last 3 s, so words are rarely
+/// split between two windows.
+const CUT_SEARCH_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize * 3;
+
+/// Block length (20 ms) over which loudness is compared when looking for that point.
+const CUT_BLOCK_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize / 50;
+
+/// How much of the finalized text each window gets as its prompt, in characters.
+const PROMPT_CHARS: usize = 200;
+
+/// whisper.cpp rejects inputs shorter than one second, so short tails are zero-padded.
+const MIN_DECODE_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize + WHISPER_SAMPLE_RATE as usize / 10;
+
+/// Loaded models are large; keep the last one around between sessions.
+static LOADED_MODEL: Mutex<Option<(PathBuf, Arc<WhisperContext>)>> = Mutex::new(None);
+
+fn load_model(path: &Path) -> Result<Arc<WhisperContext>, String> {
+    let mut loaded = LOADED_MODEL.lock().unwrap();
+    if let Some((loaded_path, ctx)) = loaded.as_ref() {
+        if loaded_path == path {
+            return Ok(ctx.clone());
+        }
+    }
+
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| "Model path is not valid UTF-8".to_string())?;
+    let ctx = WhisperContext::new_with_params(path_str, WhisperContextParameters::default())
+        .map_err(|e| format!("Failed to load Whisper model {}: {e}", path.display()))?;
+    let ctx = Arc::new(ctx);
+
+    *loaded = Some((path.to_path_buf(), ctx.clone()));
+    Ok(ctx)
+}
+
+/**
+ * Struct: WhisperProvider
+ * `SttProvider` backed by a local whisper.cpp model running on the CPU.
+ * `SessionOptions::model` is either a path to a ggml model file or a file name inside
+ * `models_dir`; without it the first model found in `models_dir` is used.
+ */
+pub struct WhisperProvider {
+    models_dir: PathBuf,
+    threads: i32,
+}
+
+impl WhisperProvider {
+    pub fn new(models_dir: PathBuf) -> Self {
+        let threads = thread::available_parallelism()
+            .map(|n| n.get().min(8))
+            .unwrap_or(4);
+        Self {
+            models_dir,
+            threads: threads as i32,
+        }
+    }
+
+    fn resolve_model(&self, model: Option<&str>) -> Result<PathBuf, String> {
+        match model {
+            Some(model) => {
+                let direct = PathBuf::from(model);
+                let path = if direct.is_absolute() {
+                    direct
+                } else {
+                    self.models_dir.join(model)
+                };
+                if path.is_file() {
+                    Ok(path)
+                } else {
+                    Err(format!("Whisper model not found: {}", path.display()))
+                }
+            }
+            None => super::list_model_files(&self.models_dir)
+                .into_iter()
+                .next()
+                .ok_or_else(|| {
+                    format!(
+                        "No Whisper model found in {}. Download a ggml model (e.g. ggml-base.en.bin) there.",
+                        self.models_dir.display()
+                    )
+                }),
+        }
+    }
+}
+
+#[async_trait]
+impl SttProvider for WhisperProvider {
+    fn id(&self) -> &'static str {
+        "whisper"
+    }
+
+    async fn open(&self, options: &SessionOptions) -> Result<Box<dyn SttSession>, String> {
+        if options.encoded {
+            return Err("Whisper only accepts decoded audio; convert the file to WAV".into());
+        }
+        if options.sample_rate != WHISPER_SAMPLE_RATE {
+            return Err(format!(
+                "Whisper requires {WHISPER_SAMPLE_RATE} Hz audio, got {} Hz",
+                options.sample_rate
+            ));
+        }
+
+        let path = self.resolve_model(options.model.as_deref())?;
+        let ctx = tokio::task::spawn_blocking(move || load_model(&path))
+            .await
+            .map_err(|e| e.to_string())??;
+
+        // Whisper takes ISO 639-1 codes ("en"), settings use BCP 47 tags ("en-US").
+        let language = options
+            .language
+            .as_deref()
+            .map(|tag| tag.split('-').next().unwrap_or(tag).to_lowercase());
+
+        Ok(Box::new(WhisperSession::spawn(
+            ctx,
+            language,
+            self.threads,
+        )?))
+    }
+}
+
+enum Command {
+    Audio(Vec<f32>),
+    Close,
+}
+
+/**
+ * Struct: WhisperSession
+ * Responsibility: Runs chunked decoding on a worker thread.
+ * The open window is re-decoded every `STEP_SAMPLES` (interim result) and finalized
+ * when it reaches `WINDOW_SAMPLES` or the session is closed. Full windows end at a
+ * pause, and the text finalized so far is carried into the next window as its prompt.
+ */
+pub struct WhisperSession {
+    commands: mpsc::Sender<Command>,
+    events: async_mpsc::UnboundedReceiver<SttEvent>,
+}
+
+impl WhisperSession {
+    fn spawn(
+        ctx: Arc<WhisperContext>,
+        language: Option<String>,
+        threads: i32,
+    ) -> Result<Self, String> {
+        let state = ctx.create_state().map_err(|e| e.to_string())?;
+        let (commands, commands_rx) = mpsc::channel();
+        let (events_tx, events) = async_mpsc::unbounded_channel();
+
+        thread::Builder::new()
+            .name("whisper-decoder".into())
+            .spawn(move || {
+                let mut decoder = Decoder {
+                    state,
+                    language,
+                    threads,
+                    events: events_tx,
+                    window: Vec::with_capacity(WINDOW_SAMPLES),
+                    window_start: 0.0,
+                    context: String::new(),
+                };
+                decoder.run(commands_rx);
+            })
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self { commands, events })
+    }
+}
+
+#[async_trait]
+impl SttSession for WhisperSession {
+    fn push_audio(&mut self, samples: &[i16]) -> Result<(), String> {
+        let samples = samples.iter().map(|&s| s as f32 / 32768.0).collect();
+        self.commands
+            .send(Command::Audio(samples))
+            .map_err(|_| "Whisper session is closed".to_string())
+    }
+
+    async fn next_event(&mut self) -> Option<SttEvent> {
+        self.events.recv().await
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        self.commands
+            .send(Command::Close)
+            .map_err(|_| "Whisper session is closed".to_string())
+    }
+}
+
+struct Decoder {
+    state: WhisperState,
+    language: Option<String>,
+    threads: i32,
+    events: async_mpsc::UnboundedSender<SttEvent>,
+    window: Vec<f32>,
+    /// Seconds from session start to the first sample in `window`.
+    window_start: f64,
+    /// Tail of the finalized text, given to whisper as the prompt for the next window.
+    context: String,
+}
+
+impl Decoder {
+    fn run(&mut self, commands: mpsc::Receiver<Command>) {
+        let mut since_decode = 0;
+
+        'session: while let Ok(command) = commands.recv() {
+            // Decoding can be slower than real time; take everything queued at once so
+            // we decode the latest audio instead of falling further behind.
+            let mut closing = false;
+            let mut next = Some(command);
+            while let Some(command) = next.take().or_else(|| commands.try_recv().ok()) {
+                match command {
+                    Command::Audio(samples) => {
+                        since_decode += samples.len();
+                        self.window.extend(samples);
+                    }
+                    Command::Close => {
+                        closing = true;
+                        break;
+                    }
+                }
+            }
+
+            while self.window.len() >= WINDOW_SAMPLES {
+                let cut = quiet_cut(&self.window);
+                let rest = self.window.split_off(cut);
+                self.emit(true);
+                self.window_start += cut as f64 / WHISPER_SAMPLE_RATE as f64;
+                self.window = rest;
+                since_decode = self.window.len();
+            }
+
+            if closing {
+                break 'session;
+            }
+            if since_decode >= STEP_SAMPLES {
+                self.emit(false);
+                since_decode = 0;
+            }
+        }
+
+        if !self.window.is_empty() {
+            self.emit(true);
+        }
+        let _ = self.events.send(SttEvent::Closed {
+            code: None,
+            reason: String::new(),
+        });
+    }
+
+    fn emit(&mut self, is_final: bool) {
+        let event = match self.decode() {
+            Ok((text, confidence)) => {
+                if is_final {
+                    self.remember(&text);
+                }
+                SttEvent::Transcript(Transcript {
+                    transcript: text,
+                    is_final,
+                    speech_final: is_final,
+                    confidence,
+                    start: self.window_start,
+                    duration: self.window.len() as f64 / WHISPER_SAMPLE_RATE as f64,
+                    words: Vec::new(),
+                })
+            }
+            Err(e) => SttEvent::Error(e),
+        };
+        let _ = self.events.send(event);
+    }
+
+    /// Appends finalized text to `context`, keeping only its last `PROMPT_CHARS`.
+    fn remember(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if !self.context.is_empty() {
+            self.context.push(' ');
+        }
+        // The prompt is handed to C as a string, so it must not contain NUL.
+        self.context.extend(text.chars().filter(|&c| c != '\0'));
+
+        let excess = self.context.chars().count().saturating_sub(PROMPT_CHARS);
+        if let Some((start, _)) = self.context.char_indices().nth(excess) {
+            self.context.drain(..start);
+        }
+    }
+
+    /**
+     * Function: decode
+     * Runs whisper over the current window. Returns the text and mean token probability.
+     */
+    fn decode(&mut self) -> Result<(String, f64), String> {
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_n_threads(self.threads);
+        params.set_language(self.language.as_deref());
+        // Interim decodes would leak into whisper's own context, so the previous
+        // windows are passed explicitly as the prompt instead.
+        params.set_no_context(true);
+        if !self.context.is_empty() {
+            params.set_initial_prompt(&self.context);
+        }
+        params.set_suppress_blank(true);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+
+        let mut samples = self.window.clone();
+        if samples.len() < MIN_DECODE_SAMPLES {
+            samples.resize(MIN_DECODE_SAMPLES, 0.0);
+        }
+        self.state
+            .full(params, &samples)
+            .map_err(|e| format!("Whisper decoding failed: {e}"))?;
+
+        let segments = self.state.full_n_segments().map_err(|e| e.to_string())?;
+        let mut text = String::new();
+        let (mut prob_sum, mut tokens) = (0.0f64, 0);
+        for segment in 0..segments {
+            let segment_text = self
+                .state
+                .full_get_segment_text_lossy(segment)
+                .map_err(|e| e.to_string())?;
+            text.push_str(&segment_text);
+
+            let n_tokens = self.state.full_n_tokens(segment).unwrap_or(0);
+            for token in 0..n_tokens {
+                if let Ok(prob) = self.state.full_get_token_prob(segment, token) {
+                    prob_sum += prob as f64;
+                    tokens += 1;
+                }
+            }
+        }
+
+        let confidence = if tokens > 0 {
+            prob_sum / tokens as f64
+        } else {
+            0.0
+        };
+        Ok((text.trim().to_string(), confidence))
+    }
+}
+
+/**
+ * Function: quiet_cut
+ * Where to end a full window: the middle of the quietest `CUT_BLOCK_SAMPLES` block in
+ * its last `CUT_SEARCH_SAMPLES`.
+ */
+fn quiet_cut(window: &[f32]) -> usize {
+    let end = window.len().min(WINDOW_SAMPLES);
+    let first = end.saturating_sub(CUT_SEARCH_SAMPLES);
+    let energy = |start: usize| -> f32 {
+        window[start..start + CUT_BLOCK_SAMPLES]
+            .iter()
+            .map(|s| s * s)
+            .sum()
+    };
+
+    (first..=end.saturating_sub(CUT_BLOCK_SAMPLES))
+        .step_by(CUT_BLOCK_SAMPLES)
+        .min_by(|&a, &b| energy(a).total_cmp(&energy(b)))
+        .map_or(end, |start| start + CUT_BLOCK_SAMPLES / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize) -> Vec<f32> {
+        (0..len).map(|i| (i as f32 * 0.3).sin() * 0.5).collect()
+    }
+
+    #[test]
+    fn cut_lands_in_the_pause() {
+        let mut window = tone(WINDOW_SAMPLES);
+        let pause = WINDOW_SAMPLES - WHISPER_SAMPLE_RATE as usize;
+        window[pause..pause + 4 * CUT_BLOCK_SAMPLES].fill(0.0);
+
+        let cut = quiet_cut(&window);
+        assert!((pause..pause + 4 * CUT_BLOCK_SAMPLES).contains(&cut));
+    }
+
+    #[test]
+    fn cut_stays_within_the_window() {
+        let window = tone(WINDOW_SAMPLES + WHISPER_SAMPLE_RATE as usize);
+        let cut = quiet_cut(&window);
+        assert!((WINDOW_SAMPLES - CUT_SEARCH_SAMPLES..=WINDOW_SAMPLES).contains(&cut));
+    }
+}
@@ -0,0 +1,230 @@
+//! Runs the Deepgram provider, on its own and wrapped in `ResilientSession`, against the
+//! `mock_deepgram` server and its fixtures.
+
+use std::io::{self, BufRead, BufReader};
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use wispr_flow_clone_lib::stt::deepgram::DeepgramProvider;
+use wispr_flow_clone_lib::stt::provider::{SessionOptions, SttProvider, SttSession};
+use wispr_flow_clone_lib::stt::resilient::{
+    ConnectionState, IdlePolicy, ReconnectPolicy, ResilientSession,
+};
+use wispr_flow_clone_lib::stt::SttEvent;
+
+const SAMPLE_RATE: u32 = 16_000;
+
+/// Longest any test waits for the next event.
+const EVENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Struct: MockServer
+ * A `mock_deepgram` process on a free port, killed when dropped.
+ */
+struct MockServer {
+    child: Child,
+    port: u16,
+}
+
+impl MockServer {
+    fn start(fixture: &str) -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("no free port")
+            .port();
+        let fixture = format!(
+            "{}/fixtures/mock-deepgram/{fixture}",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let mut child = Command::new(env!("CARGO_BIN_EXE_mock_deepgram"))
+            .args([fixture.as_str(), "--port", &port.to_string()])
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to start mock_deepgram");
+
+        // The first line is printed once the port is bound. The server logs each
+        // connection after that, so keep reading or it dies of a broken pipe.
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        assert!(line.contains("listening"), "unexpected output: {line}");
+        thread::spawn(move || io::copy(&mut stdout, &mut io::sink()));
+
+        Self { child, port }
+    }
+
+    fn provider(&self) -> DeepgramProvider {
+        DeepgramProvider::new("dev")
+            .with_base_url(format!("ws://127.0.0.1:{}/v1/listen", self.port))
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn options() -> SessionOptions {
+    SessionOptions {
+        model: None,
+        language: None,
+        sample_rate: SAMPLE_RATE,
+        encoded: false,
+    }
+}
+
+/// Pushes `ms` of silence in 100 ms chunks.
+fn push_silence(session: &mut dyn SttSession, ms: u32) {
+    let chunk = vec![0i16; SAMPLE_RATE as usize / 10];
+    for _ in 0..ms / 100 {
+        session.push_audio(&chunk).unwrap();
+    }
+}
+
+async fn next_event(session: &mut dyn SttSession) -> SttEvent {
+    tokio::time::timeout(EVENT_TIMEOUT, session.next_event())
+        .await
+        .expect("timed out waiting for an event")
+        .expect("session ended without Closed")
+}
+
+/// Reads events up to and including `Closed`.
+async fn events_until_closed(session: &mut dyn SttSession) -> Vec<SttEvent> {
+    let mut events = Vec::new();
+    loop {
+        let event = next_event(session).await;
+        let closed = matches!(event, SttEvent::Closed { .. });
+        events.push(event);
+        if closed {
+            return events;
+        }
+    }
+}
+
+/// Non-empty final transcripts with their session-time start.
+fn finals(events: &[SttEvent]) -> Vec<(String, f64)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            SttEvent::Transcript(t) if t.is_final && !t.transcript.is_empty() => {
+                Some((t.transcript.clone(), t.start))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn basic_fixture_streams_interim_and_final_results() {
+    let server = MockServer::start("basic.json");
+    let mut session = server.provider().open(&options()).await.unwrap();
+
+    push_silence(session.as_mut(), 3_500);
+    session.close().unwrap();
+    let events = events_until_closed(session.as_mut()).await;
+
+    let texts: Vec<String> = finals(&events).into_iter().map(|(text, _)| text).collect();
+    assert_eq!(texts, ["Hello world.", "This is a local test."]);
+    assert!(events.iter().any(|e| matches!(
+        e,
+        SttEvent::Transcript(t) if !t.is_final && t.transcript == "Hello"
+    )));
+    assert!(events.contains(&SttEvent::UtteranceEnd));
+    assert!(matches!(
+        events.last(),
+        Some(SttEvent::Closed { code: None, .. })
+    ));
+}
+
+#[tokio::test]
+async fn dropped_connection_is_resumed_and_stitched() {
+    let server = MockServer::start("dropped-connection.json");
+    let policy = ReconnectPolicy {
+        initial_delay: Duration::from_millis(20),
+        ..ReconnectPolicy::default()
+    };
+    let mut session = ResilientSession::open(
+        Box::new(server.provider()),
+        options(),
+        policy,
+        IdlePolicy::KeepAlive,
+    )
+    .await
+    .unwrap();
+
+    // The first connection is closed with 1011 after 2 s of audio; audio pushed after
+    // that is buffered for the next one.
+    push_silence(&mut session, 2_500);
+    let mut events = Vec::new();
+    loop {
+        let event = next_event(&mut session).await;
+        let resumed = event == SttEvent::Connection(ConnectionState::Connected)
+            && events.iter().any(|e| {
+                matches!(
+                    e,
+                    SttEvent::Connection(ConnectionState::Reconnecting { .. })
+                )
+            });
+        events.push(event);
+        if resumed {
+            break;
+        }
+    }
+
+    push_silence(&mut session, 500);
+    session.close().unwrap();
+    events.extend(events_until_closed(&mut session).await);
+
+    let reconnect = events.iter().find_map(|e| match e {
+        SttEvent::Connection(ConnectionState::Reconnecting { reason, .. }) => Some(reason),
+        _ => None,
+    });
+    assert!(reconnect.is_some_and(|reason| reason.contains("1011")));
+
+    // The second connection starts counting from the replayed audio, one second in.
+    let finals = finals(&events);
+    assert_eq!(finals.len(), 2);
+    assert_eq!(finals[0], ("First sentence.".into(), 0.0));
+    assert_eq!(finals[1].0, "Second sentence after reconnect.");
+    assert!(
+        (finals[1].1 - 1.0).abs() < 0.01,
+        "second final at {}",
+        finals[1].1
+    );
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, SttEvent::Connection(ConnectionState::Failed { .. }))));
+}
+
+#[tokio::test]
+async fn server_error_is_reported_before_the_close() {
+    let server = MockServer::start("error.json");
+    let mut session = server.provider().open(&options()).await.unwrap();
+
+    // The server gives up after 800 ms of audio and closes the connection itself.
+    push_silence(session.as_mut(), 1_000);
+    let events = events_until_closed(session.as_mut()).await;
+
+    assert!(events.iter().any(|e| matches!(
+        e,
+        SttEvent::Transcript(t) if !t.is_final && t.transcript == "Partial"
+    )));
+    let error = events
+        .iter()
+        .position(|e| {
+            *e == SttEvent::Error("Deepgram error: Failed to process audio (DATA-0000)".into())
+        })
+        .expect("no error event");
+    assert_eq!(error, events.len() - 2);
+    assert_eq!(
+        events.last(),
+        Some(&SttEvent::Closed {
+            code: Some(1008),
+            reason: "DATA-0000".into(),
+        })
+    );
+}
@@ -1,11 +1,10 @@
-import { useState, useCallback, useEffect, useRef } from "react";
+import { useState, useCallback, useEffect } from "react";
 import { FloatingWidget } from "./components/FloatingWidget";
 import { Settings } from "./components/Settings";
-import { useAudioRecorder } from "./hooks/useAudioRecorder";
-import { useDeepgram } from "./hooks/useDeepgram";
+import { useTranscription } from "./hooks/useTranscription";
 import { useSettings } from "./hooks/useSettings";
 import { useGlobalShortcut } from "./hooks/useGlobalShortcut";
-import { writeText } from "@tauri-apps/plugin-clipboard-manager";
+import { listen } from "@tauri-apps/api/event";
 
 function App() {
   /**
@@ -22,17 +21,10 @@ function App() {
 
   /**
    * State: showCopiedNotification
-   * Shows a brief notification when auto-copy completes.
+   * Shows a brief notification when the backend has copied a transcript.
    */
   const [showCopiedNotification, setShowCopiedNotification] = useState(false);
 
-  /**
-   * Refs for silence detection
-   */
-  const silenceTimerRef = useRef<number | null>(null);
-  const lastTranscriptRef = useRef("");
-  const isRecordingRef = useRef(false);
-
   /**
    * Hook: useSettings
    * Manages app configuration with persistence.
@@ -40,112 +32,71 @@ function App() {
   const { settings, updateSettings } = useSettings();
 
   /**
-   * Hook Initialization: useDeepgram
-   * Passes the API key from environment variables.
+   * Hook Initialization: useTranscription
+   * Follows the backend's recording lifecycle; capture, transcription and the silence
+   * auto-stop all run there.
    */
   const {
-    connectToDeepgram,
-    disconnectFromDeepgram,
+    startRecording,
+    stopRecording,
+    cancelRecording,
     resetTranscript,
+    recordingState,
     connectionState,
     realtimeTranscript,
-    sendAudio,
-    error: deepgramError,
-    lastSpeechTime,
-  } = useDeepgram(import.meta.env.VITE_DEEPGRAM_API_KEY || "");
-
-  /**
-   * Callback: handleAudioData
-   * Receives raw audio chunks from the microphone.
-   */
-  const handleAudioData = useCallback(
-    (data: Blob) => {
-      sendAudio(data);
-    },
-    [sendAudio]
-  );
-
-  /**
-   * Hook Initialization: useAudioRecorder
-   * Manages the actual microphone hardware.
-   */
-  const {
-    startRecording,
-    stopRecording,
     isRecording,
-    error: recorderError,
-  } = useAudioRecorder(handleAudioData);
+    error: transcriptionError,
+  } = useTranscription();
 
   /**
-   * Function: clearSilenceTimer
+   * Function: stopRecordingSession
+   * Stops recording. The backend finalizes the transcript and delivers it as
+   * configured in Settings (copy, paste), then records it in the history.
    */
-  const clearSilenceTimer = () => {
-    if (silenceTimerRef.current) {
-      window.clearInterval(silenceTimerRef.current);
-      silenceTimerRef.current = null;
-    }
-  };
+  const stopRecordingSession = useCallback(async () => {
+    if (recordingState.state !== "recording") return;
+    await stopRecording();
+  }, [recordingState, stopRecording]);
 
   /**
-   * Function: stopRecordingAndCopy
-   * Stops recording and handles auto copy.
+   * Effect: Copied notification
+   * Follows every delivery, including those of recordings stopped by silence.
    */
-  const stopRecordingAndCopy = useCallback(async () => {
-    if (!isRecordingRef.current) return;
-    
-    clearSilenceTimer();
-    isRecordingRef.current = false;
-    
-    const finalTranscript = lastTranscriptRef.current;
-    
-    stopRecording();
-    disconnectFromDeepgram();
-
-    // Auto copy if enabled and there's text
-    if (settings.autoCopyPaste && finalTranscript) {
-      try {
-        await writeText(finalTranscript);
-        // Show notification
-        setShowCopiedNotification(true);
-        setTimeout(() => setShowCopiedNotification(false), 2000);
-      } catch (err) {
-        console.error("Failed to copy:", err);
-      }
-    }
-  }, [settings.autoCopyPaste, stopRecording, disconnectFromDeepgram]);
+  useEffect(() => {
+    const unlisten = listen<{ copied: boolean }>("transcript-delivered", (event) => {
+      if (!event.payload.copied) return;
+      setShowCopiedNotification(true);
+      setTimeout(() => setShowCopiedNotification(false), 2000);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
 
   /**
    * Effect: Sync Transcription
    */
   useEffect(() => {
     setTranscription(realtimeTranscript);
-    lastTranscriptRef.current = realtimeTranscript;
   }, [realtimeTranscript]);
 
   /**
-   * Effect: Silence detection based on actual speech activity
-   * Uses lastSpeechTime from Deepgram (updated on any transcript, including interim)
+   * Function: startRecordingSession
+   * Starts a dictation unless one is already under way. The transcript is cleared
+   * when the backend reports it arming.
    */
-  useEffect(() => {
-    if (!isRecordingRef.current || settings.silenceTimeout <= 0) return;
-
-    clearSilenceTimer();
-
-    // Check periodically if silence threshold has been exceeded
-    const checkSilence = () => {
-      if (!isRecordingRef.current || !lastTranscriptRef.current) return;
-      
-      const timeSinceLastSpeech = Date.now() - lastSpeechTime;
-      if (timeSinceLastSpeech >= settings.silenceTimeout * 1000) {
-        stopRecordingAndCopy();
-      }
-    };
-
-    // Poll every 500ms to check silence duration
-    silenceTimerRef.current = window.setInterval(checkSilence, 500);
+  const startRecordingSession = async () => {
+    if (recordingState.state !== "idle" && recordingState.state !== "error") return;
+    await startRecording();
+  };
 
-    return () => clearSilenceTimer();
-  }, [lastSpeechTime, settings.silenceTimeout, stopRecordingAndCopy]);
+  /**
+   * Effect: Close the settings panel when a dictation starts, from either the mic
+   * button or the shortcut.
+   */
+  useEffect(() => {
+    if (recordingState.state === "arming") setShowSettings(false);
+  }, [recordingState]);
 
   /**
    * Handler: toggleRecording
@@ -153,61 +104,50 @@ function App() {
    */
   const toggleRecording = async () => {
     if (isRecording) {
-      await stopRecordingAndCopy();
+      await stopRecordingSession();
     } else {
-      setShowSettings(false);
-      resetTranscript();
-      setTranscription("");
-      lastTranscriptRef.current = "";
-      
-      isRecordingRef.current = true;
-      await connectToDeepgram();
-      await startRecording();
+      await startRecordingSession();
     }
   };
 
   /**
    * Handler: clearTranscription
-   * Resets the transcription state.
+   * Resets the transcription state, abandoning a dictation still in progress.
    */
-  const clearTranscription = () => {
+  const clearTranscription = async () => {
+    if (isRecording) {
+      await cancelRecording();
+    }
     setTranscription("");
     resetTranscript();
-    lastTranscriptRef.current = "";
   };
 
   /**
    * Hook: useGlobalShortcut
-   * Listens for keyboard shortcut to toggle recording.
+   * Registers the system-wide keyboard shortcut; the backend starts and stops
+   * recording from it directly.
    */
   const {
     currentShortcut,
+    shortcutError,
     isListeningForShortcut,
     startListeningForShortcut,
     stopListeningForShortcut,
   } = useGlobalShortcut(
     settings.shortcut,
-    toggleRecording,
-    settings.shortcutEnabled && !showSettings,
+    settings.shortcutMode,
+    settings.minHoldMs,
+    settings.shortcutEnabled,
     (newShortcut) => updateSettings({ shortcut: newShortcut })
   );
 
-  /**
-   * Effect: Cleanup on unmount
-   */
-  useEffect(() => {
-    return () => clearSilenceTimer();
-  }, []);
-
-  const activeError = recorderError || deepgramError;
-
   return (
     <>
       <FloatingWidget
         transcription={transcription}
         isRecording={isRecording}
         connectionState={connectionState}
-        error={activeError}
+        error={transcriptionError}
         onToggleRecording={toggleRecording}
         onClearTranscription={clearTranscription}
         onOpenSettings={() => setShowSettings(true)}
@@ -226,6 +166,7 @@ function App() {
           isRecordingShortcut={isListeningForShortcut}
           onStartRecordingShortcut={startListeningForShortcut}
           currentShortcut={currentShortcut}
+          shortcutError={shortcutError}
         />
       )}
     </>
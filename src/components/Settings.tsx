@@ -1,5 +1,46 @@
-import { FaTimes, FaKeyboard, FaCopy, FaClock } from "react-icons/fa";
-import { AppSettings } from "../hooks/useSettings";
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import {
+  FaTimes,
+  FaKeyboard,
+  FaCopy,
+  FaPaste,
+  FaClock,
+  FaKey,
+  FaHistory,
+  FaUserSecret,
+  FaMicrophone,
+} from "react-icons/fa";
+import { AppSettings, InsertMode, ShortcutMode } from "../hooks/useSettings";
+import { KeySource, useApiKey } from "../hooks/useApiKey";
+import { usePrivacy } from "../hooks/usePrivacy";
+
+/**
+ * Labels for where the API key is kept.
+ */
+const KEY_SOURCE_LABELS: Record<KeySource, string> = {
+  keychain: "system keyring",
+  encryptedFile: "encrypted file",
+  environment: "DEEPGRAM_API_KEY environment variable",
+};
+
+/**
+ * Labels for the speech-to-text providers the backend may offer.
+ */
+const PROVIDER_LABELS: Record<string, string> = {
+  deepgram: "Deepgram (cloud)",
+  whisper: "Whisper (on this device)",
+};
+
+/**
+ * Interface: LocalModel
+ * A Whisper model file found by `list_local_models`.
+ */
+interface LocalModel {
+  name: string;
+  path: string;
+  sizeBytes: number;
+}
 
 /**
  * Interface: SettingsProps
@@ -12,6 +53,7 @@ interface SettingsProps {
   isRecordingShortcut: boolean;
   onStartRecordingShortcut: () => void;
   currentShortcut: string;
+  shortcutError: string | null;
 }
 
 /**
@@ -25,7 +67,67 @@ export function Settings({
   isRecordingShortcut,
   onStartRecordingShortcut,
   currentShortcut,
+  shortcutError,
 }: SettingsProps) {
+  /**
+   * State: keyInput
+   * Key being typed; cleared once saved. The stored key is never shown.
+   */
+  const [keyInput, setKeyInput] = useState("");
+  const apiKey = useApiKey("deepgram");
+  const privacy = usePrivacy();
+
+  /**
+   * State: confirmingWipe
+   * The wipe button asks for a second click before deleting anything.
+   */
+  const [confirmingWipe, setConfirmingWipe] = useState(false);
+
+  /**
+   * State: appInput
+   * Window class typed for a new per-app insert mode.
+   */
+  const [appInput, setAppInput] = useState("");
+
+  /**
+   * Function: setAppInsertMode
+   * Sets the insert mode for one application, or removes its override with null.
+   */
+  const setAppInsertMode = (app: string, mode: InsertMode | null) => {
+    const appInsertModes = { ...settings.appInsertModes };
+    if (mode) {
+      appInsertModes[app] = mode;
+    } else {
+      delete appInsertModes[app];
+    }
+    onUpdateSettings({ appInsertModes });
+  };
+
+  /**
+   * State: providers
+   * Provider ids this build supports, as reported by the backend.
+   */
+  const [providers, setProviders] = useState<string[]>([settings.provider]);
+
+  /**
+   * State: localModels
+   * Whisper model files in the models directory, listed when Whisper is chosen.
+   */
+  const [localModels, setLocalModels] = useState<LocalModel[]>([]);
+
+  useEffect(() => {
+    invoke<string[]>("list_stt_providers")
+      .then(setProviders)
+      .catch((err) => console.error("Failed to list providers:", err));
+  }, []);
+
+  useEffect(() => {
+    if (settings.provider !== "whisper") return;
+    invoke<LocalModel[]>("list_local_models")
+      .then(setLocalModels)
+      .catch((err) => console.error("Failed to list local models:", err));
+  }, [settings.provider]);
+
   return (
     <div className="settings-panel">
       {/* Header */}
@@ -67,10 +169,146 @@ export function Settings({
               <p className="settings-hint">
                 Click to change, then press your desired key combination
               </p>
+              {shortcutError && (
+                <p className="settings-error">{shortcutError}</p>
+              )}
+              <select
+                value={settings.shortcutMode}
+                onChange={(e) =>
+                  onUpdateSettings({
+                    shortcutMode: e.target.value as ShortcutMode,
+                  })
+                }
+                className="settings-select"
+              >
+                <option value="toggle">Press to start / stop</option>
+                <option value="hold">Hold to talk</option>
+                <option value="doubleTap">Double-tap for hands-free</option>
+              </select>
             </div>
           )}
         </div>
 
+        {/* Transcription Section */}
+        <div className="settings-section">
+          <div className="settings-row">
+            <div className="settings-label">
+              <FaMicrophone className="settings-icon" />
+              <span>Transcription</span>
+            </div>
+            <select
+              value={settings.provider}
+              onChange={(e) =>
+                onUpdateSettings({ provider: e.target.value, model: null })
+              }
+              className="settings-select"
+            >
+              {providers.map((id) => (
+                <option key={id} value={id}>
+                  {PROVIDER_LABELS[id] ?? id}
+                </option>
+              ))}
+            </select>
+          </div>
+          <div className="settings-sub">
+            {settings.provider === "whisper" ? (
+              <select
+                value={settings.model ?? ""}
+                onChange={(e) => onUpdateSettings({ model: e.target.value || null })}
+                className="settings-select"
+              >
+                <option value="">First model found</option>
+                {localModels.map((model) => (
+                  <option key={model.path} value={model.name}>
+                    {model.name} ({Math.round(model.sizeBytes / 1_000_000)} MB)
+                  </option>
+                ))}
+              </select>
+            ) : (
+              // Committed on blur, so each keystroke is not saved
+              <input
+                key={`model-${settings.provider}-${settings.model}`}
+                defaultValue={settings.model ?? ""}
+                onBlur={(e) =>
+                  onUpdateSettings({ model: e.target.value.trim() || null })
+                }
+                placeholder="Model (provider default)"
+                className="settings-input"
+                spellCheck={false}
+              />
+            )}
+            <input
+              key={`language-${settings.language}`}
+              defaultValue={settings.language ?? ""}
+              onBlur={(e) =>
+                onUpdateSettings({ language: e.target.value.trim() || null })
+              }
+              placeholder="Language, e.g. en-US (provider default)"
+              className="settings-input"
+              spellCheck={false}
+            />
+            {settings.provider === "whisper" && localModels.length === 0 && (
+              <p className="settings-hint">
+                No models found. Put a ggml model (e.g. ggml-base.en.bin) in the
+                models folder of the app data directory.
+              </p>
+            )}
+          </div>
+        </div>
+
+        {/* API Key Section */}
+        <div className="settings-section">
+          <div className="settings-label">
+            <FaKey className="settings-icon" />
+            <span>Deepgram API key</span>
+          </div>
+          <div className="settings-sub">
+            <input
+              type="password"
+              value={keyInput}
+              onChange={(e) => setKeyInput(e.target.value)}
+              placeholder={
+                apiKey.status?.configured ? "Enter a new key to replace it" : "Paste your key"
+              }
+              className="settings-input"
+              autoComplete="off"
+              spellCheck={false}
+            />
+            <div className="settings-actions">
+              <button
+                className="settings-action-btn"
+                disabled={apiKey.busy || !keyInput.trim()}
+                onClick={async () => {
+                  if (await apiKey.saveKey(keyInput)) setKeyInput("");
+                }}
+              >
+                Save
+              </button>
+              <button
+                className="settings-action-btn"
+                disabled={apiKey.busy || (!keyInput.trim() && !apiKey.status?.configured)}
+                onClick={() => apiKey.testKey(keyInput.trim())}
+              >
+                Test
+              </button>
+              <button
+                className="settings-action-btn"
+                disabled={apiKey.busy || !apiKey.status?.configured}
+                onClick={apiKey.clearKey}
+              >
+                Clear
+              </button>
+            </div>
+            <p className="settings-hint">
+              {apiKey.status?.configured && apiKey.status.source
+                ? `Using the key from the ${KEY_SOURCE_LABELS[apiKey.status.source]}`
+                : "No key set"}
+            </p>
+            {apiKey.message && <p className="settings-hint">{apiKey.message}</p>}
+            {apiKey.error && <p className="settings-error">{apiKey.error}</p>}
+          </div>
+        </div>
+
         {/* Auto Copy Section */}
         <div className="settings-section">
           <div className="settings-row">
@@ -94,6 +332,118 @@ export function Settings({
           </p>
         </div>
 
+        {/* Auto Paste Section */}
+        <div className="settings-section">
+          <div className="settings-row">
+            <div className="settings-label">
+              <FaPaste className="settings-icon" />
+              <span>Auto Paste</span>
+            </div>
+            <label className="toggle">
+              <input
+                type="checkbox"
+                checked={settings.autoPaste}
+                onChange={(e) => onUpdateSettings({ autoPaste: e.target.checked })}
+              />
+              <span className="toggle-slider" />
+            </label>
+          </div>
+          {settings.autoPaste ? (
+            <div className="settings-sub">
+              <select
+                value={settings.insertMode}
+                onChange={(e) =>
+                  onUpdateSettings({ insertMode: e.target.value as InsertMode })
+                }
+                className="settings-select"
+              >
+                <option value="paste">Paste</option>
+                <option value="type">Type (for apps that block paste)</option>
+              </select>
+              {Object.entries(settings.appInsertModes).map(([app, mode]) => (
+                <div className="settings-row" key={app}>
+                  <span className="settings-hint">{app}</span>
+                  <div className="settings-actions">
+                    <select
+                      value={mode}
+                      onChange={(e) =>
+                        setAppInsertMode(app, e.target.value as InsertMode)
+                      }
+                      className="settings-select"
+                    >
+                      <option value="paste">Paste</option>
+                      <option value="type">Type</option>
+                    </select>
+                    <button
+                      className="settings-action-btn"
+                      onClick={() => setAppInsertMode(app, null)}
+                    >
+                      Remove
+                    </button>
+                  </div>
+                </div>
+              ))}
+              <div className="settings-actions">
+                <input
+                  type="text"
+                  value={appInput}
+                  onChange={(e) => setAppInput(e.target.value)}
+                  placeholder="Window class, e.g. xterm"
+                  className="settings-input"
+                  spellCheck={false}
+                />
+                <button
+                  className="settings-action-btn"
+                  disabled={!appInput.trim()}
+                  onClick={() => {
+                    setAppInsertMode(
+                      appInput.trim(),
+                      settings.insertMode === "paste" ? "type" : "paste"
+                    );
+                    setAppInput("");
+                  }}
+                >
+                  Add app
+                </button>
+              </div>
+              <div className="settings-row">
+                <span className="settings-hint">Restore clipboard after pasting</span>
+                <label className="toggle">
+                  <input
+                    type="checkbox"
+                    checked={settings.restoreClipboard}
+                    onChange={(e) =>
+                      onUpdateSettings({ restoreClipboard: e.target.checked })
+                    }
+                  />
+                  <span className="toggle-slider" />
+                </label>
+              </div>
+              {settings.restoreClipboard && (
+                <select
+                  value={settings.restoreDelayMs}
+                  onChange={(e) =>
+                    onUpdateSettings({ restoreDelayMs: Number(e.target.value) })
+                  }
+                  className="settings-select"
+                >
+                  <option value={250}>Restore after 0.25 seconds</option>
+                  <option value={750}>Restore after 0.75 seconds</option>
+                  <option value={2000}>Restore after 2 seconds</option>
+                  <option value={5000}>Restore after 5 seconds</option>
+                </select>
+              )}
+              <p className="settings-hint">
+                Only text and images on the clipboard are restored
+              </p>
+            </div>
+          ) : (
+            <p className="settings-hint settings-sub">
+              Inserts transcription into the app you were using when recording started
+            </p>
+          )}
+        </div>
+
         {/* Silence Timeout Section */}
         <div className="settings-section">
           <div className="settings-row">
@@ -119,6 +469,126 @@ export function Settings({
             Stops recording after you stop speaking
           </p>
         </div>
+
+        {/* History Section */}
+        <div className="settings-section">
+          <div className="settings-row">
+            <div className="settings-label">
+              <FaHistory className="settings-icon" />
+              <span>Save history</span>
+            </div>
+            <label className="toggle">
+              <input
+                type="checkbox"
+                checked={settings.historyEnabled}
+                onChange={(e) =>
+                  onUpdateSettings({ historyEnabled: e.target.checked })
+                }
+              />
+              <span className="toggle-slider" />
+            </label>
+          </div>
+          {settings.historyEnabled ? (
+            <div className="settings-sub">
+              <select
+                value={settings.historyMaxAgeDays}
+                onChange={(e) =>
+                  onUpdateSettings({ historyMaxAgeDays: Number(e.target.value) })
+                }
+                className="settings-select"
+              >
+                <option value={0}>Keep forever</option>
+                <option value={1}>Keep for 1 day</option>
+                <option value={7}>Keep for 7 days</option>
+                <option value={30}>Keep for 30 days</option>
+                <option value={90}>Keep for 90 days</option>
+              </select>
+              <select
+                value={settings.historyMaxEntries}
+                onChange={(e) =>
+                  onUpdateSettings({ historyMaxEntries: Number(e.target.value) })
+                }
+                className="settings-select"
+              >
+                <option value={0}>No entry limit</option>
+                <option value={50}>Newest 50 entries</option>
+                <option value={500}>Newest 500 entries</option>
+                <option value={5000}>Newest 5000 entries</option>
+              </select>
+              <div className="settings-row">
+                <span className="settings-hint">Keep session audio</span>
+                <label className="toggle">
+                  <input
+                    type="checkbox"
+                    checked={settings.archiveAudio}
+                    onChange={(e) =>
+                      onUpdateSettings({ archiveAudio: e.target.checked })
+                    }
+                  />
+                  <span className="toggle-slider" />
+                </label>
+              </div>
+              {settings.archiveAudio && (
+                <select
+                  value={settings.archiveMaxMb}
+                  onChange={(e) =>
+                    onUpdateSettings({ archiveMaxMb: Number(e.target.value) })
+                  }
+                  className="settings-select"
+                >
+                  <option value={100}>Up to 100 MB of audio</option>
+                  <option value={500}>Up to 500 MB of audio</option>
+                  <option value={2000}>Up to 2 GB of audio</option>
+                </select>
+              )}
+            </div>
+          ) : (
+            <p className="settings-hint settings-sub">
+              Transcripts are not written to disk, and stored ones are deleted
+            </p>
+          )}
+          <div className="settings-sub">
+            <div className="settings-actions">
+              <button
+                className="settings-action-btn"
+                onClick={async () => {
+                  if (!confirmingWipe) {
+                    setConfirmingWipe(true);
+                    return;
+                  }
+                  setConfirmingWipe(false);
+                  await privacy.wipeHistory();
+                }}
+                onBlur={() => setConfirmingWipe(false)}
+              >
+                {confirmingWipe ? "Click again to delete all" : "Wipe history"}
+              </button>
+            </div>
+            {privacy.message && <p className="settings-hint">{privacy.message}</p>}
+            {privacy.error && <p className="settings-error">{privacy.error}</p>}
+          </div>
+        </div>
+
+        {/* Private Session Section */}
+        <div className="settings-section">
+          <div className="settings-row">
+            <div className="settings-label">
+              <FaUserSecret className="settings-icon" />
+              <span>Private session</span>
+            </div>
+            <label className="toggle">
+              <input
+                type="checkbox"
+                checked={privacy.privateSession}
+                onChange={(e) => privacy.setPrivateSession(e.target.checked)}
+              />
+              <span className="toggle-slider" />
+            </label>
+          </div>
+          <p className="settings-hint settings-sub">
+            Nothing you dictate is saved until you turn this off or quit
+          </p>
+        </div>
       </div>
     </div>
   );
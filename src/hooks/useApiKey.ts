@@ -0,0 +1,117 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Type: KeySource
+ * Where the backend found the key: the system keyring, the encrypted fallback file,
+ * or the `<PROVIDER>_API_KEY` environment variable.
+ */
+export type KeySource = "keychain" | "encryptedFile" | "environment";
+
+/**
+ * Interface: ApiKeyStatus
+ * What the backend reveals about a key. The key itself is never returned.
+ */
+export interface ApiKeyStatus {
+  provider: string;
+  configured: boolean;
+  source: KeySource | null;
+  store: KeySource | null;
+}
+
+/**
+ * Interface: UseApiKeyReturn
+ * Defines the public API for managing one provider's key.
+ */
+interface UseApiKeyReturn {
+  status: ApiKeyStatus | null;
+  message: string | null;
+  error: string | null;
+  busy: boolean;
+  saveKey: (key: string) => Promise<boolean>;
+  testKey: (key?: string) => Promise<void>;
+  clearKey: () => Promise<void>;
+}
+
+/**
+ * Hook: useApiKey
+ * Responsibility: Sets, tests and clears a provider's API key through the backend's
+ * secret store. Keys only travel from the webview to the backend, never back.
+ */
+export function useApiKey(provider: string): UseApiKeyReturn {
+  const [status, setStatus] = useState<ApiKeyStatus | null>(null);
+  const [message, setMessage] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  /**
+   * Effect: Load the key status on mount
+   */
+  useEffect(() => {
+    invoke<ApiKeyStatus>("get_api_key_status", { provider })
+      .then(setStatus)
+      .catch((err) => setError(String(err)));
+  }, [provider]);
+
+  /**
+   * Helper: run
+   * Runs one backend call with the busy flag set and its outcome reported.
+   */
+  const run = useCallback(
+    async (action: () => Promise<string>) => {
+      setBusy(true);
+      setMessage(null);
+      setError(null);
+      try {
+        setMessage(await action());
+        return true;
+      } catch (err) {
+        setError(String(err));
+        return false;
+      } finally {
+        setBusy(false);
+      }
+    },
+    []
+  );
+
+  /**
+   * Function: saveKey
+   * Stores the key, replacing any previous one.
+   */
+  const saveKey = useCallback(
+    (key: string) =>
+      run(async () => {
+        setStatus(await invoke<ApiKeyStatus>("set_api_key", { provider, key }));
+        return "Key saved";
+      }),
+    [provider, run]
+  );
+
+  /**
+   * Function: testKey
+   * Checks `key` if given, otherwise the key in use, against the provider.
+   */
+  const testKey = useCallback(
+    async (key?: string) => {
+      await run(async () => {
+        await invoke("test_api_key", { provider, key: key || null });
+        return "Key accepted";
+      });
+    },
+    [provider, run]
+  );
+
+  /**
+   * Function: clearKey
+   * Deletes the stored key.
+   */
+  const clearKey = useCallback(async () => {
+    await run(async () => {
+      setStatus(await invoke<ApiKeyStatus>("clear_api_key", { provider }));
+      return "Key removed";
+    });
+  }, [provider, run]);
+
+  return { status, message, error, busy, saveKey, testKey, clearKey };
+}
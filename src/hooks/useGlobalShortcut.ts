@@ -1,4 +1,6 @@
 import { useEffect, useCallback, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ShortcutMode } from "./useSettings";
 
 /**
  * Interface: UseGlobalShortcutReturn
@@ -6,6 +8,7 @@ import { useEffect, useCallback, useState } from "react";
  */
 interface UseGlobalShortcutReturn {
   currentShortcut: string;
+  shortcutError: string | null;
   isListeningForShortcut: boolean;
   startListeningForShortcut: () => void;
   stopListeningForShortcut: () => void;
@@ -13,17 +16,21 @@ interface UseGlobalShortcutReturn {
 
 /**
  * Hook: useGlobalShortcut
- * Responsibility: Listens for a keyboard shortcut and triggers callback.
+ * Responsibility: Registers the shortcut system-wide through the backend, which starts
+ * and stops recording as its gesture (toggle, hold, double tap) resolves, even while
+ * another application has focus.
  * Also supports recording a new shortcut with multiple modifiers.
  */
 export function useGlobalShortcut(
   shortcut: string,
-  onShortcutPressed: () => void,
+  mode: ShortcutMode,
+  minHoldMs: number,
   enabled: boolean,
   onShortcutChanged?: (newShortcut: string) => void
 ): UseGlobalShortcutReturn {
   const [isListeningForShortcut, setIsListeningForShortcut] = useState(false);
   const [currentShortcut, setCurrentShortcut] = useState(shortcut);
+  const [shortcutError, setShortcutError] = useState<string | null>(null);
 
   // Sync with prop changes
   useEffect(() => {
@@ -31,73 +38,52 @@ export function useGlobalShortcut(
   }, [shortcut]);
 
   /**
-   * Helper: parseShortcut
-   * Converts shortcut string to key components.
+   * Effect: Register the shortcut with the backend
+   * Re-registers whenever the shortcut, its mode or its enabled state changes. Conflicts
+   * with other applications are reported through shortcutError.
    */
-  const parseShortcut = useCallback((shortcutStr: string) => {
-    const parts = shortcutStr.toLowerCase().split("+");
-    return {
-      ctrl: parts.includes("ctrl"),
-      shift: parts.includes("shift"),
-      alt: parts.includes("alt"),
-      key: parts.find((p) => !["ctrl", "shift", "alt"].includes(p)) || "",
-    };
-  }, []);
+  useEffect(() => {
+    invoke("set_global_shortcut", {
+      accelerator: currentShortcut,
+      enabled: enabled && !isListeningForShortcut,
+      mode,
+      minHoldMs,
+    })
+      .then(() => setShortcutError(null))
+      .catch((err) => setShortcutError(String(err)));
+  }, [currentShortcut, enabled, isListeningForShortcut, mode, minHoldMs]);
 
   /**
-   * Effect: Listen for shortcut or record new one
+   * Effect: Record a new shortcut
    */
   useEffect(() => {
-    const handleKeyDown = (e: KeyboardEvent) => {
-      // If recording new shortcut
-      if (isListeningForShortcut) {
-        // Wait for a non-modifier key to complete the shortcut
-        if (["Control", "Shift", "Alt", "Meta"].includes(e.key)) {
-          return; // Keep waiting for the main key
-        }
-
-        e.preventDefault();
-        e.stopPropagation();
+    if (!isListeningForShortcut) return;
 
-        // Build the shortcut string
-        const parts: string[] = [];
-        if (e.ctrlKey) parts.push("Ctrl");
-        if (e.shiftKey) parts.push("Shift");
-        if (e.altKey) parts.push("Alt");
-        parts.push(e.key.length === 1 ? e.key.toUpperCase() : e.key);
-
-        const newShortcut = parts.join("+");
-        setCurrentShortcut(newShortcut);
-        setIsListeningForShortcut(false);
-        onShortcutChanged?.(newShortcut);
-        return;
+    const handleKeyDown = (e: KeyboardEvent) => {
+      // Wait for a non-modifier key to complete the shortcut
+      if (["Control", "Shift", "Alt", "Meta"].includes(e.key)) {
+        return; // Keep waiting for the main key
       }
 
-      // Normal shortcut detection
-      if (!enabled) return;
+      e.preventDefault();
+      e.stopPropagation();
 
-      const parsed = parseShortcut(currentShortcut);
-      const ctrlMatch = e.ctrlKey === parsed.ctrl;
-      const shiftMatch = e.shiftKey === parsed.shift;
-      const altMatch = e.altKey === parsed.alt;
-      const keyMatch = e.key.toLowerCase() === parsed.key.toLowerCase();
+      // Build the shortcut string
+      const parts: string[] = [];
+      if (e.ctrlKey) parts.push("Ctrl");
+      if (e.shiftKey) parts.push("Shift");
+      if (e.altKey) parts.push("Alt");
+      parts.push(e.key.length === 1 ? e.key.toUpperCase() : e.key);
 
-      if (ctrlMatch && shiftMatch && altMatch && keyMatch) {
-        e.preventDefault();
-        onShortcutPressed();
-      }
+      const newShortcut = parts.join("+");
+      setCurrentShortcut(newShortcut);
+      setIsListeningForShortcut(false);
+      onShortcutChanged?.(newShortcut);
     };
 
     window.addEventListener("keydown", handleKeyDown);
     return () => window.removeEventListener("keydown", handleKeyDown);
-  }, [
-    enabled,
-    currentShortcut,
-    isListeningForShortcut,
-    parseShortcut,
-    onShortcutPressed,
-    onShortcutChanged,
-  ]);
+  }, [isListeningForShortcut, onShortcutChanged]);
 
   const startListeningForShortcut = useCallback(() => {
     setIsListeningForShortcut(true);
@@ -109,6 +95,7 @@ export function useGlobalShortcut(
 
   return {
     currentShortcut,
+    shortcutError,
     isListeningForShortcut,
     startListeningForShortcut,
     stopListeningForShortcut,
@@ -0,0 +1,72 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+/**
+ * Interface: UsePrivacyReturn
+ * Defines the public API for the private session switch and the history wipe.
+ */
+interface UsePrivacyReturn {
+  privateSession: boolean;
+  message: string | null;
+  error: string | null;
+  setPrivateSession: (enabled: boolean) => Promise<void>;
+  wipeHistory: () => Promise<void>;
+}
+
+/**
+ * Hook: usePrivacy
+ * Responsibility: Mirrors the backend's private session switch and wipes the stored
+ * history. The backend decides what is written to disk; this only asks it.
+ */
+export function usePrivacy(): UsePrivacyReturn {
+  const [privateSession, setPrivate] = useState(false);
+  const [message, setMessage] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  /**
+   * Effect: Load the switch on mount and follow its changes
+   */
+  useEffect(() => {
+    invoke<boolean>("get_private_session")
+      .then(setPrivate)
+      .catch((err) => setError(String(err)));
+
+    const unlisten = listen<boolean>("private-session-changed", (event) => {
+      setPrivate(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  /**
+   * Function: setPrivateSession
+   * Starts or ends a private session.
+   */
+  const setPrivateSession = useCallback(async (enabled: boolean) => {
+    setError(null);
+    try {
+      setPrivate(await invoke<boolean>("set_private_session", { enabled }));
+    } catch (err) {
+      setError(String(err));
+    }
+  }, []);
+
+  /**
+   * Function: wipeHistory
+   * Deletes every stored transcript.
+   */
+  const wipeHistory = useCallback(async () => {
+    setMessage(null);
+    setError(null);
+    try {
+      const removed = await invoke<number>("wipe_history");
+      setMessage(`Deleted ${removed} ${removed === 1 ? "entry" : "entries"}`);
+    } catch (err) {
+      setError(String(err));
+    }
+  }, []);
+
+  return { privateSession, message, error, setPrivateSession, wipeHistory };
+}
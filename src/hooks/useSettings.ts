@@ -1,4 +1,19 @@
 import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+/**
+ * Type: ShortcutMode
+ * toggle: press to start/stop; hold: record while held; doubleTap: double tap to
+ * start hands-free, press again to stop.
+ */
+export type ShortcutMode = "toggle" | "hold" | "doubleTap";
+
+/**
+ * Type: InsertMode
+ * paste: copy, then press the paste chord; type: type it character by character.
+ */
+export type InsertMode = "paste" | "type";
 
 /**
  * Interface: AppSettings
@@ -7,8 +22,23 @@ import { useState, useEffect, useCallback } from "react";
 export interface AppSettings {
   shortcutEnabled: boolean;
   shortcut: string;
+  shortcutMode: ShortcutMode;
+  minHoldMs: number; // hold mode: shorter presses are ignored
   autoCopyPaste: boolean;
+  autoPaste: boolean; // insert into the focused app when recording stops
+  insertMode: InsertMode;
+  appInsertModes: Record<string, InsertMode>; // by window class, e.g. { xterm: "type" }
+  restoreClipboard: boolean; // put back what the clipboard held after pasting
+  restoreDelayMs: number; // how long the transcript stays on the clipboard
   silenceTimeout: number; // seconds of silence before auto-stop (0 = disabled)
+  provider: string; // speech-to-text engine, from list_stt_providers
+  model: string | null; // null = the provider's default
+  language: string | null; // e.g. "en-US"; null = the provider's default
+  historyEnabled: boolean; // keep finished transcripts on disk
+  historyMaxAgeDays: number; // 0 = keep forever
+  historyMaxEntries: number; // 0 = no limit
+  archiveAudio: boolean; // keep each session's audio with its transcript
+  archiveMaxMb: number; // space all kept recordings may take up
 }
 
 /**
@@ -17,11 +47,30 @@ export interface AppSettings {
 const DEFAULT_SETTINGS: AppSettings = {
   shortcutEnabled: true,
   shortcut: "Ctrl+Shift+R",
+  shortcutMode: "toggle",
+  minHoldMs: 200,
   autoCopyPaste: false,
+  autoPaste: false,
+  insertMode: "paste",
+  appInsertModes: {},
+  restoreClipboard: true,
+  restoreDelayMs: 750,
   silenceTimeout: 2,
+  provider: "deepgram",
+  model: null,
+  language: null,
+  historyEnabled: true,
+  historyMaxAgeDays: 0,
+  historyMaxEntries: 0,
+  archiveAudio: false,
+  archiveMaxMb: 500,
 };
 
-const STORAGE_KEY = "wispr-settings";
+/**
+ * Key the settings were kept under before the backend owned them. Read once for the
+ * import, then removed.
+ */
+const LEGACY_STORAGE_KEY = "wispr-settings";
 
 /**
  * Interface: UseSettingsReturn
@@ -35,39 +84,62 @@ interface UseSettingsReturn {
 
 /**
  * Hook: useSettings
- * Responsibility: Manages app settings with localStorage persistence.
+ * Responsibility: Mirrors the backend settings store (`settings.json` in the app
+ * config dir) and sends changes to it. Imports settings from localStorage once.
  */
 export function useSettings(): UseSettingsReturn {
   const [settings, setSettings] = useState<AppSettings>(DEFAULT_SETTINGS);
 
   /**
-   * Effect: Load settings from localStorage on mount
+   * Effect: Load settings from the backend on mount and follow its changes
    */
   useEffect(() => {
-    try {
-      const stored = localStorage.getItem(STORAGE_KEY);
-      if (stored) {
-        const parsed = JSON.parse(stored);
-        setSettings({ ...DEFAULT_SETTINGS, ...parsed });
+    let cancelled = false;
+
+    const load = async () => {
+      try {
+        const legacy = localStorage.getItem(LEGACY_STORAGE_KEY);
+        if (legacy) {
+          await invoke("import_legacy_settings", { values: JSON.parse(legacy) });
+          localStorage.removeItem(LEGACY_STORAGE_KEY);
+        }
+      } catch (err) {
+        console.error("Failed to import settings:", err);
       }
-    } catch (err) {
-      console.error("Failed to load settings:", err);
-    }
+
+      try {
+        const loaded = await invoke<AppSettings>("get_settings");
+        if (!cancelled) setSettings({ ...DEFAULT_SETTINGS, ...loaded });
+      } catch (err) {
+        console.error("Failed to load settings:", err);
+      }
+    };
+    load();
+
+    const unlisten = listen<AppSettings>("settings-changed", (event) => {
+      setSettings({ ...DEFAULT_SETTINGS, ...event.payload });
+    });
+    return () => {
+      cancelled = true;
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   /**
    * Function: updateSettings
-   * Updates specific settings and persists to localStorage.
+   * Applies the change immediately and persists it through the backend, which
+   * validates it. A rejected change is rolled back to the stored settings.
    */
   const updateSettings = useCallback((updates: Partial<AppSettings>) => {
-    setSettings((prev) => {
-      const newSettings = { ...prev, ...updates };
+    setSettings((prev) => ({ ...prev, ...updates }));
+    invoke<AppSettings>("update_settings", { patch: updates }).catch(async (err) => {
+      console.error("Failed to save settings:", err);
       try {
-        localStorage.setItem(STORAGE_KEY, JSON.stringify(newSettings));
-      } catch (err) {
-        console.error("Failed to save settings:", err);
+        const stored = await invoke<AppSettings>("get_settings");
+        setSettings({ ...DEFAULT_SETTINGS, ...stored });
+      } catch (loadErr) {
+        console.error("Failed to load settings:", loadErr);
       }
-      return newSettings;
     });
   }, []);
 
@@ -76,12 +148,9 @@ export function useSettings(): UseSettingsReturn {
    * Resets all settings to defaults.
    */
   const resetSettings = useCallback(() => {
-    setSettings(DEFAULT_SETTINGS);
-    try {
-      localStorage.removeItem(STORAGE_KEY);
-    } catch (err) {
-      console.error("Failed to clear settings:", err);
-    }
+    invoke<AppSettings>("reset_settings")
+      .then((stored) => setSettings({ ...DEFAULT_SETTINGS, ...stored }))
+      .catch((err) => console.error("Failed to reset settings:", err));
   }, []);
 
   return {
@@ -0,0 +1,175 @@
+import { useState, useCallback, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+/**
+ * Type: TranscriptionConnectionState
+ * Represents the current status of the backend's provider connection.
+ */
+type TranscriptionConnectionState = "closed" | "connecting" | "connected" | "error";
+
+/**
+ * Type: RecordingState
+ * Payload of the backend `recording-state` event, and of `get_recording_state`.
+ */
+export type RecordingState =
+  | { state: "idle" | "arming" | "recording" | "finalizing" | "delivering" }
+  | { state: "error"; message: string };
+
+/**
+ * Interface: TranscriptEvent
+ * Payload of the backend `transcript` event (fields used here).
+ */
+interface TranscriptEvent {
+  transcript: string;
+  isFinal: boolean;
+}
+
+/**
+ * Interface: Delivery
+ * Result of `stop_recording` (fields used here).
+ */
+interface Delivery {
+  text: string;
+}
+
+/**
+ * Interface: UseTranscriptionReturn
+ * Defines the public API exposed by the useTranscription hook.
+ */
+interface UseTranscriptionReturn {
+  startRecording: () => Promise<boolean>;
+  stopRecording: () => Promise<string | null>;
+  cancelRecording: () => Promise<void>;
+  resetTranscript: () => void;
+  recordingState: RecordingState;
+  connectionState: TranscriptionConnectionState;
+  realtimeTranscript: string;
+  isRecording: boolean;
+  error: string | null;
+}
+
+/**
+ * Hook: useTranscription
+ * Responsibility: Drives the backend's recording lifecycle and follows its events.
+ * Capture, the provider connection (and its API key), silence auto-stop and delivery
+ * all stay in the backend; this only renders what `recording-state` reports.
+ */
+export function useTranscription(): UseTranscriptionReturn {
+  const [recordingState, setRecordingState] = useState<RecordingState>({
+    state: "idle",
+  });
+  const [reconnecting, setReconnecting] = useState(false);
+  const [realtimeTranscript, setRealtimeTranscript] = useState("");
+  const [startError, setStartError] = useState<string | null>(null);
+
+  /**
+   * Effect: Follow the recording lifecycle, transcripts and connection events
+   */
+  useEffect(() => {
+    invoke<RecordingState>("get_recording_state")
+      .then(setRecordingState)
+      .catch(() => {});
+
+    const unlisteners = [
+      listen<RecordingState>("recording-state", (event) => {
+        // A new dictation, however it was started, begins with an empty transcript.
+        if (event.payload.state === "arming") {
+          setRealtimeTranscript("");
+          setStartError(null);
+        }
+        setReconnecting(false);
+        setRecordingState(event.payload);
+      }),
+      listen<TranscriptEvent>("transcript", (event) => {
+        const { transcript, isFinal } = event.payload;
+        if (isFinal && transcript) {
+          setRealtimeTranscript((prev) => prev + (prev ? " " : "") + transcript);
+        }
+      }),
+      listen<{ state: string }>("connection-state", (event) => {
+        setReconnecting(event.payload.state === "reconnecting");
+      }),
+      listen<string>("transcription-error", (event) => {
+        console.error("Transcription error:", event.payload);
+      }),
+    ];
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()));
+    };
+  }, []);
+
+  /**
+   * Function: startRecording
+   * Starts a dictation. The backend releases the microphone itself if the session
+   * cannot be opened (e.g. no API key is set). Resolves with whether it started.
+   */
+  const startRecording = useCallback(async () => {
+    setStartError(null);
+    try {
+      await invoke("start_recording");
+      return true;
+    } catch (err) {
+      setStartError(typeof err === "string" ? err : "Could not start recording.");
+      return false;
+    }
+  }, []);
+
+  /**
+   * Function: stopRecording
+   * Stops the dictation and waits for finalization and delivery.
+   * Resolves with the delivered transcript, or null if nothing was recording.
+   */
+  const stopRecording = useCallback(async () => {
+    try {
+      const delivery = await invoke<Delivery>("stop_recording");
+      return delivery.text;
+    } catch (err) {
+      console.error("Failed to stop recording:", err);
+      return null;
+    }
+  }, []);
+
+  /**
+   * Function: cancelRecording
+   * Abandons the dictation without delivering anything.
+   */
+  const cancelRecording = useCallback(async () => {
+    await invoke("cancel_recording").catch((err) =>
+      console.error("Failed to cancel recording:", err)
+    );
+  }, []);
+
+  /**
+   * Function: resetTranscript
+   * Responsibility: Clears the accumulated transcript.
+   */
+  const resetTranscript = useCallback(() => {
+    setRealtimeTranscript("");
+  }, []);
+
+  const { state } = recordingState;
+  const isRecording = state === "arming" || state === "recording";
+  const error =
+    recordingState.state === "error" ? recordingState.message : startError;
+  const connectionState: TranscriptionConnectionState =
+    state === "error" || (state === "idle" && startError)
+      ? "error"
+      : state === "recording" && !reconnecting
+        ? "connected"
+        : state === "idle"
+          ? "closed"
+          : "connecting";
+
+  return {
+    startRecording,
+    stopRecording,
+    cancelRecording,
+    resetTranscript,
+    recordingState,
+    connectionState,
+    realtimeTranscript,
+    isRecording,
+    error,
+  };
+}